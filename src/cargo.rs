@@ -3,7 +3,7 @@ use reqwest::Client;
 use serde::Deserialize;
 
 use crate::config::Config;
-use crate::error::Error;
+use crate::error::{self, Error, Kind};
 use crate::VERSION;
 
 // CRATES.IO URLS
@@ -51,9 +51,13 @@ pub async fn get_latest_version(config: Config) -> Result<String, Error> {
         let cr: CargoResponse = serde_json::from_str(&response.text().await?)?;
         Ok(cr.versions.first().unwrap().num.clone())
     } else {
+        let status = response.status().as_u16();
         let message = format!("Error: {:#?}", response.text().await);
-        let source = "get_latest_version response failure".to_string();
-        Err(Error { message, source })
+        Err(error::with_kind(
+            Kind::Api { status },
+            "get_latest_version response failure",
+            &message,
+        ))
     }
 }
 #[cfg(test)]
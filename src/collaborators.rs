@@ -0,0 +1,66 @@
+use serde::Deserialize;
+
+use crate::error::{self, Error, Kind};
+
+// People a shared project has been shared with
+#[derive(PartialEq, Eq, Deserialize, Clone, Debug)]
+pub struct Collaborator {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+pub fn json_to_collaborators(json: String) -> Result<Vec<Collaborator>, Error> {
+    let collaborators: Vec<Collaborator> = serde_json::from_str(&json)?;
+    Ok(collaborators)
+}
+
+/// Finds a collaborator by name or email, ignoring case
+pub fn by_name(collaborators: &[Collaborator], name: &str) -> Result<Collaborator, Error> {
+    let name = name.trim().to_lowercase();
+    collaborators
+        .iter()
+        .find(|c| c.name.to_lowercase() == name || c.email.to_lowercase() == name)
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<&str> = collaborators.iter().map(|c| c.name.as_str()).collect();
+            error::with_kind(
+                Kind::NotFound,
+                "assign",
+                &format!(
+                    "Could not find '{name}' in the project, choose from: {}",
+                    names.join(", ")
+                ),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn by_name_ignores_case_and_accepts_email() {
+        let collaborators =
+            json_to_collaborators(test::responses::collaborators()).expect("valid collaborators");
+
+        assert_eq!(
+            by_name(&collaborators, "alice").map(|c| c.id),
+            Ok(String::from("2671362"))
+        );
+        assert_eq!(
+            by_name(&collaborators, "BOB@example.com").map(|c| c.id),
+            Ok(String::from("2671366"))
+        );
+        assert_eq!(
+            by_name(&collaborators, "carol"),
+            Err(error::with_kind(
+                Kind::NotFound,
+                "assign",
+                "Could not find 'carol' in the project, choose from: Alice, Bob"
+            ))
+        );
+    }
+}
@@ -1,4 +1,133 @@
 use colored::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use crate::error::{self, Error, Kind};
+use crate::tasks::priority::Priority;
+
+/// Levels of each channel in the 6x6x6 cube of the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Replacement colors from the colors block in config, each a name such as "bright blue" or a number from 0 to 255
+#[derive(Default, Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Colors {
+    pub priority_high: Option<String>,
+    pub priority_medium: Option<String>,
+    pub priority_low: Option<String>,
+    /// Used for confirmations and list titles
+    pub success: Option<String>,
+    /// Used for group and table headers
+    pub header: Option<String>,
+}
+
+impl Colors {
+    /// Errors on the first value that is not a color
+    pub fn validate(&self) -> Result<(), Error> {
+        [
+            &self.priority_high,
+            &self.priority_medium,
+            &self.priority_low,
+            &self.success,
+            &self.header,
+        ]
+        .into_iter()
+        .flatten()
+        .try_for_each(|name| parse(name).map(|_| ()))
+    }
+
+    fn for_priority(&self, priority: &Priority) -> &Option<String> {
+        match priority {
+            Priority::High => &self.priority_high,
+            Priority::Medium => &self.priority_medium,
+            Priority::Low => &self.priority_low,
+            Priority::None => &None,
+        }
+    }
+}
+
+/// The configured color picked out of the colors block, when there is one and it parses
+fn configured(colors: &Option<Colors>, pick: impl Fn(&Colors) -> &Option<String>) -> Option<Color> {
+    let name = pick(colors.as_ref()?).as_deref()?;
+    parse(name).ok()
+}
+
+/// Reads a color name such as "bright_blue", or a number from the 256 color palette
+pub fn parse(name: &str) -> Result<Color, Error> {
+    let normalized = name.trim().replace(['_', '-'], " ");
+    if let Ok(color) = Color::from_str(&normalized) {
+        return Ok(color);
+    }
+
+    match normalized.parse::<u8>() {
+        Ok(number) => Ok(from_ansi_256(number)),
+        Err(_) => Err(error::with_kind(
+            Kind::Config,
+            "colors",
+            &format!("'{name}' is not a color, use a name such as \"bright blue\" or a number from 0 to 255"),
+        )),
+    }
+}
+
+/// The first 16 are the named colors, the rest are a color cube followed by a grey ramp
+fn from_ansi_256(number: u8) -> Color {
+    const NAMED: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::BrightBlack,
+        Color::BrightRed,
+        Color::BrightGreen,
+        Color::BrightYellow,
+        Color::BrightBlue,
+        Color::BrightMagenta,
+        Color::BrightCyan,
+        Color::BrightWhite,
+    ];
+
+    match number {
+        0..=15 => NAMED[number as usize],
+        16..=231 => {
+            let index = (number - 16) as usize;
+            Color::TrueColor {
+                r: CUBE_LEVELS[index / 36],
+                g: CUBE_LEVELS[(index / 6) % 6],
+                b: CUBE_LEVELS[index % 6],
+            }
+        }
+        _ => {
+            let level = 8 + 10 * (number - 232);
+            Color::TrueColor {
+                r: level,
+                g: level,
+                b: level,
+            }
+        }
+    }
+}
+
+/// Turns off all colored output when NO_COLOR is set, see https://no-color.org
+/// Should be called once at startup
+pub fn disable_if_no_color() {
+    if is_no_color(env::var_os("NO_COLOR")) {
+        colored::control::set_override(false);
+    }
+}
+
+/// NO_COLOR counts as set when it is present and not an empty string
+fn is_no_color(no_color: Option<OsString>) -> bool {
+    match no_color {
+        None => false,
+        Some(value) => !value.is_empty(),
+    }
+}
 
 pub fn green_string(str: &str) -> String {
     if cfg!(test) {
@@ -8,6 +137,24 @@ pub fn green_string(str: &str) -> String {
     String::from(str).green().to_string()
 }
 
+/// For confirmations and list titles, green unless the colors block in config says otherwise
+pub fn success_string(str: &str, colors: &Option<Colors>) -> String {
+    if cfg!(test) {
+        return normal_string(str);
+    }
+
+    match configured(colors, |colors| &colors.success) {
+        Some(color) => String::from(str).color(color).to_string(),
+        None => green_string(str),
+    }
+}
+
+/// Removes color codes and terminal hyperlinks, leaving the text as it would be read on screen
+pub fn strip_ansi(str: &str) -> String {
+    let escapes = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]|\x1b\]8;;[^\x07]*\x07").unwrap();
+    escapes.replace_all(str, "").into_owned()
+}
+
 pub fn red_string(str: &str) -> String {
     if cfg!(test) {
         return normal_string(str);
@@ -24,6 +171,35 @@ pub fn cyan_string(str: &str) -> String {
     String::from(str).bright_cyan().to_string()
 }
 
+/// For group and table headers, cyan unless the colors block in config says otherwise
+pub fn header_string(str: &str, colors: &Option<Colors>) -> String {
+    if cfg!(test) {
+        return normal_string(str);
+    }
+
+    match configured(colors, |colors| &colors.header) {
+        Some(color) => String::from(str).color(color).to_string(),
+        None => cyan_string(str),
+    }
+}
+
+/// Content colored by priority, using the colors block in config when it has one for the priority
+pub fn priority_string(str: &str, priority: &Priority, colors: &Option<Colors>) -> String {
+    if cfg!(test) {
+        return normal_string(str);
+    }
+
+    match configured(colors, |colors| colors.for_priority(priority)) {
+        Some(color) => String::from(str).color(color).to_string(),
+        None => match priority {
+            Priority::Low => blue_string(str),
+            Priority::Medium => yellow_string(str),
+            Priority::High => red_string(str),
+            Priority::None => normal_string(str),
+        },
+    }
+}
+
 pub fn purple_string(str: &str) -> String {
     if cfg!(test) {
         return normal_string(str);
@@ -48,6 +224,15 @@ pub fn yellow_string(str: &str) -> String {
     String::from(str).yellow().to_string()
 }
 
+/// A single block in the given RGB color
+pub fn swatch_string(red: u8, green: u8, blue: u8) -> String {
+    if cfg!(test) {
+        return normal_string("■");
+    }
+
+    String::from("■").truecolor(red, green, blue).to_string()
+}
+
 pub fn debug_string(str: &str) -> String {
     if cfg!(test) {
         return normal_string(str);
@@ -59,3 +244,107 @@ pub fn debug_string(str: &str) -> String {
 pub fn normal_string(str: &str) -> String {
     String::from(str).normal().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_is_no_color() {
+        assert!(!is_no_color(None));
+        assert!(!is_no_color(Some(OsString::from(""))));
+        assert!(is_no_color(Some(OsString::from("1"))));
+    }
+
+    #[test]
+    fn strip_ansi_removes_colors_and_links() {
+        assert_eq!(
+            strip_ansi("\x1b[1;31mDue\x1b[0m \x1b]8;;https://todoist.com\x07[Todoist]\x1b]8;;\x07"),
+            "Due [Todoist]"
+        );
+    }
+
+    #[test]
+    fn parse_reads_names_and_numbers() {
+        assert_eq!(parse("bright_blue"), Ok(Color::BrightBlue));
+        assert_eq!(parse("Magenta"), Ok(Color::Magenta));
+        assert_eq!(parse("9"), Ok(Color::BrightRed));
+        assert_eq!(
+            parse("208"),
+            Ok(Color::TrueColor {
+                r: 255,
+                g: 135,
+                b: 0
+            })
+        );
+        assert_eq!(
+            parse("244"),
+            Ok(Color::TrueColor {
+                r: 128,
+                g: 128,
+                b: 128
+            })
+        );
+        assert_eq!(parse("plaid").unwrap_err().source, "colors");
+        assert_eq!(parse("256").unwrap_err().source, "colors");
+    }
+
+    #[test]
+    fn validate_rejects_unknown_names() {
+        let colors = Colors {
+            priority_high: Some(String::from("orange")),
+            success: Some(String::from("bright green")),
+            ..Colors::default()
+        };
+        assert_eq!(
+            colors.validate().unwrap_err().message,
+            "'orange' is not a color, use a name such as \"bright blue\" or a number from 0 to 255"
+        );
+        assert_eq!(Colors::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn configured_picks_colors_from_config() {
+        let colors = Some(Colors {
+            priority_high: Some(String::from("bright_magenta")),
+            header: Some(String::from("208")),
+            ..Colors::default()
+        });
+        fn high(colors: &Colors) -> &Option<String> {
+            colors.for_priority(&Priority::High)
+        }
+        fn low(colors: &Colors) -> &Option<String> {
+            colors.for_priority(&Priority::Low)
+        }
+
+        assert_eq!(configured(&colors, high), Some(Color::BrightMagenta));
+        assert_eq!(configured(&colors, low), None);
+        assert_eq!(configured(&None, high), None);
+        assert_eq!(
+            configured(&colors, |colors| &colors.header),
+            Some(Color::TrueColor {
+                r: 255,
+                g: 135,
+                b: 0
+            })
+        );
+    }
+
+    #[test]
+    fn no_color_should_return_plain_text() {
+        env::set_var("NO_COLOR", "1");
+        disable_if_no_color();
+
+        assert_eq!(red_string("hello"), String::from("hello"));
+        assert_eq!(green_string("hello"), String::from("hello"));
+        assert_eq!(
+            String::from("hello").red().to_string(),
+            String::from("hello")
+        );
+        assert_eq!(
+            String::from("hello").green().to_string(),
+            String::from("hello")
+        );
+    }
+}
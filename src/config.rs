@@ -1,11 +1,19 @@
 use crate::cargo::Version;
+use crate::collaborators::Collaborator;
+use crate::color::Colors;
 use crate::error::{self, Error};
 use crate::projects::Project;
+use crate::tasks::priority;
 use crate::{cargo, color, input, time, todoist, VERSION};
-use chrono_tz::TZ_VARIANTS;
 use rand::distributions::{Alphanumeric, DistString};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc::UnboundedSender;
@@ -22,6 +30,14 @@ pub struct Config {
     pub path: String,
     /// The ID of the next task
     pub next_id: Option<String>,
+    /// The ID of the last task completed, so that it can be undone
+    pub last_completed_id: Option<String>,
+    /// The ID of the project picked last, offered first when prompting for a project
+    pub last_project: Option<String>,
+    /// Whether "monday 3pm" on a Monday after 3pm means next week rather than earlier today
+    pub roll_past_weekday_times: Option<bool>,
+    /// The account's inbox, so that `--project inbox` works without importing it
+    pub inbox: Option<Project>,
     pub timezone: Option<String>,
     pub timeout: Option<u64>,
     /// The last time we checked crates.io for the version
@@ -29,15 +45,41 @@ pub struct Config {
     pub mock_url: Option<String>,
     pub mock_string: Option<String>,
     pub mock_select: Option<usize>,
+    pub mock_confirm: Option<bool>,
+    pub mock_multiselect: Option<Vec<usize>>,
     /// Whether spinners are enabled
     pub spinners: Option<bool>,
     #[serde(default = "default_disable_links")]
     pub disable_links: bool,
+    /// Show the task URL in single task views such as `task next`
+    pub show_urls: Option<bool>,
+    /// Show how many tasks are left after the task in `task next`
+    pub show_remaining: Option<bool>,
+    /// How many of the following tasks to list after the next task
+    pub preview: Option<u8>,
+    /// Priority from 1 to 4 for `task create` to use in a project, keyed by project name
+    pub project_priorities: Option<HashMap<String, u8>>,
+    /// The order priorities are listed in when prompting, from 1 (without priority) to 4 (highest)
+    pub priority_prompt_order: Option<Vec<u8>>,
+    /// Tasks with any of these labels are never returned by `task next`
+    pub exclude_labels: Option<Vec<String>>,
     pub verbose: Option<bool>,
     /// Don't ask for sections
     pub no_sections: Option<bool>,
     /// Goes straight to natural language input in datetime selection
     pub natural_language_only: Option<bool>,
+    /// Ask for a description in task create when --description isn't given
+    pub always_prompt_description: Option<bool>,
+    /// Language that Todoist reads natural language dates in, i.e. "de". Defaults to "en"
+    pub language: Option<String>,
+    /// The hour of the day that the day starts at, for those that work past midnight
+    pub day_start_hour: Option<u8>,
+    /// Command to run when tod is called without arguments, i.e. "task next --project Work"
+    pub default_command: Option<String>,
+    /// Format tried before YYYY-MM-DD when reading dates, i.e. "%d-%m-%Y"
+    pub date_input_format: Option<String>,
+    /// Colors to use instead of the defaults for priorities, confirmations, and headers
+    pub colors: Option<Colors>,
     pub sort_value: Option<SortValue>,
 
     /// For storing arguments from the commandline
@@ -49,6 +91,14 @@ pub struct Config {
     pub internal: Internal,
 }
 
+/// Bump when exported settings change in a way that older exports need converting on import
+const EXPORT_VERSION: u64 = 1;
+/// Settings that only make sense on the machine that wrote them, never exported or imported
+const MACHINE_KEYS: [&str; 2] = ["path", "last_version_check"];
+/// How many times to try writing the config file before giving up
+const SAVE_ATTEMPTS: u32 = 3;
+const SAVE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
 fn default_disable_links() -> bool {
     false
 }
@@ -57,11 +107,21 @@ fn default_disable_links() -> bool {
 pub struct Args {
     pub verbose: bool,
     pub timeout: Option<u64>,
+    pub quiet: bool,
+    pub dry_run: bool,
+    /// Answer yes to every confirmation without prompting
+    pub yes: bool,
+    /// Used instead of the timezone in config without being saved
+    pub timezone: Option<String>,
 }
 
 #[derive(Default, Clone, Debug)]
 pub struct Internal {
     pub tx: Option<UnboundedSender<Error>>,
+    /// Names of the people tasks can be assigned to, keyed by user id
+    pub collaborators: HashMap<String, String>,
+    /// Set by Ctrl-C so that interactive loops can stop between tasks
+    pub cancelled: Arc<AtomicBool>,
 }
 
 // Determining how
@@ -78,6 +138,13 @@ pub struct SortValue {
     pub overdue: u8,
     /// Happens now plus or minus 15min
     pub now: u8,
+    /// Percentage of the overdue points that overdue recurring tasks get
+    #[serde(default = "default_recurring_overdue_weight")]
+    pub recurring_overdue_weight: u8,
+}
+
+fn default_recurring_overdue_weight() -> u8 {
+    50
 }
 
 impl Default for SortValue {
@@ -92,6 +159,7 @@ impl Default for SortValue {
             not_recurring: 50,
             today: 100,
             now: 200,
+            recurring_overdue_weight: default_recurring_overdue_weight(),
         }
     }
 }
@@ -110,7 +178,34 @@ impl Config {
 
         self.projects = Some(updated_projects);
 
-        Ok(color::green_string("✓"))
+        Ok(color::success_string("✓", &self.colors))
+    }
+
+    /// Keep collaborator names around so assigned tasks show a name instead of a uid
+    pub fn with_collaborators(self, collaborators: &[Collaborator]) -> Config {
+        let mut internal = self.internal;
+        internal
+            .collaborators
+            .extend(collaborators.iter().map(|c| (c.id.clone(), c.name.clone())));
+        Config { internal, ..self }
+    }
+
+    /// Marks the config as cancelled on Ctrl-C instead of killing the process
+    pub fn cancel_on_ctrl_c(&self) {
+        let cancelled = self.internal.cancelled.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        });
+    }
+
+    pub fn cancel(&self) {
+        self.internal.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.internal.cancelled.load(Ordering::SeqCst)
     }
 
     /// Fetches a sender for the error channel
@@ -135,10 +230,7 @@ impl Config {
                         VERSION,
                         color::cyan_string("cargo install tod --force")
                     );
-                    self.tx().send(Error {
-                        message,
-                        source: String::from("Crates.io"),
-                    })?;
+                    self.tx().send(error::new("Crates.io", &message))?;
                     new_config.clone().save().await?;
                 }
                 Ok(Version::Latest) => (),
@@ -158,16 +250,7 @@ impl Config {
     }
 
     pub async fn set_timezone(self) -> Result<Config, Error> {
-        let desc =
-            "Please select your timezone. This should match your Timezone setting within Todoist.";
-        let mut options = TZ_VARIANTS
-            .to_vec()
-            .iter()
-            .map(|v| v.to_string())
-            .collect::<Vec<String>>();
-        options.sort();
-
-        let tz = input::select(desc, options, self.mock_select)?;
+        let tz = self.prompt_timezone()?;
         let config = Config {
             timezone: Some(tz),
             ..self
@@ -178,12 +261,73 @@ impl Config {
         Ok(config)
     }
 
+    /// Asks a yes/no question, or answers yes straight away when --yes was given
+    pub fn confirm(&self, desc: &str, default: bool) -> Result<bool, Error> {
+        if self.args.yes {
+            return Ok(true);
+        }
+        input::confirm(desc, default, self.mock_confirm)
+    }
+
+    /// Asks for a timezone, fuzzy matching what was typed
+    pub fn prompt_timezone(&self) -> Result<String, Error> {
+        let desc =
+            "Please enter your timezone, i.e. \"pacific\". This should match your Timezone setting within Todoist.";
+        let query = input::string(desc, self.mock_string.clone())?;
+        let mut options = time::fuzzy_timezones(&query)?;
+
+        if options.len() == 1 {
+            Ok(options.remove(0))
+        } else {
+            input::select("Select your timezone", options, self.mock_select)
+        }
+    }
+
+    /// The language natural language dates are written in
+    pub fn due_lang(&self) -> String {
+        self.language.clone().unwrap_or_else(|| String::from("en"))
+    }
+
     pub fn clear_next_id(self) -> Config {
         let next_id: Option<String> = None;
 
         Config { next_id, ..self }
     }
 
+    pub fn set_last_completed_id(self, task_id: &str) -> Config {
+        let last_completed_id: Option<String> = Some(task_id.to_owned());
+
+        Config {
+            last_completed_id,
+            ..self
+        }
+    }
+
+    pub fn clear_last_completed_id(self) -> Config {
+        let last_completed_id: Option<String> = None;
+
+        Config {
+            last_completed_id,
+            ..self
+        }
+    }
+
+    pub fn set_last_project(self, project: &Project) -> Config {
+        let last_project: Option<String> = Some(project.id.to_owned());
+
+        Config {
+            last_project,
+            ..self
+        }
+    }
+
+    pub fn set_inbox(self, inbox: &Project) -> Config {
+        Config {
+            inbox: Some(inbox.to_owned()),
+            ..self
+        }
+    }
+
     pub async fn create(self) -> Result<Config, Error> {
         let json = json!(self).to_string();
         let mut file = fs::File::create(&self.path).await?;
@@ -200,6 +344,7 @@ impl Config {
             .read_to_string(&mut json)
             .await?;
         let config = serde_json::from_str::<Config>(&json)?;
+        config.validate()?;
 
         match config.sort_value {
             None => Ok(Config {
@@ -215,22 +360,47 @@ impl Config {
             path: generate_path().await?,
             token: String::from(token),
             next_id: None,
+            last_completed_id: None,
+            last_project: None,
+            inbox: None,
+            roll_past_weekday_times: None,
             last_version_check: None,
             timeout: None,
             sort_value: Some(SortValue::default()),
             timezone: None,
             disable_links: false,
+            show_urls: None,
+            show_remaining: None,
+            preview: None,
+            exclude_labels: None,
+            project_priorities: None,
+            priority_prompt_order: None,
             spinners: Some(true),
             mock_url: None,
             no_sections: None,
             natural_language_only: None,
+            always_prompt_description: None,
+            language: None,
+            day_start_hour: None,
+            default_command: None,
+            date_input_format: None,
+            colors: None,
             mock_string: None,
             mock_select: None,
+            mock_confirm: None,
+            mock_multiselect: None,
             verbose: None,
-            internal: Internal { tx: Some(tx) },
+            internal: Internal {
+                tx: Some(tx),
+                ..Internal::default()
+            },
             args: Args {
                 verbose: false,
                 timeout: None,
+                quiet: false,
+                dry_run: false,
+                yes: false,
+                timezone: None,
             },
             projects: Some(Vec::new()),
         })
@@ -267,8 +437,15 @@ impl Config {
     }
 
     pub async fn save(&mut self) -> std::result::Result<String, Error> {
-        // We don't want to overwrite verbose in the config
+        // We don't want to overwrite verbose in the config, or timezone when it came from --timezone
         let config = match Config::load(&self.path).await {
+            Ok(Config {
+                verbose, timezone, ..
+            }) if self.args.timezone.is_some() => Config {
+                verbose,
+                timezone,
+                ..self.clone()
+            },
             Ok(Config { verbose, .. }) => Config {
                 verbose,
                 ..self.clone()
@@ -278,16 +455,84 @@ impl Config {
 
         let json = json!(config);
         let string = serde_json::to_string_pretty(&json)?;
-        fs::OpenOptions::new()
-            .write(true)
-            .read(true)
-            .truncate(true)
-            .open(&self.path)
-            .await?
-            .write_all(string.as_bytes())
-            .await?;
+        write_atomically(&self.path, &string).await?;
+
+        Ok(color::success_string("✓", &config.colors))
+    }
+
+    /// Checks the values that serde can't
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(format) = &self.date_input_format {
+            time::validate_date_format(format)?;
+        }
+
+        if let Some(colors) = &self.colors {
+            colors.validate()?;
+        }
+
+        if let Some(order) = &self.priority_prompt_order {
+            priority::validate_prompt_order(order)?;
+        }
 
-        Ok(color::green_string("✓"))
+        Ok(())
+    }
+
+    /// Settings and projects as JSON for importing on another machine, the token is left out unless asked for
+    pub fn export(&self, include_token: bool) -> Result<String, Error> {
+        let mut settings = json!(self);
+        if let Some(map) = settings.as_object_mut() {
+            map.retain(|key, value| {
+                !value.is_null()
+                    && !key.starts_with("mock_")
+                    && !MACHINE_KEYS.contains(&key.as_str())
+            });
+            if !include_token {
+                map.remove("token");
+            }
+        }
+
+        let export = json!({
+            "export_version": EXPORT_VERSION,
+            "tod_version": VERSION,
+            "config": settings
+        });
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+
+    /// Merges an export into this config, values from the export win
+    pub fn import(&self, json: &str) -> Result<Config, Error> {
+        let export: serde_json::Value = serde_json::from_str(json)?;
+        let version = export["export_version"].as_u64().ok_or_else(|| {
+            error::new(
+                "config_import",
+                "Not a tod config export, export_version is missing",
+            )
+        })?;
+        if version > EXPORT_VERSION {
+            let tod_version = export["tod_version"].as_str().unwrap_or("unknown");
+            return Err(error::new(
+                "config_import",
+                &format!("The export is from a newer version of tod ({tod_version}), update tod to import it"),
+            ));
+        }
+        let settings = export["config"]
+            .as_object()
+            .ok_or_else(|| error::new("config_import", "The export does not contain a config"))?;
+
+        let mut merged = json!(self);
+        for (key, value) in settings {
+            if !MACHINE_KEYS.contains(&key.as_str()) {
+                merged[key] = value.clone();
+            }
+        }
+        let config: Config = serde_json::from_value(merged)?;
+        config.validate()?;
+
+        Ok(Config {
+            args: self.args.clone(),
+            internal: self.internal.clone(),
+            ..config
+        })
     }
 
     pub fn set_next_id(&self, next_id: &String) -> Config {
@@ -302,14 +547,10 @@ impl Config {
 
 pub async fn get_or_create(
     config_path: Option<String>,
-    verbose: bool,
-    timeout: Option<u64>,
+    args: Args,
     tx: UnboundedSender<Error>,
 ) -> Result<Config, Error> {
-    let path: String = match config_path {
-        None => generate_path().await?,
-        Some(path) => maybe_expand_home_dir(path)?,
-    };
+    let path = resolve_path(config_path).await?;
 
     match fs::File::open(&path).await {
         Ok(_) => Config::load(&path).await,
@@ -321,13 +562,74 @@ pub async fn get_or_create(
             Config::new(&token, tx.clone()).await?.create().await
         }
     }
-    .map(|config| Config {
-        args: Args { timeout, verbose },
-        internal: Internal { tx: Some(tx) },
-        ..config
+    .and_then(|config| {
+        let timezone = match &args.timezone {
+            Some(timezone) => {
+                time::timezone_from_str(&args.timezone)?;
+                Some(timezone.clone())
+            }
+            None => config.timezone,
+        };
+
+        Ok(Config {
+            timezone,
+            args,
+            internal: Internal {
+                tx: Some(tx),
+                ..Internal::default()
+            },
+            ..config
+        })
     })
 }
 
+/// The absolute path of the config file, from --config if given or the config directory otherwise.
+/// Does not read or create the file.
+pub async fn resolve_path(config_path: Option<String>) -> Result<String, Error> {
+    let path = match config_path {
+        None => generate_path().await?,
+        Some(path) => maybe_expand_home_dir(path)?,
+    };
+
+    let path = PathBuf::from(path);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    path.to_str()
+        .map(String::from)
+        .ok_or_else(|| error::new("resolve_path", "Could not convert path to string"))
+}
+
+/// The path of a named profile, $XDG_CONFIG_HOME/tod/NAME.cfg. Creates the tod directory so setup
+/// can write the file there.
+pub async fn profile_path(name: &str) -> Result<String, Error> {
+    let config_directory =
+        dirs::config_dir().ok_or_else(|| error::new("dirs", "Could not find config directory"))?;
+    profile_path_in(&config_directory, name).await
+}
+
+async fn profile_path_in(config_directory: &Path, name: &str) -> Result<String, Error> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) {
+        return Err(error::with_kind(
+            error::Kind::Config,
+            "profile_path",
+            &format!("Invalid profile name '{name}', use letters, numbers, - and _"),
+        ));
+    }
+    let config_directory = config_directory.join("tod");
+    fs::create_dir_all(&config_directory).await?;
+
+    config_directory
+        .join(format!("{name}.cfg"))
+        .to_str()
+        .map(String::from)
+        .ok_or_else(|| error::new("profile_path", "Could not convert path to string"))
+}
+
 pub async fn generate_path() -> Result<String, Error> {
     let config_directory = dirs::config_dir()
         .ok_or_else(|| error::new("dirs", "Could not find config directory"))?
@@ -343,6 +645,72 @@ pub async fn generate_path() -> Result<String, Error> {
     }
 }
 
+/// Writes to a temporary file beside path and renames it into place, so an interrupted save
+/// never leaves a partial config. Retries when the file is briefly locked, i.e. by a sync client.
+/// A symlinked config is replaced at the file it points to, keeping that file's permissions.
+async fn write_atomically(path: &str, contents: &str) -> Result<(), Error> {
+    let target = match fs::canonicalize(path).await {
+        Ok(target) => target,
+        Err(e) if e.kind() == ErrorKind::NotFound => PathBuf::from(path),
+        Err(e) => return Err(Error::from(e)),
+    };
+    let permissions = match fs::metadata(&target).await {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::from(e)),
+    };
+    // Unique so that two saves at once don't write into the same temporary file
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = target.with_file_name(format!("{file_name}.{}.tmp", uuid::Uuid::new_v4()));
+
+    let mut attempt = 1;
+    loop {
+        let result = match write_private(&temp_path, contents, &permissions).await {
+            Ok(()) => fs::rename(&temp_path, &target).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < SAVE_ATTEMPTS && is_transient(&e) => {
+                attempt += 1;
+                tokio::time::sleep(SAVE_RETRY_DELAY).await;
+            }
+            Err(e) => {
+                _ = fs::remove_file(&temp_path).await;
+                return Err(Error::from(e));
+            }
+        }
+    }
+}
+
+/// New files are only readable by the user as the config holds the API token
+async fn write_private(
+    path: &Path,
+    contents: &str,
+    permissions: &Option<std::fs::Permissions>,
+) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path).await?;
+    file.write_all(contents.as_bytes()).await?;
+    file.sync_all().await?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions.clone()).await?;
+    }
+    Ok(())
+}
+
+fn is_transient(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ResourceBusy | ErrorKind::PermissionDenied
+    )
+}
+
 fn maybe_expand_home_dir(path: String) -> Result<String, Error> {
     if path.starts_with('~') {
         let home = homedir::get_my_home()?
@@ -387,6 +755,22 @@ mod tests {
                 ..self
             }
         }
+
+        /// Mock out the answer to a yes/no prompt
+        pub fn mock_confirm(self, answer: bool) -> Config {
+            Config {
+                mock_confirm: Some(answer),
+                ..self
+            }
+        }
+
+        /// Mock out a multi-select prompt, setting the indexes of the ticked options
+        pub fn mock_multiselect(self, indexes: Vec<usize>) -> Config {
+            Config {
+                mock_multiselect: Some(indexes),
+                ..self
+            }
+        }
     }
 
     use crate::test;
@@ -399,6 +783,114 @@ mod tests {
         tx
     }
 
+    #[tokio::test]
+    async fn export_then_import_keeps_projects_and_settings() {
+        let exported = Config {
+            preview: Some(3),
+            exclude_labels: Some(vec![String::from("waiting")]),
+            ..test::fixtures::config().await
+        };
+        let json = exported.export(false).unwrap();
+        assert!(!json.contains(&exported.token));
+        assert!(!json.contains(&exported.path));
+
+        let other_machine = Config {
+            token: String::from("other-token"),
+            projects: None,
+            preview: Some(1),
+            ..test::fixtures::config().await
+        };
+        let imported = other_machine.import(&json).unwrap();
+
+        assert_eq!(imported.projects, exported.projects);
+        assert_eq!(imported.preview, Some(3));
+        assert_eq!(imported.exclude_labels, exported.exclude_labels);
+        assert_eq!(imported.timezone, exported.timezone);
+        assert_eq!(imported.token, String::from("other-token"));
+        assert_eq!(imported.path, other_machine.path);
+
+        let with_token = other_machine
+            .import(&exported.export(true).unwrap())
+            .unwrap();
+        assert_eq!(with_token.token, exported.token);
+    }
+
+    #[tokio::test]
+    async fn import_rejects_newer_exports() {
+        let config = test::fixtures::config().await;
+        let json = r#"{"export_version": 99, "tod_version": "9.0.0", "config": {}}"#;
+        assert_eq!(
+            config.import(json).unwrap_err(),
+            error::new(
+                "config_import",
+                "The export is from a newer version of tod (9.0.0), update tod to import it"
+            )
+        );
+        assert_eq!(
+            config.import("{}").unwrap_err().source,
+            String::from("config_import")
+        );
+    }
+
+    #[tokio::test]
+    async fn write_atomically_replaces_the_whole_file() {
+        let path = generate_path().await.unwrap();
+        fs::write(&path, "a longer config that would leave a tail behind")
+            .await
+            .unwrap();
+
+        write_atomically(&path, "{}").await.unwrap();
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), "{}");
+
+        let file_name = PathBuf::from(&path).file_name().unwrap().to_owned();
+        let leftovers = std::fs::read_dir("tests")
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| {
+                name.starts_with(&*file_name.to_string_lossy()) && name.ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+
+        fs::remove_file(&path).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn write_atomically_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = |path: &str| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let new_path = generate_path().await.unwrap();
+        write_atomically(&new_path, "{}").await.unwrap();
+        assert_eq!(mode(&new_path), 0o600);
+
+        let target = generate_path().await.unwrap();
+        fs::write(&target, "{}").await.unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let link = generate_path().await.unwrap();
+        std::os::unix::fs::symlink(fs::canonicalize(&target).await.unwrap(), &link).unwrap();
+
+        write_atomically(&link, "{\"a\": 1}").await.unwrap();
+        assert!(fs::symlink_metadata(&link).await.unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&target).await.unwrap(), "{\"a\": 1}");
+        assert_eq!(mode(&target), 0o640);
+
+        for path in [new_path, target, link] {
+            fs::remove_file(path).await.unwrap();
+        }
+    }
+
+    #[test]
+    fn locked_files_are_transient() {
+        assert!(is_transient(&std::io::Error::from(
+            ErrorKind::PermissionDenied
+        )));
+        assert!(is_transient(&std::io::Error::from(ErrorKind::ResourceBusy)));
+        assert!(!is_transient(&std::io::Error::from(ErrorKind::NotFound)));
+    }
+
     #[tokio::test]
     async fn new_should_generate_config() {
         let config = Config::new("something", tx()).await.unwrap();
@@ -417,6 +909,48 @@ mod tests {
         config.reload().await.expect("Failed to reload config");
     }
 
+    #[tokio::test]
+    async fn resolve_path_should_use_override() {
+        let path = resolve_path(Some(String::from("/tmp/tod_override.cfg"))).await;
+        assert_eq!(path, Ok(String::from("/tmp/tod_override.cfg")));
+
+        let path = resolve_path(Some(String::from("~/tod_override.cfg")))
+            .await
+            .unwrap();
+        let home = homedir::get_my_home().unwrap().unwrap();
+        assert_eq!(path, format!("{}/tod_override.cfg", home.display()));
+
+        let path = resolve_path(Some(String::from("relative.cfg")))
+            .await
+            .unwrap();
+        let current_dir = std::env::current_dir().unwrap();
+        assert_eq!(path, format!("{}/relative.cfg", current_dir.display()));
+    }
+
+    #[tokio::test]
+    async fn resolve_path_should_default_without_override() {
+        let path = resolve_path(None).await.unwrap();
+        assert!(PathBuf::from(&path).is_absolute());
+        assert!(path.ends_with(".testcfg"));
+        assert!(fs::File::open(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn profile_path_should_be_in_tod_config_directory() {
+        let config_dir = std::env::temp_dir().join(format!("tod_profile_{}", uuid::Uuid::new_v4()));
+        let expected = config_dir.join("tod").join("work_2-b.cfg");
+        assert_eq!(
+            profile_path_in(&config_dir, "work_2-b").await.unwrap(),
+            expected.to_str().unwrap()
+        );
+        assert!(config_dir.join("tod").is_dir());
+
+        for name in ["", "../work", ".work", "my work", "wörk"] {
+            assert!(profile_path_in(&config_dir, name).await.is_err());
+        }
+        fs::remove_dir_all(&config_dir).await.unwrap();
+    }
+
     #[tokio::test]
     async fn set_and_clear_next_id_should_work() {
         let config = test::fixtures::config().await;
@@ -490,7 +1024,7 @@ mod tests {
         assert_matches!(loaded_config.token.as_str(), "created");
 
         // get_or_create (create)
-        let config = get_or_create(None, false, None, tx())
+        let config = get_or_create(None, Args::default(), tx())
             .await
             .expect("Could not get or create");
         delete_config(&config.path).await;
@@ -503,12 +1037,12 @@ mod tests {
             .await
             .unwrap();
 
-        let config = get_or_create(None, false, None, tx()).await;
+        let config = get_or_create(None, Args::default(), tx()).await;
 
         assert_matches!(
             config,
             Ok(Config {
-                internal: Internal { tx: Some(_) },
+                internal: Internal { tx: Some(_), .. },
                 ..
             })
         );
@@ -516,6 +1050,50 @@ mod tests {
         delete_config(&config.unwrap().path).await;
     }
 
+    #[tokio::test]
+    async fn timezone_flag_overrides_config_without_saving() {
+        let config = Config {
+            timezone: Some(String::from("Pacific/Pago_Pago")),
+            ..test::fixtures::config().await
+        }
+        .create()
+        .await
+        .unwrap();
+        let args = Args {
+            timezone: Some(String::from("Pacific/Kiritimati")),
+            ..Args::default()
+        };
+
+        let mut overridden = get_or_create(Some(config.path.clone()), args, tx())
+            .await
+            .unwrap();
+        assert_eq!(
+            overridden.timezone,
+            Some(String::from("Pacific/Kiritimati"))
+        );
+
+        // The zones are 25 hours apart so they never share a date
+        let kiritimati_today = time::today_date(&overridden).unwrap();
+        assert!(time::date_is_today(kiritimati_today, &overridden).unwrap());
+        assert!(!time::date_is_today(kiritimati_today, &config).unwrap());
+
+        overridden.save().await.unwrap();
+        let saved = config.reload().await.unwrap();
+        assert_eq!(saved.timezone, Some(String::from("Pacific/Pago_Pago")));
+
+        let args = Args {
+            timezone: Some(String::from("Mars/Olympus")),
+            ..Args::default()
+        };
+        let result = get_or_create(Some(config.path.clone()), args, tx()).await;
+        assert_eq!(
+            result.unwrap_err().message,
+            "'Mars/Olympus' is not a valid timezone"
+        );
+
+        delete_config(&config.path).await;
+    }
+
     async fn delete_config(path: &str) {
         assert_matches!(fs::remove_file(path).await, Ok(_));
     }
@@ -3,9 +3,35 @@ use crate::{color, config::Config};
 // Print a debug statement if in verbose mode
 pub fn print(config: &Config, text: String) {
     if config.verbose.unwrap_or_default() || config.args.verbose {
+        let text = redact_token(&text, &config.token);
         let text = format!("=== DEBUG ===\n{}\n===", text);
         let text = color::debug_string(&text);
 
         println!("{}", text);
     }
 }
+
+/// Strips the API token out of text so that it never ends up in debug output
+fn redact_token(text: &str, token: &str) -> String {
+    if token.is_empty() {
+        return text.to_string();
+    }
+
+    text.replace(token, "[REDACTED]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_redact_token() {
+        let text = "Authorization: Bearer abc123";
+        assert_eq!(
+            redact_token(text, "abc123"),
+            String::from("Authorization: Bearer [REDACTED]")
+        );
+        assert_eq!(redact_token(text, ""), String::from(text));
+    }
+}
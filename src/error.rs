@@ -4,15 +4,53 @@ use crate::color;
 use homedir::GetHomeError;
 use serde::Deserialize;
 
+/// Source used for errors where Todoist could not be reached at all
+const NETWORK_SOURCE: &str = "network";
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Error {
     pub message: String,
     pub source: String,
+    /// What went wrong, for deciding whether to retry and which exit code to use
+    #[serde(default)]
+    pub kind: Kind,
+}
+
+/// The category of an error, independent of the message shown to the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Kind {
+    /// Todoist could not be reached at all
+    Network,
+    /// Todoist responded with an error status or something that isn't JSON
+    Api { status: u16 },
+    /// A response, date, or file could not be read
+    Parse,
+    /// A project, section, task, or file does not exist
+    NotFound,
+    /// The configuration is invalid
+    Config,
+    /// The user's input could not be used
+    Input,
+    /// The user pressed Ctrl-C at a prompt
+    Interrupted,
+    #[default]
+    Other,
+}
+
+impl Error {
+    /// True when the request never made it to Todoist, i.e. when offline
+    pub fn is_network(&self) -> bool {
+        self.kind == Kind::Network
+    }
 }
 
+impl std::error::Error for Error {}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Error { source, message } = self;
+        let Error {
+            source, message, ..
+        } = self;
         write!(
             f,
             "Error from {}:\n{}",
@@ -24,80 +62,81 @@ impl Display for Error {
 
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
-        Self {
-            source: String::from("io"),
-            message: format!("{value}"),
-        }
+        let kind = match value.kind() {
+            std::io::ErrorKind::NotFound => Kind::NotFound,
+            _ => Kind::Other,
+        };
+        with_kind(kind, "io", &format!("{value}"))
     }
 }
 
 impl From<tokio::sync::mpsc::error::SendError<Error>> for Error {
     fn from(value: tokio::sync::mpsc::error::SendError<Error>) -> Self {
-        Self {
-            source: String::from("tokio mpsc"),
-            message: format!("{value}"),
-        }
+        new("tokio mpsc", &format!("{value}"))
     }
 }
 
 impl From<chrono_tz::ParseError> for Error {
     fn from(value: chrono_tz::ParseError) -> Self {
-        Self {
-            source: String::from("chrono_tz"),
-            message: format!("{value}"),
-        }
+        with_kind(Kind::Parse, "chrono_tz", &format!("{value}"))
     }
 }
 
 impl From<chrono::ParseError> for Error {
     fn from(value: chrono::ParseError) -> Self {
-        Self {
-            source: String::from("chrono"),
-            message: format!("{value}"),
-        }
+        with_kind(Kind::Parse, "chrono", &format!("{value}"))
     }
 }
 
 impl From<GetHomeError> for Error {
     fn from(value: GetHomeError) -> Self {
-        Self {
-            source: String::from("homedir"),
-            message: format!("{value}"),
-        }
+        with_kind(Kind::Config, "homedir", &format!("{value}"))
     }
 }
 
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
-        Self {
-            source: String::from("serde_json"),
-            message: format!("{value}"),
-        }
+        with_kind(Kind::Parse, "serde_json", &format!("{value}"))
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(value: reqwest::Error) -> Self {
-        Self {
-            source: String::from("reqwest"),
-            message: format!("{value}"),
-        }
+        // Timeouts are left out as the request may still have reached Todoist
+        let (kind, source) = match value.status() {
+            _ if value.is_connect() => (Kind::Network, NETWORK_SOURCE),
+            Some(status) => (
+                Kind::Api {
+                    status: status.as_u16(),
+                },
+                "reqwest",
+            ),
+            None => (Kind::Other, "reqwest"),
+        };
+
+        with_kind(kind, source, &format!("{value}"))
     }
 }
 
 impl From<inquire::InquireError> for Error {
     fn from(value: inquire::InquireError) -> Self {
-        Self {
-            source: String::from("inquire"),
-            message: format!("{value}"),
-        }
+        let kind = match value {
+            inquire::InquireError::OperationInterrupted => Kind::Interrupted,
+            _ => Kind::Input,
+        };
+        with_kind(kind, "inquire", &format!("{value}"))
     }
 }
 
 pub fn new(source: &str, message: &str) -> Error {
+    with_kind(Kind::Other, source, message)
+}
+
+pub fn with_kind(kind: Kind, source: &str, message: &str) -> Error {
     Error {
         source: String::from(source),
         message: String::from(message),
+        kind,
     }
 }
 #[cfg(test)]
@@ -110,7 +149,30 @@ mod tests {
         let error = Error {
             message: "there".to_string(),
             source: "hello".to_string(),
+            kind: Kind::Other,
         };
         assert_eq!(error.to_string(), String::from("Error from hello:\nthere"))
     }
+
+    #[test]
+    fn conversions_keep_the_kind() {
+        let parse: Error = serde_json::from_str::<u8>("nope").unwrap_err().into();
+        assert_eq!(parse.kind, Kind::Parse);
+        assert_eq!(parse.source, "serde_json");
+
+        let missing: Error = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+        assert_eq!(missing.kind, Kind::NotFound);
+        assert_eq!(missing.to_string(), "Error from io:\nentity not found");
+
+        let network = with_kind(Kind::Network, NETWORK_SOURCE, "offline");
+        assert!(network.is_network());
+        assert!(!new("network", "not actually offline").is_network());
+    }
+
+    #[tokio::test]
+    async fn connection_failures_are_network_errors() {
+        // Nothing listens on port 1, so the connection is refused
+        let refused: Error = reqwest::get("http://127.0.0.1:1").await.unwrap_err().into();
+        assert!(refused.is_network());
+    }
 }
@@ -1,33 +1,102 @@
 use futures::future;
+use std::collections::BTreeMap;
 use tokio::task::JoinHandle;
 
 use crate::{
     color,
     config::Config,
-    error::Error,
-    input::{self, DateTimeInput},
-    tasks::{self, FormatType, Task},
+    error::{self, Error},
+    input,
+    tasks::{self, priority::Rule, FormatType, GroupBy, ListFormat, ListOptions, Processed, Task},
     todoist,
 };
 
-/// All tasks for a project
-pub async fn all_tasks(config: &Config, filter: &String) -> Result<String, Error> {
+/// All tasks for a filter
+pub async fn all_tasks(
+    config: &Config,
+    filter: &String,
+    options: &ListOptions,
+) -> Result<String, Error> {
+    let ListOptions {
+        format: list_format,
+        group_by,
+        created_since,
+        due_before,
+        stale_days,
+        untriaged,
+        ..
+    } = options;
     let tasks = todoist::tasks_for_filter(config, filter).await?;
+    let tasks = tasks::maybe_only_untriaged(tasks, config, *untriaged);
+    let tasks = match created_since {
+        Some(date) => tasks::filter_created_since(tasks, *date, config),
+        None => tasks,
+    };
+    let tasks = match due_before {
+        Some(date) => tasks::filter_due_before(tasks, *date, config),
+        None => tasks,
+    };
+    let tasks = match stale_days {
+        Some(days) => tasks::mark_stale(tasks, config, *days),
+        None => tasks,
+    };
 
     if tasks.is_empty() {
         return Ok(format!("No tasks for filter: '{filter}'"));
     }
 
-    let mut buffer = String::new();
-    buffer.push_str(&color::green_string(&format!(
-        "Tasks for filter: '{filter}'"
-    )));
+    let title = format!("Tasks for filter: '{filter}'");
+
+    match group_by {
+        None if due_before.is_some() => {
+            let tasks = tasks::sort_by_due(tasks, config);
+            Ok(tasks::format_sorted_list(
+                config,
+                &title,
+                tasks,
+                list_format,
+                true,
+            ))
+        }
+        None => Ok(tasks::format_list(config, &title, tasks, list_format, true)),
+        Some(GroupBy::Project) => Ok(format_grouped_by_project(
+            config,
+            &title,
+            tasks,
+            list_format,
+        )),
+    }
+}
 
-    for task in tasks::sort_by_datetime(tasks, config) {
-        buffer.push('\n');
-        buffer.push_str(&task.fmt(config, FormatType::List, true));
+/// Renders a section per project in config, sorted by name.
+/// Tasks from projects that are not in config go in a final "Other" section.
+fn format_grouped_by_project(
+    config: &Config,
+    title: &str,
+    tasks: Vec<Task>,
+    list_format: &ListFormat,
+) -> String {
+    let projects = config.projects.clone().unwrap_or_default();
+    let mut groups: BTreeMap<String, Vec<Task>> = BTreeMap::new();
+    let mut other: Vec<Task> = Vec::new();
+
+    for task in tasks {
+        match projects.iter().find(|p| p.id == task.project_id) {
+            Some(project) => groups.entry(project.name.clone()).or_default().push(task),
+            None => other.push(task),
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<Task>)> = groups.into_iter().collect();
+    if !other.is_empty() {
+        groups.push((String::from("Other"), other));
     }
-    Ok(buffer)
+    let groups = groups
+        .into_iter()
+        .map(|(name, tasks)| (name, tasks::sort_by_datetime(tasks, config)))
+        .collect();
+
+    tasks::format_groups(config, title, groups, list_format)
 }
 
 pub async fn rename_task(config: &Config, filter: String) -> Result<String, Error> {
@@ -43,14 +112,32 @@ pub async fn rename_task(config: &Config, filter: String) -> Result<String, Erro
     let new_task_content = input::string_with_default("Edit the task you selected:", task_content)?;
 
     if task_content == new_task_content {
-        return Ok(color::green_string(
+        return Ok(color::success_string(
             "The content is the same, no need to change it",
+            &config.colors,
         ));
     }
 
     todoist::update_task_name(config, selected_task, new_task_content).await
 }
 
+/// Choose a task from the filter and add a line to its description
+pub async fn append_task_description(
+    config: &Config,
+    filter: &str,
+    note: &str,
+) -> Result<String, Error> {
+    let filter_tasks = todoist::tasks_for_filter(config, filter).await?;
+
+    let selected_task = input::select(
+        "Choose a task of the filter:",
+        filter_tasks,
+        config.mock_select,
+    )?;
+
+    tasks::append_to_description(config, &selected_task, note).await
+}
+
 pub async fn label(config: &Config, filter: &str, labels: &Vec<String>) -> Result<String, Error> {
     let tasks = todoist::tasks_for_filter(config, filter).await?;
     let mut handles = Vec::new();
@@ -60,9 +147,10 @@ pub async fn label(config: &Config, filter: &str, labels: &Vec<String>) -> Resul
     }
 
     future::join_all(handles).await;
-    Ok(color::green_string(&format!(
-        "There are no more tasks for filter: '{filter}'"
-    )))
+    Ok(color::success_string(
+        &format!("There are no more tasks for filter: '{filter}'"),
+        &config.colors,
+    ))
 }
 
 async fn label_task(
@@ -82,53 +170,179 @@ async fn label_task(
 }
 
 /// Get the next task by priority and save its id to config
-pub async fn next_task(config: Config, filter: &str) -> Result<String, Error> {
-    match fetch_next_task(&config, filter).await {
-        Ok(Some((task, remaining))) => {
+pub async fn next_task(
+    config: Config,
+    filter: &str,
+    exclude_labels: &[String],
+    only_actionable: bool,
+) -> Result<String, Error> {
+    let tasks = fetch_next_tasks(&config, filter, exclude_labels).await?;
+    let tasks = if only_actionable {
+        tasks::filter_actionable(tasks, &config)
+    } else {
+        tasks
+    };
+    match tasks.split_first() {
+        Some((task, upcoming)) => {
             config.set_next_id(&task.id).save().await?;
             let task_string = task.fmt(&config, FormatType::Single, true);
-            Ok(format!("{task_string}\n{remaining} task(s) remaining"))
+            let url = tasks::maybe_url_line(&config, task);
+            let preview = tasks::preview_lines(&config, upcoming);
+            let remaining = tasks::maybe_remaining_line(&config, tasks.len());
+            Ok(format!("{task_string}{url}{preview}{remaining}"))
         }
-        Ok(None) => Ok(color::green_string("No tasks on list")),
-        Err(e) => Err(e),
+        None => Ok(color::success_string("No tasks on list", &config.colors)),
     }
 }
 
-async fn fetch_next_task(config: &Config, filter: &str) -> Result<Option<(Task, usize)>, Error> {
+/// Tasks for the filter, with the next task first
+async fn fetch_next_tasks(
+    config: &Config,
+    filter: &str,
+    exclude_labels: &[String],
+) -> Result<Vec<Task>, Error> {
     let tasks = todoist::tasks_for_filter(config, filter).await?;
-    let tasks = tasks::sort_by_value(tasks, config);
+    let tasks = tasks::reject_excluded_labels(tasks, config, exclude_labels);
 
-    Ok(tasks.first().map(|task| (task.to_owned(), tasks.len())))
+    Ok(tasks::sort_by_value(tasks, config))
 }
 
 /// Get next tasks and give an interactive prompt for completing them one by one
-pub async fn process_tasks(config: &Config, filter: &String) -> Result<String, Error> {
+pub async fn process_tasks(
+    config: &Config,
+    filter: &String,
+    no_recurring: bool,
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_filter(config, filter).await?;
+    let tasks = tasks::maybe_reject_recurring(tasks, no_recurring);
     let tasks = tasks::sort_by_value(tasks, config);
-    let tasks = tasks::reject_parent_tasks(tasks, config).await;
+    let mut tasks = tasks::reject_parent_tasks(tasks, config).await;
     let mut task_count = tasks.len() as i32;
     let mut handles = Vec::new();
-    for task in tasks {
+    let mut index = 0;
+    config.cancel_on_ctrl_c();
+    while let Some(task) = tasks.get(index).cloned() {
+        if config.is_cancelled() {
+            return tasks::save_interrupted(config, handles).await;
+        }
         println!(" ");
         match tasks::process_task(config, task, &mut task_count, true).await {
-            Some(handle) => handles.push(handle),
-            None => return Ok(color::green_string("Exited")),
+            Processed::Handle(handle) => handles.push(handle),
+            Processed::Jump(text) => {
+                if !tasks::jump_to(&mut tasks, index, &text) {
+                    println!("{}", tasks::no_task_to_jump_to(&text));
+                }
+                continue;
+            }
+            Processed::Quit => return Ok(color::success_string("Exited", &config.colors)),
         }
+        index += 1;
+    }
+    future::join_all(handles).await;
+    Ok(color::success_string(
+        &format!("There are no more tasks for filter: '{filter}'"),
+        &config.colors,
+    ))
+}
+
+/// Complete all tasks in a filter, recurring tasks are rolled forward to their next date
+pub async fn complete_tasks(config: &Config, filter: &str) -> Result<String, Error> {
+    let tasks = todoist::tasks_for_filter(config, filter).await?;
+
+    if tasks.is_empty() {
+        return Ok(color::success_string(
+            &format!("No tasks to complete for filter: '{filter}'"),
+            &config.colors,
+        ));
     }
+
+    let (recurring, one_off): (Vec<Task>, Vec<Task>) =
+        tasks.into_iter().partition(|task| task.is_recurring());
+    let recurring_count = recurring.len();
+    let one_off_count = one_off.len();
+
+    let handles = recurring
+        .into_iter()
+        .chain(one_off)
+        .map(|task| tasks::spawn_complete_task(config.clone(), task))
+        .collect::<Vec<JoinHandle<()>>>();
+
     future::join_all(handles).await;
-    Ok(color::green_string(&format!(
-        "There are no more tasks for filter: '{filter}'"
-    )))
+    Ok(color::success_string(
+        &format!("{one_off_count} completed, {recurring_count} rolled forward"),
+        &config.colors,
+    ))
+}
+
+/// Complete every task due at a time today after confirming, i.e. once the day's meetings are over
+pub async fn complete_scheduled_today(config: &Config) -> Result<String, Error> {
+    let tasks = todoist::tasks_for_filter(config, "today").await?;
+    let tasks = tasks::filter_today_and_has_time(tasks, config);
+
+    if tasks.is_empty() {
+        return Ok(color::success_string(
+            "No scheduled tasks to complete for today",
+            &config.colors,
+        ));
+    }
+
+    let count = tasks.len();
+    let question = format!("Complete {count} scheduled task(s) for today?");
+    if !config.confirm(&question, false)? {
+        return Ok(String::from("Nothing completed"));
+    }
+
+    let handles = tasks
+        .into_iter()
+        .map(|task| {
+            let config = config.clone();
+            tokio::spawn(async move { todoist::complete_task(&config, &task.id, false).await })
+        })
+        .collect::<Vec<JoinHandle<Result<String, Error>>>>();
+
+    // Failures are reported as errors, so only the tasks that were closed are counted
+    let mut completed = 0;
+    for result in future::join_all(handles).await {
+        match result {
+            Ok(Ok(_)) => completed += 1,
+            Ok(Err(e)) => config.clone().tx().send(e).unwrap(),
+            Err(e) => config
+                .clone()
+                .tx()
+                .send(error::new("complete", &e.to_string()))
+                .unwrap(),
+        }
+    }
+
+    let message = if completed == count {
+        format!("Completed {count} scheduled task(s)")
+    } else {
+        format!("Completed {completed} of {count} scheduled task(s)")
+    };
+    Ok(color::success_string(&message, &config.colors))
 }
 
-/// Prioritize all unprioritized tasks in a project
-pub async fn prioritize_tasks(config: &Config, filter: &String) -> Result<String, Error> {
+/// Prioritize all tasks in a filter, or every task matching one of the rules
+pub async fn prioritize_tasks(
+    config: &Config,
+    filter: &String,
+    rules: &[Rule],
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_filter(config, filter).await?;
 
+    if !rules.is_empty() {
+        let count = tasks::prioritize_by_rules(config, tasks, rules).await;
+        return Ok(color::success_string(
+            &format!("Prioritized {count} task(s) in '{filter}'"),
+            &config.colors,
+        ));
+    }
+
     if tasks.is_empty() {
-        Ok(color::green_string(&format!(
-            "No tasks to prioritize in '{filter}'"
-        )))
+        Ok(color::success_string(
+            &format!("No tasks to prioritize in '{filter}'"),
+            &config.colors,
+        ))
     } else {
         let mut handles = Vec::new();
         for task in tasks.iter() {
@@ -136,63 +350,41 @@ pub async fn prioritize_tasks(config: &Config, filter: &String) -> Result<String
             handles.push(handle);
         }
         future::join_all(handles).await;
-        Ok(color::green_string(&format!(
-            "Successfully prioritized '{filter}'"
-        )))
+        Ok(color::success_string(
+            &format!("Successfully prioritized '{filter}'"),
+            &config.colors,
+        ))
     }
 }
 
 /// Put dates on all tasks without dates
-pub async fn schedule(config: &Config, filter: &String) -> Result<String, Error> {
+pub async fn schedule(
+    config: &Config,
+    filter: &String,
+    no_recurring: bool,
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_filter(config, filter).await?;
+    let tasks = tasks::maybe_reject_recurring(tasks, no_recurring);
 
     if tasks.is_empty() {
-        Ok(color::green_string(&format!(
-            "No tasks to schedule in '{filter}'"
-        )))
+        Ok(color::success_string(
+            &format!("No tasks to schedule in '{filter}'"),
+            &config.colors,
+        ))
     } else {
-        let mut handles = Vec::new();
-        for task in tasks.iter() {
-            println!("{}", task.fmt(config, FormatType::Single, true));
-            let datetime_input = input::datetime(
-                config.mock_select,
-                config.mock_string.clone(),
-                config.natural_language_only,
-            )?;
-            match datetime_input {
-                input::DateTimeInput::Complete => {
-                    let handle = tasks::spawn_complete_task(config.clone(), task.clone());
-                    handles.push(handle);
-                }
-                DateTimeInput::Skip => (),
-
-                input::DateTimeInput::Text(due_string) => {
-                    let handle =
-                        tasks::spawn_update_task_due(config.clone(), task.clone(), due_string);
-                    handles.push(handle);
-                }
-                input::DateTimeInput::None => {
-                    let handle = tasks::spawn_update_task_due(
-                        config.clone(),
-                        task.clone(),
-                        "No date".to_string(),
-                    );
-                    handles.push(handle);
-                }
-            };
-        }
-
-        future::join_all(handles).await;
-        Ok(color::green_string(&format!(
-            "Successfully scheduled tasks in '{filter}'"
-        )))
+        tasks::schedule(config, tasks, true).await?;
+        Ok(color::success_string(
+            &format!("Successfully scheduled tasks in '{filter}'"),
+            &config.colors,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test;
+    use crate::tasks::DateInfo;
+    use crate::{test, time};
     use pretty_assertions::assert_eq;
 
     #[tokio::test]
@@ -216,7 +408,9 @@ mod tests {
 
         let filter = String::from("today");
 
-        let tasks = all_tasks(&config_with_timezone, &filter).await.unwrap();
+        let tasks = all_tasks(&config_with_timezone, &filter, &ListOptions::default())
+            .await
+            .unwrap();
         //     Ok(format!(
         //         "Tasks for filter: 'today'\n- Put out recycling\n  ! {TIME} ↻ every other mon at 16:30\n# Project not in config\nUse tod project import --auto to import missing projects\n"
         //     ))
@@ -226,6 +420,73 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_format_grouped_by_project() {
+        let config = test::fixtures::config().await;
+        let in_config = Task {
+            id: String::from("1"),
+            content: String::from("Write report"),
+            project_id: String::from("123"),
+            ..test::fixtures::task()
+        };
+        let elsewhere = Task {
+            id: String::from("2"),
+            content: String::from("Water plants"),
+            project_id: String::from("987"),
+            ..test::fixtures::task()
+        };
+
+        let output = format_grouped_by_project(
+            &config,
+            "Tasks for filter: '@work'",
+            vec![elsewhere, in_config.clone()],
+            &ListFormat::Text,
+        );
+
+        let myproject = output.find("\n\nmyproject\n- Write report").unwrap();
+        let other = output.find("\n\nOther\n- Water plants").unwrap();
+        assert!(output.starts_with("Tasks for filter: '@work'"));
+        assert!(myproject < other);
+        assert!(!output.contains("\n\n\n"));
+
+        // Same order as the list without --group-by, where undated tasks come first
+        let timed = Task {
+            due: Some(DateInfo {
+                date: String::from("2061-11-13T08:00:00Z"),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..in_config.clone()
+        };
+        let undated = Task {
+            id: String::from("3"),
+            content: String::from("Someday"),
+            project_id: String::from("123"),
+            due: None,
+            ..test::fixtures::task()
+        };
+        let output = format_grouped_by_project(
+            &config,
+            "Tasks for filter: '@work'",
+            vec![timed, undated],
+            &ListFormat::Text,
+        );
+        assert!(output.find("- Someday").unwrap() < output.find("- Write report").unwrap());
+
+        let markdown = format_grouped_by_project(
+            &config,
+            "Tasks for filter: '@work'",
+            vec![Task {
+                project_id: String::from("123"),
+                ..test::fixtures::task()
+            }],
+            &ListFormat::Markdown,
+        );
+        assert!(markdown.starts_with("## Tasks for filter: '@work'\n\n### myproject\n\n- [ ]"));
+        assert!(!markdown.contains("Other"));
+    }
+
     #[tokio::test]
     async fn test_rename_task() {
         let mut server = mockito::Server::new_async().await;
@@ -274,10 +535,13 @@ mod tests {
         config_with_timezone.clone().create().await.unwrap();
 
         let filter = String::from("today");
-        let task = next_task(config_with_timezone, &filter).await.unwrap();
+        let task = next_task(config_with_timezone, &filter, &[], false)
+            .await
+            .unwrap();
 
         assert!(task.contains("Put out recycling"));
         assert!(task.contains("every other mon at 16:30"));
+        assert!(task.contains("https://app.todoist.com/app/task/999999\n"));
     }
     #[tokio::test]
     async fn test_label() {
@@ -351,7 +615,7 @@ mod tests {
             .unwrap();
         let filter = String::from("today");
 
-        let result = process_tasks(&config, &filter);
+        let result = process_tasks(&config, &filter, false);
         assert_eq!(
             result.await,
             Ok("There are no more tasks for filter: 'today'".to_string())
@@ -360,6 +624,213 @@ mod tests {
         mock2.assert();
     }
 
+    #[tokio::test]
+    async fn process_tasks_stops_when_cancelled() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/tasks/?filter=today")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::get_tasks().await)
+            .create_async()
+            .await;
+        let complete_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = Config {
+            next_id: Some(String::from("999999")),
+            ..test::fixtures::config().await
+        }
+        .mock_url(server.url())
+        .mock_select(0)
+        .create()
+        .await
+        .unwrap();
+        config.cancel();
+        let filter = String::from("today");
+
+        assert_eq!(
+            process_tasks(&config, &filter, false).await,
+            Ok(String::from("Interrupted, progress saved"))
+        );
+        mock.assert();
+        complete_mock.assert();
+        assert_eq!(config.reload().await.unwrap().next_id, None);
+    }
+
+    #[tokio::test]
+    async fn process_tasks_without_recurring() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/tasks/?filter=today")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::get_tasks().await)
+            .create_async()
+            .await;
+        // The only task is recurring, so nothing is completed
+        let complete_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let filter = String::from("today");
+
+        assert_eq!(
+            process_tasks(&config, &filter, true).await,
+            Ok("There are no more tasks for filter: 'today'".to_string())
+        );
+        mock.assert();
+        complete_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn complete_scheduled_today_counts_only_closed_tasks() {
+        let config = test::fixtures::config().await;
+        let today = time::today_string(&config).unwrap();
+        let due = |id: &str| Task {
+            id: String::from(id),
+            due: Some(DateInfo {
+                date: format!("{today}T09:00:00"),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let body = serde_json::json!([due("1"), due("2")]).to_string();
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/rest/v2/tasks/?filter=today")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(r#""id":"1""#)))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(r#""id":"2""#)))
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut config = config.mock_url(server.url()).mock_confirm(true);
+        config.internal.tx = Some(tx);
+
+        assert_eq!(
+            complete_scheduled_today(&config).await,
+            Ok(String::from("Completed 1 of 2 scheduled task(s)"))
+        );
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_tasks() {
+        let recurring = Task {
+            id: String::from("111"),
+            due: Some(DateInfo {
+                is_recurring: true,
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+        let one_off = test::fixtures::task();
+        let body = serde_json::json!([recurring, one_off]).to_string();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/tasks/?filter=today")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let result = complete_tasks(&config, "today").await;
+        assert_eq!(result, Ok(String::from("1 completed, 1 rolled forward")));
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn complete_scheduled_today_only_completes_timed_tasks() {
+        let config = test::fixtures::config().await;
+        let today = time::today_string(&config).unwrap();
+        let due = |id: &str, date: String| Task {
+            id: String::from(id),
+            due: Some(DateInfo {
+                date,
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let body = serde_json::json!([
+            due("1", format!("{today}T09:00:00")),
+            due("2", format!("{today}T13:30:00")),
+            due("3", today.clone()),
+        ])
+        .to_string();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/tasks/?filter=today")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(2)
+            .create_async()
+            .await;
+        let complete_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = config.mock_url(server.url());
+        assert_eq!(
+            complete_scheduled_today(&config.clone().mock_confirm(false)).await,
+            Ok(String::from("Nothing completed"))
+        );
+        assert_eq!(
+            complete_scheduled_today(&config.mock_confirm(true)).await,
+            Ok(String::from("Completed 2 scheduled task(s)"))
+        );
+        mock.assert();
+        complete_mock.assert();
+    }
+
     #[tokio::test]
     async fn test_schedule() {
         let mut server = mockito::Server::new_async().await;
@@ -386,7 +857,7 @@ mod tests {
             .mock_string("tod");
 
         let filter = String::from("today");
-        let result = schedule(&config, &filter);
+        let result = schedule(&config, &filter, false);
         assert_eq!(
             result.await,
             Ok("Successfully scheduled tasks in 'today'".to_string())
@@ -395,7 +866,7 @@ mod tests {
         let config = config.mock_select(2);
 
         let filter = String::from("today");
-        let result = schedule(&config, &filter);
+        let result = schedule(&config, &filter, false);
         assert_eq!(
             result.await,
             Ok("Successfully scheduled tasks in 'today'".to_string())
@@ -428,7 +899,7 @@ mod tests {
             .mock_select(1);
 
         let filter = String::from("today");
-        let result = prioritize_tasks(&config, &filter);
+        let result = prioritize_tasks(&config, &filter, &[]);
         assert_eq!(
             result.await,
             Ok(String::from("Successfully prioritized 'today'"))
@@ -1,12 +1,15 @@
 use std::fmt::Display;
 
-use crate::error::Error;
-use inquire::{DateSelect, Select, Text};
+use crate::error::{self, Error, Kind};
+use inquire::{Confirm, DateSelect, MultiSelect, Select, Text};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DateTimeInput {
     Skip,
     None,
     Complete,
+    /// Return to the previous task
+    Back,
     Text(String),
 }
 
@@ -25,6 +28,7 @@ pub fn datetime(
             "No Date",
             "Skip",
             "Complete",
+            "Back",
         ];
         let description = "Set a due date";
         select(description, options, mock_select)?
@@ -33,7 +37,7 @@ pub fn datetime(
     match selection {
         "Natural Language" => {
             let entry = string(
-                "Enter datetime in natural language, or one of:\n[none (n), skip (s), complete (c)]",
+                "Enter datetime in natural language, or one of:\n[none (n), skip (s), complete (c), back (b)]",
                 mock_string,
             )?;
 
@@ -44,6 +48,8 @@ pub fn datetime(
                 "c" => Ok(DateTimeInput::Complete),
                 "skip" => Ok(DateTimeInput::Skip),
                 "s" => Ok(DateTimeInput::Skip),
+                "back" => Ok(DateTimeInput::Back),
+                "b" => Ok(DateTimeInput::Back),
                 _ => Ok(DateTimeInput::Text(entry)),
             }
         }
@@ -59,10 +65,12 @@ pub fn datetime(
         "No Date" => Ok(DateTimeInput::None),
         "Complete" => Ok(DateTimeInput::Complete),
         "Skip" => Ok(DateTimeInput::Skip),
-        _ => Err(Error {
-            message: String::from("Unrecognized input"),
-            source: String::from("Datetime Input"),
-        }),
+        "Back" => Ok(DateTimeInput::Back),
+        _ => Err(error::with_kind(
+            Kind::Input,
+            "Datetime Input",
+            "Unrecognized input",
+        )),
     }
 }
 
@@ -110,6 +118,69 @@ pub fn select<T: Display>(
         Select::new(desc, options).prompt().map_err(Error::from)
     }
 }
+
+/// Ask a yes/no question, pressing enter picks the default
+pub fn confirm(desc: &str, default: bool, mock_confirm: Option<bool>) -> Result<bool, Error> {
+    if cfg!(test) {
+        if let Some(answer) = mock_confirm {
+            Ok(answer)
+        } else {
+            panic!("Must set mock_confirm in config")
+        }
+    } else {
+        Confirm::new(desc)
+            .with_default(default)
+            .prompt()
+            .map_err(Error::from)
+    }
+}
+
+/// Select an input from a list with the cursor starting on the default.
+/// When testing without mock_select the default is chosen, as if enter was pressed.
+pub fn select_with_default<T: Display>(
+    desc: &str,
+    options: Vec<T>,
+    default: usize,
+    mock_select: Option<usize>,
+) -> Result<T, Error> {
+    if cfg!(test) {
+        let index = mock_select.unwrap_or(default);
+        Ok(options
+            .into_iter()
+            .nth(index)
+            .expect("Must provide a vector of options"))
+    } else {
+        Select::new(desc, options)
+            .with_starting_cursor(default)
+            .prompt()
+            .map_err(Error::from)
+    }
+}
+
+/// Tick any number of options from a list at once
+pub fn multi_select<T: Display>(
+    desc: &str,
+    options: Vec<T>,
+    mock_multiselect: Option<Vec<usize>>,
+) -> Result<Vec<T>, Error> {
+    if cfg!(test) {
+        if let Some(indexes) = mock_multiselect {
+            Ok(options
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| indexes.contains(index))
+                .map(|(_, option)| option)
+                .collect())
+        } else {
+            panic!("Must set mock_multiselect in config")
+        }
+    } else {
+        MultiSelect::new(desc, options)
+            .prompt()
+            .map_err(Error::from)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +196,32 @@ mod tests {
         let expected = Ok("are");
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn can_select_with_default() {
+        let options = vec!["there", "are", "words"];
+        assert_eq!(
+            select_with_default("type", options.clone(), 2, None),
+            Ok("words")
+        );
+        assert_eq!(
+            select_with_default("type", options, 2, Some(0)),
+            Ok("there")
+        );
+    }
+
+    #[test]
+    fn can_multi_select() {
+        let options = vec!["there", "are", "words"];
+        assert_eq!(
+            multi_select("type", options, Some(vec![0, 2])),
+            Ok(vec!["there", "words"])
+        );
+    }
+
+    #[test]
+    fn can_confirm() {
+        assert_eq!(confirm("Delete?", false, Some(true)), Ok(true));
+        assert_eq!(confirm("Delete?", true, Some(false)), Ok(false));
+    }
 }
@@ -0,0 +1,136 @@
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::error::{self, Error, Kind};
+use crate::{color, todoist};
+
+// Personal labels, renaming one renames it on every task
+#[derive(PartialEq, Eq, Deserialize, Clone, Debug)]
+pub struct Label {
+    pub id: String,
+    pub name: String,
+}
+
+pub fn json_to_labels(json: String) -> Result<Vec<Label>, Error> {
+    let labels: Vec<Label> = serde_json::from_str(&json)?;
+    Ok(labels)
+}
+
+/// Finds a label by name ignoring case, the name can start with @
+pub fn by_name(labels: &[Label], name: &str) -> Result<Label, Error> {
+    let name = name.trim().trim_start_matches('@');
+    labels
+        .iter()
+        .find(|label| label.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| {
+            error::with_kind(
+                Kind::NotFound,
+                "label_rename",
+                &format!("Could not find the label '{name}' in Todoist"),
+            )
+        })
+}
+
+/// Renames a label in Todoist, which updates every task that has it
+pub async fn rename(config: &Config, from: &str, to: &str) -> Result<String, Error> {
+    let to = to.trim().trim_start_matches('@');
+    let labels = todoist::labels(config).await?;
+    let label = by_name(&labels, from)?;
+
+    let question = format!("Rename '{}' to '{to}' on every task?", label.name);
+    if !config.confirm(&question, false)? {
+        return Ok(String::from("Cancelled"));
+    }
+
+    todoist::rename_label(config, &label, to).await?;
+    Ok(color::success_string(
+        &format!("Renamed '{}' to '{to}'", label.name),
+        &config.colors,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn by_name_ignores_case_and_at() {
+        let labels = json_to_labels(test::responses::labels()).expect("valid labels");
+
+        assert_eq!(
+            by_name(&labels, "@Errands").map(|l| l.id),
+            Ok(String::from("2156154810"))
+        );
+        assert_eq!(
+            by_name(&labels, "someday"),
+            Err(error::with_kind(
+                Kind::NotFound,
+                "label_rename",
+                "Could not find the label 'someday' in Todoist"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn rename_posts_to_the_resolved_label() {
+        let mut server = mockito::Server::new_async().await;
+        let labels_mock = server
+            .mock("GET", "/rest/v2/labels")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::labels())
+            .create_async()
+            .await;
+        let rename_mock = server
+            .mock("POST", "/rest/v2/labels/2156154810")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"name": "chores"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("{\"id\": \"2156154810\", \"name\": \"chores\"}")
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_confirm(true);
+
+        assert_eq!(
+            rename(&config, "errands", "@chores").await,
+            Ok(color::success_string(
+                "Renamed 'errands' to 'chores'",
+                &config.colors
+            ))
+        );
+        labels_mock.assert();
+        rename_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn rename_does_nothing_when_declined() {
+        let mut server = mockito::Server::new_async().await;
+        let labels_mock = server
+            .mock("GET", "/rest/v2/labels")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::labels())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_confirm(false);
+
+        assert_eq!(
+            rename(&config, "errands", "chores").await,
+            Ok(String::from("Cancelled"))
+        );
+        labels_mock.assert();
+    }
+}
@@ -9,23 +9,33 @@ extern crate clap;
 use std::fmt::Display;
 
 use cargo::Version;
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
-use config::Config;
-use error::Error;
+use config::{Args, Config};
+use error::{Error, Kind};
+use projects::palette::ProjectColor;
 use projects::Project;
+use report::Week;
+use std::future::Future;
 use tasks::priority;
-use tasks::priority::Priority;
-use tokio::sync::mpsc::UnboundedSender;
+use tasks::priority::{Priority, Rule};
+use tasks::{GroupBy, ListFormat, ListOptions};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 mod cargo;
+mod collaborators;
 mod color;
 mod config;
 mod debug;
 mod error;
 mod filters;
 mod input;
+mod labels;
 mod projects;
+mod queue;
+mod report;
 mod sections;
+mod setup;
 mod tasks;
 mod test;
 mod time;
@@ -53,10 +63,34 @@ struct Cli {
     /// Absolute path of configuration. Defaults to $XDG_CONFIG_HOME/tod.cfg
     config: Option<String>,
 
+    #[arg(long)]
+    /// Use the named profile at $XDG_CONFIG_HOME/tod/NAME.cfg, --config takes precedence
+    profile: Option<String>,
+
     #[arg(short, long)]
     /// Time to wait for a response from API in seconds. Defaults to 30.
     timeout: Option<u64>,
 
+    #[arg(short, long, default_value_t = false)]
+    /// Do not show a spinner while waiting for Todoist
+    quiet: bool,
+
+    #[arg(long)]
+    /// Use this timezone instead of the one in config for this command only, i.e. "Europe/London"
+    timezone: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Show what would change without saving it, used by `project remove --auto`
+    dry_run: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Stop at the first error from a batch operation instead of continuing
+    fail_fast: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Answer yes to every confirmation, for unattended runs
+    yes: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -78,10 +112,32 @@ enum Commands {
     /// (l) Commands for multiple tasks
     List(ListCommands),
 
+    #[command(subcommand)]
+    /// Commands for labels in Todoist
+    Label(LabelCommands),
+
     #[command(subcommand)]
     #[clap(alias = "c")]
     /// (c) Commands around configuration and the app
     Config(ConfigCommands),
+
+    #[command(subcommand)]
+    #[clap(alias = "r")]
+    /// (r) Reports on completed tasks
+    Report(ReportCommands),
+
+    #[clap(alias = "s")]
+    /// (s) Send changes that were queued while Todoist could not be reached
+    Sync(QueueSync),
+
+    /// Walk through the API token, timezone, and project import
+    Setup(Setup),
+
+    /// Check that the token works and Todoist can be reached
+    Ping(Ping),
+
+    /// Show how priority numbers map to p1 to p4 and their colors
+    Priority(PriorityLegend),
 }
 
 // -- PROJECTS --
@@ -104,19 +160,50 @@ enum ProjectCommands {
     /// (i) Get projects from Todoist and prompt to add to config
     Import(ProjectImport),
 
+    #[clap(alias = "c")]
+    /// (c) Create a project in Todoist and add it to config
+    Create(ProjectCreate),
+
     #[clap(alias = "e")]
     /// (e) Empty a project by putting tasks in other projects"
     Empty(ProjectEmpty),
 }
 
 #[derive(Parser, Debug, Clone)]
-struct ProjectList {}
+struct ProjectList {
+    #[arg(short, long, default_value_t = false)]
+    /// Show the number of open tasks in each project
+    counts: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "counts")]
+    /// Print only project names, one per line, for shell completion scripts
+    completion: bool,
+}
 
 #[derive(Parser, Debug, Clone)]
 struct ProjectImport {
     #[arg(short = 'a', long, default_value_t = false)]
     /// Add all projects to config that are not there aleady
     auto: bool,
+
+    #[arg(short, long, default_value_t = false, conflicts_with = "auto")]
+    /// Tick the projects to add from a single list instead of being asked about each one
+    select: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ProjectCreate {
+    #[arg(short, long)]
+    /// Name of the new project
+    name: Option<String>,
+
+    #[arg(short, long, value_parser = parse_project_color)]
+    /// Color of the new project, i.e. "berry_red"
+    color: Option<ProjectColor>,
+
+    #[arg(short, long)]
+    /// Name of a project in config to nest the new project under
+    parent: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -143,12 +230,24 @@ struct ProjectRename {
     #[arg(short, long)]
     /// Project to remove
     project: Option<String>,
+
+    #[arg(short, long, value_parser = parse_project_color)]
+    /// New color for the project, i.e. "berry_red"
+    color: Option<ProjectColor>,
 }
 #[derive(Parser, Debug, Clone)]
 struct ProjectEmpty {
     #[arg(short, long)]
     /// Project to remove
     project: Option<String>,
+
+    #[arg(short, long)]
+    /// Only empty the tasks in this section of the project
+    section: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Fetch each task again after moving it to check that it reached the project
+    verify: bool,
 }
 
 // -- TASKS --
@@ -172,8 +271,16 @@ enum TaskCommands {
     Next(TaskNext),
 
     #[clap(alias = "o")]
-    /// (o) Complete the last task fetched with the next command
+    /// (o) Complete the last task fetched with the next command, or all tasks in a filter
     Complete(TaskComplete),
+
+    #[clap(alias = "s")]
+    /// (s) Push a task's due date forward, defaults to the last task fetched with the next command
+    Snooze(TaskSnooze),
+
+    #[clap(alias = "a")]
+    /// (a) Assign a task to someone in a shared project, defaults to the last task fetched with the next command
+    Assign(TaskAssign),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -181,6 +288,18 @@ struct TaskQuickAdd {
     #[arg(short, long, num_args(1..))]
     /// Content for task
     content: Option<Vec<String>>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Add the content as is, without turning @, #, and dates into labels, projects, and due dates
+    literal: bool,
+
+    #[arg(short = 'r', long)]
+    /// Priority from 1 (without priority) to 4 (highest), set after adding and taking precedence over p1 to p4 in the content
+    priority: Option<u8>,
+
+    #[arg(short, long)]
+    /// Description for the task, added once the task has been created
+    description: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -189,14 +308,30 @@ struct TaskCreate {
     /// The project into which the task will be added
     project: Option<String>,
 
+    #[arg(long, conflicts_with = "project")]
+    /// Create a project with this name in Todoist and config, then add the task to it
+    project_new: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Fetch the task again after creating it to check that it is in the project
+    verify: bool,
+
     #[arg(short = 'u', long)]
     /// Date date in format YYYY-MM-DD, YYYY-MM-DD HH:MM, or natural language
     due: Option<String>,
 
+    #[arg(long)]
+    /// Date the task must be done by in format YYYY-MM-DD, kept separate from the due date
+    deadline: Option<String>,
+
     #[arg(short, long, default_value_t = String::new())]
     /// Description for task
     description: String,
 
+    #[arg(long)]
+    /// URL to add to the end of the description as a markdown link
+    link: Option<String>,
+
     #[arg(short, long)]
     /// Content for task
     content: Option<String>,
@@ -206,7 +341,7 @@ struct TaskCreate {
     no_section: bool,
 
     #[arg(short = 'r', long)]
-    /// Priority from 1 (without priority) to 4 (highest)
+    /// Priority from 1 (without priority) to 4 (highest). A trailing p1 to p4 in the content is used when this is not given
     priority: Option<u8>,
 
     #[arg(short, long)]
@@ -223,6 +358,10 @@ struct TaskEdit {
     #[arg(short, long)]
     /// The filter containing the task
     filter: Option<String>,
+
+    #[arg(long)]
+    /// Add this text on a new line at the end of the description instead of editing the content
+    append_description: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -234,10 +373,72 @@ struct TaskNext {
     #[arg(short, long)]
     /// The filter containing the task
     filter: Option<String>,
+
+    #[arg(short, long)]
+    /// Skip tasks with this label, in addition to exclude_labels in config. Can be used multiple times
+    exclude_label: Vec<String>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Skip tasks with a time later today, so only tasks that can be done right now are returned
+    only_actionable: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct TaskComplete {
+    #[arg(short, long)]
+    /// Complete all tasks in the filter instead, recurring tasks are rolled forward
+    filter: Option<String>,
+
+    #[arg(short, long)]
+    /// The project containing the task, used with --index
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// Complete the task at this position in `list view`, starting from 1
+    index: Option<usize>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reopen the task that was completed last
+    undo: bool,
+
+    #[arg(long, conflicts_with_all = ["filter", "index", "undo"])]
+    /// Add a task with this content to the same project once the next task is completed
+    followup: Option<String>,
+
+    #[arg(long, default_value_t = false, conflicts_with_all = ["filter", "project", "index", "undo", "followup"])]
+    /// Complete every task due at a time today, after confirming
+    scheduled: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct TaskSnooze {
+    #[arg(short, long)]
+    /// Choose the task from this project instead
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// Choose the task from this filter instead
+    filter: Option<String>,
+
+    #[arg(short, long)]
+    /// How far to push the due date, i.e. 1h, 2d, or 1w
+    by: String,
 }
 
 #[derive(Parser, Debug, Clone)]
-struct TaskComplete {}
+struct TaskAssign {
+    #[arg(short, long)]
+    /// Choose the task from this project instead
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// Choose the task from this filter instead
+    filter: Option<String>,
+
+    #[arg(short, long)]
+    /// Name or email of the person to assign the task to
+    to: String,
+}
 
 // -- LISTS --
 
@@ -273,6 +474,54 @@ struct ListView {
     #[arg(short, long)]
     /// The filter containing the tasks
     filter: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    /// How to display the list of tasks
+    format: ListFormat,
+
+    #[arg(short, long, value_enum)]
+    /// Split the tasks from a filter into sections
+    group_by: Option<GroupBy>,
+
+    #[arg(long)]
+    /// Only show tasks created on or after this date, in the format YYYY-MM-DD
+    created_since: Option<String>,
+
+    #[arg(long)]
+    /// Only show tasks due before this date, in the format YYYY-MM-DD, soonest first
+    due_before: Option<String>,
+
+    #[arg(short, long)]
+    /// Only show tasks in this section of the project
+    section: Option<String>,
+
+    #[arg(short, long)]
+    /// Only show tasks in the project with this label
+    label: Option<String>,
+
+    #[arg(long)]
+    /// Flag tasks that have been overdue for more than this many days
+    stale_days: Option<u32>,
+
+    #[arg(short, long, default_value_t = false, conflicts_with_all = ["agenda", "due_before", "raw"])]
+    /// Only show recurring tasks in the project, sorted by when they are next due
+    recurring: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Show tasks completed in the project over the last 7 days instead, newest first
+    completed: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "due_before")]
+    /// Only show tasks that have neither a priority nor a date
+    raw: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with_all = ["due_before", "raw"])]
+    /// Split the tasks in the project into Overdue, Today, Upcoming, and No date sections
+    agenda: bool,
+
+    #[arg(short, long)]
+    /// Write the tasks to this file without colors instead of printing them
+    output: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -284,6 +533,10 @@ struct ListProcess {
     #[arg(short, long)]
     /// The filter containing the tasks
     filter: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Leave recurring tasks out so that their cadence isn't reset
+    no_recurring: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -295,6 +548,14 @@ struct ListPrioritize {
     #[arg(short, long)]
     /// The filter containing the tasks
     filter: Option<String>,
+
+    #[arg(short, long, value_parser = parse_priority_rule)]
+    /// Set priorities without prompting, i.e. "overdue=high". Conditions are overdue, due-today, and has-label:<label>. Can be repeated, the first matching rule wins
+    rule: Vec<Rule>,
+
+    #[arg(short, long)]
+    /// Only prioritize tasks in the project with this label
+    label: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -318,13 +579,37 @@ struct ListSchedule {
     /// The filter containing the tasks
     filter: Option<String>,
 
-    #[arg(short, long, default_value_t = false)]
-    /// Don't re-schedule recurring tasks that are overdue
+    #[arg(short, long, visible_alias = "no-recurring", default_value_t = false)]
+    /// Don't re-schedule recurring tasks, so that their cadence isn't reset
     skip_recurring: bool,
 
     #[arg(short, long, default_value_t = false)]
     /// Only schedule overdue tasks
     overdue: bool,
+
+    #[arg(short, long)]
+    /// Only schedule tasks in the project with this label
+    label: Option<String>,
+}
+
+// -- LABELS --
+
+#[derive(Subcommand, Debug, Clone)]
+enum LabelCommands {
+    #[clap(alias = "r")]
+    /// (r) Rename a label in Todoist, which updates every task that has it
+    Rename(LabelRename),
+}
+
+#[derive(Parser, Debug, Clone)]
+struct LabelRename {
+    #[arg(short, long)]
+    /// The current name of the label
+    from: String,
+
+    #[arg(short, long)]
+    /// The new name for the label
+    to: String,
 }
 
 // -- CONFIG --
@@ -342,8 +627,41 @@ enum ConfigCommands {
     #[clap(alias = "tz")]
     /// (tz) Change the timezone in the configuration file
     SetTimezone(ConfigSetTimezone),
+
+    #[clap(alias = "p")]
+    /// (p) Print the path of the configuration file, without creating it
+    Path(ConfigPath),
+
+    #[clap(alias = "e")]
+    /// (e) Export settings and projects as JSON, for moving to another machine
+    Export(ConfigExport),
+
+    #[clap(alias = "i")]
+    /// (i) Merge settings and projects from an export into the configuration file
+    Import(ConfigImport),
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigExport {
+    #[arg(short, long)]
+    /// Write the export to this file instead of printing it
+    file: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Include the Todoist API token
+    include_token: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigImport {
+    #[arg(short, long)]
+    /// The file written by config export
+    file: String,
 }
 
+#[derive(Parser, Debug, Clone)]
+struct ConfigPath {}
+
 #[derive(Parser, Debug, Clone)]
 struct ConfigCheckVersion {}
 
@@ -357,6 +675,81 @@ struct ConfigSetTimezone {
     timezone: Option<String>,
 }
 
+// -- REPORTS --
+
+#[derive(Subcommand, Debug, Clone)]
+enum ReportCommands {
+    #[clap(alias = "c")]
+    /// (c) Tasks completed between two dates
+    Completed(ReportCompleted),
+
+    #[clap(alias = "p")]
+    /// (p) Current tasks in a project grouped by priority
+    Priority(ReportPriority),
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ReportCompleted {
+    #[arg(short, long)]
+    /// Only include tasks from this project
+    project: Option<String>,
+
+    #[arg(short, long, required_unless_present = "week")]
+    /// First day to include, in the format YYYY-MM-DD
+    since: Option<String>,
+
+    #[arg(short, long)]
+    /// Last day to include, in the format YYYY-MM-DD. Defaults to today
+    until: Option<String>,
+
+    #[arg(short, long, value_enum, conflicts_with_all = ["since", "until"])]
+    /// Report on this week or last week, Monday to Sunday, instead of --since and --until
+    week: Option<Week>,
+
+    #[arg(short, long)]
+    /// Only include tasks that had this label when they were completed
+    label: Option<String>,
+
+    #[arg(short, long)]
+    /// Write the report to this file without colors instead of printing it
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ReportPriority {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// Only include tasks with this label
+    label: Option<String>,
+
+    #[arg(short, long)]
+    /// Write the report to this file without colors instead of printing it
+    output: Option<String>,
+}
+
+// -- SYNC --
+
+#[derive(Parser, Debug, Clone)]
+struct QueueSync {}
+
+// -- SETUP --
+
+#[derive(Parser, Debug, Clone)]
+struct Setup {}
+
+// -- PING --
+
+#[derive(Parser, Debug, Clone)]
+struct Ping {}
+
+// -- PRIORITY --
+
+#[derive(Parser, Debug, Clone)]
+struct PriorityLegend {}
+
 enum Flag {
     Project(Project),
     Filter(String),
@@ -379,50 +772,95 @@ impl Display for FlagOptions {
 #[cfg(not(tarpaulin_include))]
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    color::disable_if_no_color();
+    let cli = fetch_cli().await;
 
     // Channel for sending errors from async processes
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+    let fail_fast = cli.fail_fast;
 
-    let result: Result<String, Error> = match &cli.command {
-        Commands::Project(ProjectCommands::List(args)) => project_list(cli.clone(), args, tx).await,
-        Commands::Project(ProjectCommands::Remove(args)) => {
-            project_remove(cli.clone(), args, tx).await
-        }
-        Commands::Project(ProjectCommands::Rename(args)) => {
-            project_rename(cli.clone(), args, tx).await
-        }
-        Commands::Project(ProjectCommands::Import(args)) => {
-            project_import(cli.clone(), args, tx).await
-        }
-        Commands::Project(ProjectCommands::Empty(args)) => {
-            project_empty(cli.clone(), args, tx).await
-        }
+    let command = async {
+        match &cli.command {
+            Commands::Project(ProjectCommands::List(args)) => {
+                project_list(cli.clone(), args, tx).await
+            }
+            Commands::Project(ProjectCommands::Remove(args)) => {
+                project_remove(cli.clone(), args, tx).await
+            }
+            Commands::Project(ProjectCommands::Rename(args)) => {
+                project_rename(cli.clone(), args, tx).await
+            }
+            Commands::Project(ProjectCommands::Import(args)) => {
+                project_import(cli.clone(), args, tx).await
+            }
+            Commands::Project(ProjectCommands::Create(args)) => {
+                project_create(cli.clone(), args, tx).await
+            }
+            Commands::Project(ProjectCommands::Empty(args)) => {
+                project_empty(cli.clone(), args, tx).await
+            }
 
-        Commands::Task(TaskCommands::QuickAdd(args)) => task_quick_add(cli.clone(), args, tx).await,
-        Commands::Task(TaskCommands::Create(args)) => task_create(cli.clone(), args, tx).await,
-        Commands::Task(TaskCommands::Edit(args)) => task_edit(cli.clone(), args, tx).await,
-        Commands::Task(TaskCommands::Next(args)) => task_next(cli.clone(), args, tx).await,
-        Commands::Task(TaskCommands::Complete(args)) => task_complete(cli.clone(), args, tx).await,
+            Commands::Task(TaskCommands::QuickAdd(args)) => {
+                task_quick_add(cli.clone(), args, tx).await
+            }
+            Commands::Task(TaskCommands::Create(args)) => task_create(cli.clone(), args, tx).await,
+            Commands::Task(TaskCommands::Edit(args)) => task_edit(cli.clone(), args, tx).await,
+            Commands::Task(TaskCommands::Next(args)) => task_next(cli.clone(), args, tx).await,
+            Commands::Task(TaskCommands::Complete(args)) => {
+                task_complete(cli.clone(), args, tx).await
+            }
+            Commands::Task(TaskCommands::Snooze(args)) => task_snooze(cli.clone(), args, tx).await,
+            Commands::Task(TaskCommands::Assign(args)) => task_assign(cli.clone(), args, tx).await,
 
-        Commands::List(ListCommands::View(args)) => list_view(cli.clone(), args, tx).await,
-        Commands::List(ListCommands::Process(args)) => list_process(cli.clone(), args, tx).await,
-        Commands::List(ListCommands::Prioritize(args)) => {
-            list_prioritize(cli.clone(), args, tx).await
-        }
-        Commands::List(ListCommands::Label(args)) => list_label(cli.clone(), args, tx).await,
-        Commands::List(ListCommands::Schedule(args)) => list_schedule(cli.clone(), args, tx).await,
+            Commands::List(ListCommands::View(args)) => list_view(cli.clone(), args, tx).await,
+            Commands::List(ListCommands::Process(args)) => {
+                list_process(cli.clone(), args, tx).await
+            }
+            Commands::List(ListCommands::Prioritize(args)) => {
+                list_prioritize(cli.clone(), args, tx).await
+            }
+            Commands::List(ListCommands::Label(args)) => list_label(cli.clone(), args, tx).await,
+            Commands::List(ListCommands::Schedule(args)) => {
+                list_schedule(cli.clone(), args, tx).await
+            }
 
-        Commands::Config(ConfigCommands::CheckVersion(args)) => {
-            config_check_version(cli.clone(), args, tx).await
-        }
-        Commands::Config(ConfigCommands::Reset(args)) => config_reset(cli.clone(), args, tx).await,
-        Commands::Config(ConfigCommands::SetTimezone(args)) => {
-            tz_reset(cli.clone(), args, tx).await
+            Commands::Label(LabelCommands::Rename(args)) => {
+                label_rename(cli.clone(), args, tx).await
+            }
+
+            Commands::Config(ConfigCommands::CheckVersion(args)) => {
+                config_check_version(cli.clone(), args, tx).await
+            }
+            Commands::Config(ConfigCommands::Reset(args)) => {
+                config_reset(cli.clone(), args, tx).await
+            }
+            Commands::Config(ConfigCommands::SetTimezone(args)) => {
+                tz_reset(cli.clone(), args, tx).await
+            }
+            Commands::Config(ConfigCommands::Path(args)) => config_path(cli.clone(), args).await,
+            Commands::Config(ConfigCommands::Export(args)) => {
+                config_export(cli.clone(), args, tx).await
+            }
+            Commands::Config(ConfigCommands::Import(args)) => {
+                config_import(cli.clone(), args, tx).await
+            }
+
+            Commands::Report(ReportCommands::Completed(args)) => {
+                report_completed(cli.clone(), args, tx).await
+            }
+            Commands::Report(ReportCommands::Priority(args)) => {
+                report_priority(cli.clone(), args, tx).await
+            }
+
+            Commands::Sync(args) => sync(cli.clone(), args, tx).await,
+            Commands::Setup(args) => setup(cli.clone(), args, tx).await,
+            Commands::Ping(args) => ping(cli.clone(), args, tx).await,
+            Commands::Priority(args) => priority_legend(cli.clone(), args, tx).await,
         }
     };
 
-    while let Some(e) = rx.recv().await {
+    let (result, errors) = collect_errors(command, &mut rx, fail_fast).await;
+    for e in errors {
         eprintln!("Error from async process: {e}");
     }
 
@@ -438,6 +876,63 @@ async fn main() {
     }
 }
 
+/// Parses the command line, falling back to default_command in config when there are no arguments
+#[cfg(not(tarpaulin_include))]
+async fn fetch_cli() -> Cli {
+    if std::env::args().count() > 1 {
+        return Cli::parse();
+    }
+
+    let default_command = match config::resolve_path(None).await {
+        Ok(path) => Config::load(&path)
+            .await
+            .ok()
+            .and_then(|config| config.default_command),
+        Err(_) => None,
+    };
+
+    match default_command {
+        Some(command) => cli_from_command(&command).unwrap_or_else(|e| e.exit()),
+        None => Cli::parse(),
+    }
+}
+
+/// Parses a command stored as a string, double quotes group words into one argument
+fn cli_from_command(command: &str) -> Result<Cli, clap::Error> {
+    let mut args = vec![String::from("tod")];
+    for (index, part) in command.split('"').enumerate() {
+        if index % 2 == 1 {
+            args.push(part.to_string());
+        } else {
+            args.extend(part.split_whitespace().map(String::from));
+        }
+    }
+
+    Cli::try_parse_from(args)
+}
+
+/// Runs the command while collecting errors sent from async processes.
+/// With fail_fast the first error is returned straight away instead of waiting for the command.
+async fn collect_errors(
+    command: impl Future<Output = Result<String, Error>>,
+    rx: &mut UnboundedReceiver<Error>,
+    fail_fast: bool,
+) -> (Result<String, Error>, Vec<Error>) {
+    tokio::pin!(command);
+
+    let result = tokio::select! {
+        result = &mut command => result,
+        Some(e) = rx.recv(), if fail_fast => return (Err(e), Vec::new()),
+    };
+
+    let mut errors = Vec::new();
+    while let Some(e) = rx.recv().await {
+        errors.push(e);
+    }
+
+    (result, errors)
+}
+
 // --- TASK ---
 
 #[cfg(not(tarpaulin_include))]
@@ -446,12 +941,17 @@ async fn task_quick_add(
     args: &TaskQuickAdd,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let TaskQuickAdd { content } = args;
+    let TaskQuickAdd {
+        content,
+        literal,
+        priority,
+        description,
+    } = args;
     let config = fetch_config(cli, tx).await?;
 
     let content = fetch_string(&content.as_ref().map(|c| c.join(" ")), &config, "CONTENT")?;
-    todoist::quick_add_task(&config, &content).await?;
-    Ok(color::green_string("✓"))
+    let priority = priority::from_integer(priority);
+    tasks::quick_add(&config, &content, *literal, priority, description).await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -462,19 +962,35 @@ async fn task_create(
 ) -> Result<String, Error> {
     let TaskCreate {
         project,
+        project_new,
+        verify,
         due,
+        deadline,
         description,
+        link,
         content,
         no_section,
         priority,
         label: labels,
     } = args;
-    let config = fetch_config(cli, tx).await?;
+    let mut config = fetch_config(cli, tx).await?;
+    let deadline = match deadline {
+        Some(date) => Some(fetch_date(date, &config)?),
+        None => None,
+    };
     let content = fetch_string(content, &config, "CONTENT")?;
-    let priority = fetch_priority(priority, &config)?;
-    let project = match fetch_project(project, &config)? {
-        Flag::Project(project) => project,
-        _ => unreachable!(),
+    let description = tasks::maybe_prompt_description(&config, description)?;
+    let description = tasks::description_with_link(&description, link);
+    let project = match project_new {
+        Some(name) => projects::create_for_task(&mut config, name).await?,
+        None => match fetch_project(project, &mut config).await? {
+            Flag::Project(project) => project,
+            _ => unreachable!(),
+        },
+    };
+    let (content, priority) = match (priority, priority::split_inline(&content)) {
+        (None, (stripped, Some(inline))) => (stripped, inline),
+        _ => (content, fetch_priority(priority, &project, &config)?),
     };
     let section = if *no_section || config.no_sections.unwrap_or_default() {
         None
@@ -493,67 +1009,241 @@ async fn task_create(
         }
     };
 
-    todoist::add_task(
+    let task = todoist::add_task(
         &config,
         &content,
         &project,
         section,
         priority,
-        description,
+        &description,
         due,
+        &deadline,
         labels,
     )
     .await?;
 
-    Ok(color::green_string("✓"))
+    if *verify {
+        todoist::verify_task_project(&config, &task.id, &project.id).await?;
+    }
+
+    Ok(color::success_string("✓", &config.colors))
 }
 
 #[cfg(not(tarpaulin_include))]
 async fn task_edit(cli: Cli, args: &TaskEdit, tx: UnboundedSender<Error>) -> Result<String, Error> {
-    let config = fetch_config(cli, tx).await?;
-    let TaskEdit { project, filter } = args;
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Project(project) => projects::rename_task(&config, &project).await,
-        Flag::Filter(filter) => filters::rename_task(&config, filter).await,
+    let mut config = fetch_config(cli, tx).await?;
+    let TaskEdit {
+        project,
+        filter,
+        append_description,
+    } = args;
+    match (
+        fetch_project_or_filter(project, filter, &mut config).await?,
+        append_description,
+    ) {
+        (Flag::Project(project), Some(note)) => {
+            projects::append_task_description(&config, &project, note).await
+        }
+        (Flag::Filter(filter), Some(note)) => {
+            filters::append_task_description(&config, &filter, note).await
+        }
+        (Flag::Project(project), None) => projects::rename_task(&config, &project).await,
+        (Flag::Filter(filter), None) => filters::rename_task(&config, filter).await,
     }
 }
 #[cfg(not(tarpaulin_include))]
 async fn task_next(cli: Cli, args: &TaskNext, tx: UnboundedSender<Error>) -> Result<String, Error> {
-    let TaskNext { project, filter } = args;
-    let config = fetch_config(cli, tx).await?;
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Project(project) => projects::next_task(config, &project).await,
-        Flag::Filter(filter) => filters::next_task(config, &filter).await,
-    }
-}
-
-#[cfg(not(tarpaulin_include))]
-async fn task_complete(
-    cli: Cli,
-    _args: &TaskComplete,
+    let TaskNext {
+        project,
+        filter,
+        exclude_label,
+        only_actionable,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+    match fetch_project_or_filter(project, filter, &mut config).await? {
+        Flag::Project(project) => {
+            projects::next_task(config, &project, exclude_label, *only_actionable).await
+        }
+        Flag::Filter(filter) => {
+            filters::next_task(config, &filter, exclude_label, *only_actionable).await
+        }
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn task_complete(
+    cli: Cli,
+    args: &TaskComplete,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let config = fetch_config(cli, tx).await?;
-    match config.next_id.as_ref() {
-        Some(id) => todoist::complete_task(&config, id, true).await,
-        None => Err(error::new(
+    let TaskComplete {
+        filter,
+        project,
+        index,
+        undo,
+        followup,
+        scheduled,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+
+    if *scheduled {
+        return filters::complete_scheduled_today(&config).await;
+    }
+
+    if *undo {
+        return tasks::undo_complete(config).await;
+    }
+
+    if let Some(index) = index {
+        let tasks = match fetch_project_or_filter(project, filter, &mut config).await? {
+            Flag::Project(project) => todoist::tasks_for_project(&config, &project).await?,
+            Flag::Filter(filter) => todoist::tasks_for_filter(&config, &filter).await?,
+        };
+        return tasks::complete_by_index(&config, tasks, *index).await;
+    }
+
+    if let Some(filter) = filter {
+        return filters::complete_tasks(&config, filter).await;
+    }
+
+    match (config.next_id.as_ref(), followup) {
+        (Some(id), Some(content)) => tasks::complete_with_followup(&config, id, content).await,
+        (Some(id), None) => tasks::complete_with_subtasks(&config, id).await,
+        (None, _) => Err(error::new(
             "task_complete",
             "There is nothing to complete. A task must first be marked as 'next'.",
         )),
     }
 }
 
+#[cfg(not(tarpaulin_include))]
+async fn task_snooze(
+    cli: Cli,
+    args: &TaskSnooze,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let TaskSnooze {
+        project,
+        filter,
+        by,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+    let by = time::parse_duration(by)?;
+
+    let task = match (project, filter, config.next_id.as_ref()) {
+        (None, None, Some(id)) => todoist::get_task(&config, id).await?,
+        _ => {
+            let tasks = match fetch_project_or_filter(project, filter, &mut config).await? {
+                Flag::Project(project) => todoist::tasks_for_project(&config, &project).await?,
+                Flag::Filter(filter) => todoist::tasks_for_filter(&config, &filter).await?,
+            };
+            input::select("Choose a task to snooze:", tasks, config.mock_select)?
+        }
+    };
+
+    tasks::snooze(&config, task, by).await
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn task_assign(
+    cli: Cli,
+    args: &TaskAssign,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let TaskAssign {
+        project,
+        filter,
+        to,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+
+    let task = match (project, filter, config.next_id.as_ref()) {
+        (None, None, Some(id)) => todoist::get_task(&config, id).await?,
+        _ => {
+            let tasks = match fetch_project_or_filter(project, filter, &mut config).await? {
+                Flag::Project(project) => todoist::tasks_for_project(&config, &project).await?,
+                Flag::Filter(filter) => todoist::tasks_for_filter(&config, &filter).await?,
+            };
+            input::select("Choose a task to assign:", tasks, config.mock_select)?
+        }
+    };
+
+    tasks::assign(&config, task, to).await
+}
+
 // --- LIST ---
 
 #[cfg(not(tarpaulin_include))]
 async fn list_view(cli: Cli, args: &ListView, tx: UnboundedSender<Error>) -> Result<String, Error> {
-    let config = fetch_config(cli, tx).await?;
-    let ListView { project, filter } = args;
+    let mut config = fetch_config(cli, tx).await?;
+    let ListView {
+        project,
+        filter,
+        format,
+        group_by,
+        created_since,
+        due_before,
+        section,
+        label,
+        stale_days,
+        recurring,
+        completed,
+        raw,
+        agenda,
+        output,
+    } = args;
+    let created_since = match created_since {
+        None => None,
+        Some(date) => Some(fetch_date(date, &config)?),
+    };
+    let due_before = match due_before {
+        None => None,
+        Some(date) => Some(fetch_date(date, &config)?),
+    };
 
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Project(project) => projects::all_tasks(&config, &project).await,
-        Flag::Filter(filter) => filters::all_tasks(&config, &filter).await,
-    }
+    let options = &ListOptions {
+        format: *format,
+        group_by: *group_by,
+        created_since,
+        due_before,
+        section: section.clone(),
+        label: label.clone(),
+        stale_days: *stale_days,
+        recurring: *recurring,
+        untriaged: *raw,
+        agenda: *agenda,
+    };
+    let flags = fetch_projects_or_filter(project, filter, &mut config).await?;
+    let config = &config;
+    let result = for_each_flag(flags, |flag| async move {
+        match flag {
+            Flag::Project(project) if *completed => {
+                projects::recently_completed(config, &project).await
+            }
+            Flag::Project(project) => projects::all_tasks(config, &project, options).await,
+            Flag::Filter(_) if section.is_some() => Err(error::new(
+                "list_view",
+                "--section can only be used with --project",
+            )),
+            Flag::Filter(_) if label.is_some() => Err(label_needs_project("list_view")),
+            Flag::Filter(_) if *recurring => Err(error::new(
+                "list_view",
+                "--recurring can only be used with --project",
+            )),
+            Flag::Filter(_) if *completed => Err(error::new(
+                "list_view",
+                "--completed can only be used with --project",
+            )),
+            Flag::Filter(_) if *agenda => Err(error::new(
+                "list_view",
+                "--agenda can only be used with --project",
+            )),
+            Flag::Filter(filter) => filters::all_tasks(config, &filter, options).await,
+        }
+    })
+    .await;
+
+    write_output_if_requested(result, output, config).await
 }
 
 // --- PROJECT ---
@@ -561,12 +1251,16 @@ async fn list_view(cli: Cli, args: &ListView, tx: UnboundedSender<Error>) -> Res
 #[cfg(not(tarpaulin_include))]
 async fn project_list(
     cli: Cli,
-    _args: &ProjectList,
+    args: &ProjectList,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
+    let ProjectList { counts, completion } = args;
     let mut config = fetch_config(cli, tx).await?;
 
-    projects::list(&mut config).await
+    if *completion {
+        return Ok(projects::completion_list(&config));
+    }
+    projects::list(&mut config, *counts).await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -586,7 +1280,7 @@ async fn project_remove(
         (true, false) => projects::remove_all(&mut config).await,
         (false, true) => projects::remove_auto(&mut config).await,
         (false, false) => loop {
-            let project = match fetch_project(project, &config)? {
+            let project = match fetch_project(project, &mut config).await? {
                 Flag::Project(project) => project,
                 _ => unreachable!(),
             };
@@ -606,9 +1300,9 @@ async fn project_rename(
     args: &ProjectRename,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let config = fetch_config(cli, tx).await?;
-    let ProjectRename { project } = args;
-    let project = match fetch_project(project, &config)? {
+    let mut config = fetch_config(cli, tx).await?;
+    let ProjectRename { project, color } = args;
+    let project = match fetch_project(project, &mut config).await? {
         Flag::Project(project) => project,
         _ => unreachable!(),
     };
@@ -616,7 +1310,7 @@ async fn project_rename(
         &config,
         format!("Calling projects::rename with project:\n{project}"),
     );
-    projects::rename(config, &project).await
+    projects::rename(config, &project, color).await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -626,9 +1320,44 @@ async fn project_import(
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
     let mut config = fetch_config(cli, tx).await?;
-    let ProjectImport { auto } = args;
+    let ProjectImport { auto, select } = args;
 
-    projects::import(&mut config, auto).await
+    if *select {
+        projects::import_selected(&mut config).await
+    } else {
+        projects::import(&mut config, auto).await
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn project_create(
+    cli: Cli,
+    args: &ProjectCreate,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let ProjectCreate {
+        name,
+        color,
+        parent,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+    let name = fetch_string(name, &config, "PROJECT NAME")?;
+    let parent = match parent {
+        None => None,
+        Some(parent_name) => Some(
+            config
+                .projects
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|p| p.name == parent_name.as_str())
+                .ok_or_else(|| {
+                    error::new("project_create", "Could not find parent project in config")
+                })?,
+        ),
+    };
+
+    projects::create(&mut config, &name, color, &parent).await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -637,14 +1366,18 @@ async fn project_empty(
     args: &ProjectEmpty,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let ProjectEmpty { project } = args;
+    let ProjectEmpty {
+        project,
+        section,
+        verify,
+    } = args;
     let mut config = fetch_config(cli, tx.clone()).await?;
-    let project = match fetch_project(project, &config)? {
+    let project = match fetch_project(project, &mut config).await? {
         Flag::Project(project) => project,
         _ => unreachable!(),
     };
 
-    projects::empty(&mut config, &project).await
+    projects::empty(&mut config, &project, section, *verify).await
 }
 
 // --- LIST ---
@@ -673,12 +1406,23 @@ async fn list_process(
     args: &ListProcess,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let ListProcess { project, filter } = args;
-    let config = fetch_config(cli, tx).await?;
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Filter(filter) => filters::process_tasks(&config, &filter).await,
-        Flag::Project(project) => projects::process_tasks(&config, &project).await,
-    }
+    let ListProcess {
+        project,
+        filter,
+        no_recurring,
+    } = args;
+    let mut config = fetch_config(cli, tx).await?;
+    let flags = fetch_projects_or_filter(project, filter, &mut config).await?;
+    let config = &config;
+    for_each_flag(flags, |flag| async move {
+        match flag {
+            Flag::Filter(filter) => filters::process_tasks(config, &filter, *no_recurring).await,
+            Flag::Project(project) => {
+                projects::process_tasks(config, &project, *no_recurring).await
+            }
+        }
+    })
+    .await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -687,12 +1431,25 @@ async fn list_prioritize(
     args: &ListPrioritize,
     tx: UnboundedSender<Error>,
 ) -> Result<String, Error> {
-    let ListPrioritize { project, filter } = args;
-    let config = fetch_config(cli, tx.clone()).await?;
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Filter(filter) => filters::prioritize_tasks(&config, &filter).await,
-        Flag::Project(project) => projects::prioritize_tasks(&config, &project).await,
-    }
+    let ListPrioritize {
+        project,
+        filter,
+        rule,
+        label,
+    } = args;
+    let mut config = fetch_config(cli, tx.clone()).await?;
+    let flags = fetch_projects_or_filter(project, filter, &mut config).await?;
+    let config = &config;
+    for_each_flag(flags, |flag| async move {
+        match flag {
+            Flag::Filter(_) if label.is_some() => Err(label_needs_project("list_prioritize")),
+            Flag::Filter(filter) => filters::prioritize_tasks(config, &filter, rule).await,
+            Flag::Project(project) => {
+                projects::prioritize_tasks(config, &project, rule, label).await
+            }
+        }
+    })
+    .await
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -706,20 +1463,41 @@ async fn list_schedule(
         filter,
         skip_recurring,
         overdue,
+        label,
     } = args;
-    let config = fetch_config(cli, tx.clone()).await?;
-    match fetch_project_or_filter(project, filter, &config)? {
-        Flag::Filter(filter) => filters::schedule(&config, &filter).await,
-        Flag::Project(project) => {
-            let task_filter = if *overdue {
-                projects::TaskFilter::Overdue
-            } else {
-                projects::TaskFilter::Unscheduled
-            };
-
-            projects::schedule(&config, &project, task_filter, *skip_recurring).await
+    let mut config = fetch_config(cli, tx.clone()).await?;
+    let flags = fetch_projects_or_filter(project, filter, &mut config).await?;
+    let config = &config;
+    for_each_flag(flags, |flag| async move {
+        match flag {
+            Flag::Filter(_) if label.is_some() => Err(label_needs_project("list_schedule")),
+            Flag::Filter(filter) => filters::schedule(config, &filter, *skip_recurring).await,
+            Flag::Project(project) => {
+                let task_filter = if *overdue {
+                    projects::TaskFilter::Overdue
+                } else {
+                    projects::TaskFilter::Unscheduled
+                };
+
+                projects::schedule(config, &project, task_filter, *skip_recurring, label).await
+            }
         }
-    }
+    })
+    .await
+}
+
+// --- LABEL ---
+
+#[cfg(not(tarpaulin_include))]
+async fn label_rename(
+    cli: Cli,
+    args: &LabelRename,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let LabelRename { from, to } = args;
+    let config = fetch_config(cli, tx).await?;
+
+    labels::rename(&config, from, to).await
 }
 
 // // --- CONFIG ---
@@ -765,6 +1543,49 @@ async fn config_reset(
     }
 }
 
+#[cfg(not(tarpaulin_include))]
+async fn config_path(cli: Cli, _args: &ConfigPath) -> Result<String, Error> {
+    config::resolve_path(cli_config_path(&cli).await?).await
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn config_export(
+    cli: Cli,
+    args: &ConfigExport,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let ConfigExport {
+        file,
+        include_token,
+    } = args;
+    let config = fetch_config(cli, tx).await?;
+    let json = config.export(*include_token)?;
+
+    match file {
+        None => Ok(json),
+        Some(file) => {
+            tokio::fs::write(file, json).await?;
+            Ok(color::success_string(
+                &format!("✓ Exported to {file}"),
+                &config.colors,
+            ))
+        }
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn config_import(
+    cli: Cli,
+    args: &ConfigImport,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let ConfigImport { file } = args;
+    let config = fetch_config(cli, tx).await?;
+    let json = tokio::fs::read_to_string(file).await?;
+
+    config.import(&json)?.save().await
+}
+
 #[cfg(not(tarpaulin_include))]
 async fn tz_reset(
     cli: Cli,
@@ -782,18 +1603,173 @@ async fn tz_reset(
     }
 }
 
+// --- REPORT ---
+
+#[cfg(not(tarpaulin_include))]
+async fn report_completed(
+    cli: Cli,
+    args: &ReportCompleted,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let mut config = fetch_config(cli, tx).await?;
+    let ReportCompleted {
+        project,
+        since,
+        until,
+        week,
+        label,
+        output,
+    } = args;
+    let project = match project {
+        None => None,
+        Some(_) => match fetch_project(project, &mut config).await? {
+            Flag::Project(project) => Some(project),
+            _ => unreachable!(),
+        },
+    };
+    let (since, until) = match (week, since) {
+        (Some(week), _) => report::week_bounds(*week, time::today_date(&config)?),
+        (None, Some(since)) => {
+            let until = match until {
+                Some(until) => fetch_date(until, &config)?,
+                None => time::today_date(&config)?,
+            };
+            (fetch_date(since, &config)?, until)
+        }
+        (None, None) => unreachable!(),
+    };
+
+    let result = report::completed(&config, &project, since, until, label).await;
+    write_output_if_requested(result, output, &config).await
+}
+
+#[cfg(not(tarpaulin_include))]
+async fn report_priority(
+    cli: Cli,
+    args: &ReportPriority,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let mut config = fetch_config(cli, tx).await?;
+    let ReportPriority {
+        project,
+        label,
+        output,
+    } = args;
+    let project = match fetch_project(project, &mut config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+
+    let result = report::by_priority(&config, &project, label).await;
+    write_output_if_requested(result, output, &config).await
+}
+
+// --- SYNC ---
+
+#[cfg(not(tarpaulin_include))]
+async fn sync(cli: Cli, _args: &QueueSync, tx: UnboundedSender<Error>) -> Result<String, Error> {
+    let config = fetch_config(cli, tx).await?;
+
+    queue::sync(&config).await
+}
+
+// --- SETUP ---
+
+#[cfg(not(tarpaulin_include))]
+async fn setup(cli: Cli, _args: &Setup, tx: UnboundedSender<Error>) -> Result<String, Error> {
+    let path = config::resolve_path(cli_config_path(&cli).await?).await?;
+    let config = setup::run(setup::starting_config(path, tx).await?).await?;
+
+    Ok(color::success_string(
+        &format!("✓ Saved to {}", config.path),
+        &config.colors,
+    ))
+}
+
+// --- PING ---
+
+#[cfg(not(tarpaulin_include))]
+async fn ping(cli: Cli, _args: &Ping, tx: UnboundedSender<Error>) -> Result<String, Error> {
+    let config = fetch_config(cli, tx).await?;
+
+    todoist::ping(&config).await
+}
+
+// --- PRIORITY ---
+
+#[cfg(not(tarpaulin_include))]
+async fn priority_legend(
+    cli: Cli,
+    _args: &PriorityLegend,
+    tx: UnboundedSender<Error>,
+) -> Result<String, Error> {
+    let config = fetch_config(cli, tx).await?;
+
+    Ok(priority::legend(&config))
+}
+
 // --- VALUE HELPERS ---
 
+/// Todoist filters can already select labels with @name, so --label is for projects
+fn label_needs_project(source: &str) -> Error {
+    error::new(
+        source,
+        "--label can only be used with --project, add @label to the filter instead",
+    )
+}
+
+/// Sends the output of a command to the --output file when one is given
+async fn write_output_if_requested(
+    result: Result<String, Error>,
+    output: &Option<String>,
+    config: &Config,
+) -> Result<String, Error> {
+    match output {
+        None => result,
+        Some(file) => report::write_output(&result?, file, config).await,
+    }
+}
+
+/// An explicit --config wins over --profile
+#[cfg(not(tarpaulin_include))]
+async fn cli_config_path(cli: &Cli) -> Result<Option<String>, Error> {
+    match (&cli.config, &cli.profile) {
+        (Some(path), _) => Ok(Some(path.clone())),
+        (None, Some(profile)) => config::profile_path(profile).await.map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
 async fn fetch_config(cli: Cli, tx: UnboundedSender<Error>) -> Result<Config, Error> {
+    let config_path = cli_config_path(&cli).await?;
     let Cli {
         verbose,
-        config: config_path,
+        config: _,
+        profile: _,
         timeout,
+        quiet,
+        timezone,
+        dry_run,
+        fail_fast: _,
+        yes,
         command: _,
     } = cli;
 
-    let config = config::get_or_create(config_path, verbose, timeout, tx).await?;
+    let args = Args {
+        verbose,
+        timeout,
+        quiet,
+        dry_run,
+        yes,
+        timezone,
+    };
+    // New installs go through setup rather than being asked for each setting as it is needed
+    let path = config::resolve_path(config_path).await?;
+    if !std::path::Path::new(&path).exists() {
+        setup::run(setup::starting_config(path.clone(), tx.clone()).await?).await?;
+    }
+    let config = config::get_or_create(Some(path), args, tx).await?;
 
     let async_config = config.clone();
 
@@ -802,6 +1778,16 @@ async fn fetch_config(cli: Cli, tx: UnboundedSender<Error>) -> Result<Config, Er
     config.check_for_timezone().await
 }
 
+/// Lets clap reject unknown colors before anything is sent to Todoist
+fn parse_project_color(color: &str) -> Result<ProjectColor, String> {
+    color.parse().map_err(|e: Error| e.message)
+}
+
+/// Lets clap reject malformed rules before any tasks are fetched
+fn parse_priority_rule(rule: &str) -> Result<Rule, String> {
+    rule.parse().map_err(|e: Error| e.message)
+}
+
 #[cfg(not(tarpaulin_include))]
 fn fetch_string(
     maybe_string: &Option<String>,
@@ -814,9 +1800,28 @@ fn fetch_string(
     }
 }
 
+fn fetch_date(date: &str, config: &Config) -> Result<NaiveDate, Error> {
+    let date = &time::localized_date_to_iso(date, config);
+    if !time::is_date(date) {
+        return Err(error::new(
+            "fetch_date",
+            &format!("'{date}' is not a date in the format YYYY-MM-DD"),
+        ));
+    }
+
+    time::date_from_str(date, time::timezone_from_str(&config.timezone)?)
+}
+
 #[cfg(not(tarpaulin_include))]
-fn fetch_project(project: &Option<String>, config: &Config) -> Result<Flag, Error> {
+async fn fetch_project(project: &Option<String>, config: &mut Config) -> Result<Flag, Error> {
     let projects = config.projects.clone().unwrap_or_default();
+    if let Some(name) = project {
+        let in_config = projects.iter().any(|p| &p.name == name);
+        if projects::is_inbox_name(name) && !in_config {
+            return Ok(Flag::Project(projects::inbox(config).await?));
+        }
+    }
+
     if projects.is_empty() {
         return Err(error::new("fetch_project", NO_PROJECTS_ERR));
     }
@@ -825,21 +1830,83 @@ fn fetch_project(project: &Option<String>, config: &Config) -> Result<Flag, Erro
         return Ok(Flag::Project(projects.first().unwrap().clone()));
     }
 
-    match project {
+    let project = match project {
+        Some(pattern) if projects::is_pattern(pattern) => {
+            match projects::matching(&projects, pattern).as_slice() {
+                [project] => Ok(project.to_owned()),
+                [] => Err(no_matching_projects(pattern)),
+                matched => Err(error::new(
+                    "fetch_project",
+                    &format!(
+                        "'{pattern}' matches {} projects, this command needs exactly one",
+                        matched.len()
+                    ),
+                )),
+            }
+        }
         Some(project_name) => projects
             .iter()
             .find(|p| p.name == project_name.as_str())
             .map_or_else(
                 || {
-                    Err(error::new(
+                    Err(error::with_kind(
+                        Kind::NotFound,
                         "fetch_project",
                         "Could not find project in config",
                     ))
                 },
-                |p| Ok(Flag::Project(p.to_owned())),
+                |p| Ok(p.to_owned()),
             ),
-        None => input::select("Select project", projects, config.mock_select).map(Flag::Project),
+        None => projects::select_project(config, projects),
+    }?;
+
+    projects::remember_last_project(config, &project).await?;
+
+    Ok(Flag::Project(project))
+}
+
+fn no_matching_projects(pattern: &str) -> Error {
+    error::with_kind(
+        Kind::NotFound,
+        "fetch_project",
+        &format!("No projects in config match '{pattern}'"),
+    )
+}
+
+/// Every project matching a wildcard in --project, or the one project or filter as usual otherwise
+#[cfg(not(tarpaulin_include))]
+async fn fetch_projects_or_filter(
+    project: &Option<String>,
+    filter: &Option<String>,
+    config: &mut Config,
+) -> Result<Vec<Flag>, Error> {
+    match project {
+        Some(pattern) if projects::is_pattern(pattern) && filter.is_none() => {
+            let projects = config.projects.clone().unwrap_or_default();
+            let matched = projects::matching(&projects, pattern);
+            if matched.is_empty() {
+                return Err(no_matching_projects(pattern));
+            }
+            Ok(matched.into_iter().map(Flag::Project).collect())
+        }
+        _ => Ok(vec![
+            fetch_project_or_filter(project, filter, config).await?,
+        ]),
+    }
+}
+
+/// Runs the command for each project or filter from fetch_projects_or_filter
+#[cfg(not(tarpaulin_include))]
+async fn for_each_flag<F, Fut>(flags: Vec<Flag>, run: F) -> Result<String, Error>
+where
+    F: Fn(Flag) -> Fut,
+    Fut: Future<Output = Result<String, Error>>,
+{
+    let mut outputs = Vec::new();
+    for flag in flags {
+        outputs.push(run(flag).await?);
     }
+    Ok(outputs.join("\n\n"))
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -854,13 +1921,13 @@ fn fetch_filter(filter: &Option<String>, config: &Config) -> Result<Flag, Error>
 }
 
 #[cfg(not(tarpaulin_include))]
-fn fetch_project_or_filter(
+async fn fetch_project_or_filter(
     project: &Option<String>,
     filter: &Option<String>,
-    config: &Config,
+    config: &mut Config,
 ) -> Result<Flag, Error> {
     match (project, filter) {
-        (Some(_), None) => fetch_project(project, config),
+        (Some(_), None) => fetch_project(project, config).await,
         (None, Some(_)) => fetch_filter(filter, config),
         (Some(_), Some(_)) => Err(error::new(
             "project_or_filter",
@@ -869,7 +1936,7 @@ fn fetch_project_or_filter(
         (None, None) => {
             let options = vec![FlagOptions::Project, FlagOptions::Filter];
             match input::select("Select Project or Filter:", options, config.mock_select)? {
-                FlagOptions::Project => fetch_project(project, config),
+                FlagOptions::Project => fetch_project(project, config).await,
                 FlagOptions::Filter => fetch_filter(filter, config),
             }
         }
@@ -877,22 +1944,18 @@ fn fetch_project_or_filter(
 }
 
 #[cfg(not(tarpaulin_include))]
-fn fetch_priority(priority: &Option<u8>, config: &Config) -> Result<Priority, Error> {
-    match priority::from_integer(priority) {
+fn fetch_priority(
+    priority: &Option<u8>,
+    project: &Project,
+    config: &Config,
+) -> Result<Priority, Error> {
+    match priority::from_integer(priority).or_else(|| priority::project_default(config, project)) {
         Some(priority) => Ok(priority),
-        None => {
-            let options = vec![
-                Priority::None,
-                Priority::Low,
-                Priority::Medium,
-                Priority::High,
-            ];
-            input::select(
-                "Choose a priority that should be assigned to task:",
-                options,
-                config.mock_select,
-            )
-        }
+        None => input::select(
+            "Choose a priority that should be assigned to task:",
+            priority::prompt_order(config),
+            config.mock_select,
+        ),
     }
 }
 
@@ -920,3 +1983,114 @@ fn verify_cmd() {
     Cli::try_parse().err();
     Cli::command().debug_assert();
 }
+
+#[cfg(test)]
+async fn batch_with_failure(
+    tx: UnboundedSender<Error>,
+    completed: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) -> Result<String, Error> {
+    for number in 1..=3 {
+        if number == 1 {
+            tx.send(error::new("batch", "first failure")).unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        completed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+    Ok(String::from("done"))
+}
+
+#[tokio::test]
+async fn collect_errors_continues_by_default() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+    let completed = Arc::new(AtomicUsize::new(0));
+
+    let (result, errors) =
+        collect_errors(batch_with_failure(tx, completed.clone()), &mut rx, false).await;
+
+    assert_eq!(result, Ok(String::from("done")));
+    assert_eq!(errors, vec![error::new("batch", "first failure")]);
+    assert_eq!(completed.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn collect_errors_with_fail_fast_stops_at_first_error() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+    let completed = Arc::new(AtomicUsize::new(0));
+
+    let (result, errors) =
+        collect_errors(batch_with_failure(tx, completed.clone()), &mut rx, true).await;
+
+    assert_eq!(result, Err(error::new("batch", "first failure")));
+    assert!(errors.is_empty());
+    assert!(completed.load(Ordering::SeqCst) < 3);
+}
+
+#[test]
+fn cli_from_command_parses_the_default_command() {
+    let cli = cli_from_command("task next --project Work").unwrap();
+    assert_matches!(
+        cli.command,
+        Commands::Task(TaskCommands::Next(TaskNext { project: Some(project), .. })) if project == "Work"
+    );
+
+    let cli = cli_from_command("--verbose list view --filter \"today | overdue\"").unwrap();
+    assert!(cli.verbose);
+    assert_matches!(
+        cli.command,
+        Commands::List(ListCommands::View(ListView { filter: Some(filter), .. })) if filter == "today | overdue"
+    );
+
+    assert!(cli_from_command("task bogus").is_err());
+}
+
+#[test]
+fn list_view_rejects_flags_that_pick_different_lists() {
+    for flags in [
+        "--agenda --recurring",
+        "--agenda --due-before 2024-05-01",
+        "--recurring --due-before 2024-05-01",
+        "--raw --agenda",
+        "--raw --recurring",
+        "--raw --due-before 2024-05-01",
+    ] {
+        let kind = cli_from_command(&format!("list view {flags}"))
+            .err()
+            .map(|e| e.kind());
+        assert_eq!(kind, Some(clap::error::ErrorKind::ArgumentConflict));
+    }
+
+    assert!(cli_from_command("list view --agenda --stale-days 3").is_ok());
+}
+
+#[test]
+fn project_list_completion_cannot_have_counts() {
+    let kind = cli_from_command("project list --counts --completion")
+        .err()
+        .map(|e| e.kind());
+    assert_eq!(kind, Some(clap::error::ErrorKind::ArgumentConflict));
+}
+
+#[tokio::test]
+async fn fetch_priority_uses_project_default() {
+    let project = test::fixtures::project();
+    let config = Config {
+        project_priorities: Some(std::collections::HashMap::from([(project.name.clone(), 3)])),
+        ..test::fixtures::config().await
+    };
+
+    // No mock_select is set, so this would panic if it prompted
+    assert_eq!(
+        fetch_priority(&None, &project, &config),
+        Ok(Priority::Medium)
+    );
+    assert_eq!(
+        fetch_priority(&Some(4), &project, &config),
+        Ok(Priority::High)
+    );
+}
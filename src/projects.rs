@@ -1,17 +1,25 @@
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
 use futures::future;
 use pad::PadStr;
+use std::collections::HashMap;
 use std::fmt::Display;
 use tokio::task::JoinHandle;
 
+pub mod palette;
 use crate::config::Config;
-use crate::error::{self, Error};
-use crate::input::DateTimeInput;
-use crate::tasks::priority::Priority;
-use crate::tasks::{FormatType, Task};
-use crate::{color, input, tasks, todoist};
+use crate::error::{self, Error, Kind};
+use crate::sections::Section;
+use crate::tasks::priority::{Priority, Rule};
+use crate::tasks::{FormatType, ListOptions, Processed, Task};
+use crate::{color, input, tasks, time, todoist};
+use palette::ProjectColor;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 const PAD_WIDTH: usize = 30;
+/// How far back `list view --completed` looks
+const RECENTLY_COMPLETED_DAYS: i64 = 7;
 
 // Projects are split into sections
 #[derive(PartialEq, Eq, Serialize, Deserialize, Clone, Debug)]
@@ -37,6 +45,8 @@ pub enum TaskFilter {
     Overdue,
     /// Is a repeating task
     Recurring,
+    /// Has neither a priority nor a date, so hasn't been triaged at all
+    Untriaged,
 }
 
 impl Display for Project {
@@ -49,60 +59,121 @@ pub fn json_to_projects(json: String) -> Result<Vec<Project>, Error> {
     Ok(projects)
 }
 
-/// List the projects in config with task counts
-pub async fn list(config: &mut Config) -> Result<String, Error> {
+pub fn json_to_project(json: String) -> Result<Project, Error> {
+    let project: Project = serde_json::from_str(&json)?;
+    Ok(project)
+}
+
+/// Lists the projects in config, with the number of open tasks in each when counts is true
+pub async fn list(config: &mut Config, counts: bool) -> Result<String, Error> {
     config.reload_projects().await?;
 
     if let Some(projects) = config.projects.clone() {
-        let mut project_handles = Vec::new();
-
-        for project in projects {
-            let config = config.clone();
-            let handle =
-                tokio::spawn(async move { project_name_with_count(&config, &project).await });
-
-            project_handles.push(handle);
-        }
-
-        let mut projects: Vec<String> = future::join_all(project_handles)
-            .await
-            .into_iter()
-            .map(|p| p.unwrap_or_default())
-            .collect();
         if projects.is_empty() {
             return Ok(String::from("No projects found"));
         }
-        projects.sort();
-        let mut buffer = String::new();
-        buffer.push_str(&color::green_string("Projects").pad_to_width(PAD_WIDTH + 5));
-        buffer.push_str(&color::green_string("# Tasks"));
 
-        for key in projects {
-            buffer.push_str("\n - ");
-            buffer.push_str(&key);
+        let mut buffer = String::new();
+        if counts {
+            buffer.push_str(
+                &color::success_string("Projects", &config.colors).pad_to_width(PAD_WIDTH + 5),
+            );
+            buffer.push_str(&color::success_string("# Tasks", &config.colors));
+            let counts = fetch_task_counts(config, &projects).await;
+            buffer.push_str(&format_project_tree(&projects, &counts));
+        } else {
+            buffer.push_str(&color::success_string("Projects", &config.colors));
+            buffer.push_str(&format_project_tree(&projects, &HashMap::new()));
         }
+
         Ok(buffer)
     } else {
         Ok(String::from("No projects found"))
     }
 }
 
-/// Formats a string with project name and the count that is a standard length
-async fn project_name_with_count(config: &Config, project: &Project) -> String {
-    let count = match count_processable_tasks(config, project).await {
-        Ok(num) => format!("{}", num),
-        Err(_) => String::new(),
-    };
+/// Project names one per line for shell completion scripts. Reads config only so completing is instant.
+pub fn completion_list(config: &Config) -> String {
+    let mut names: Vec<String> = config
+        .projects
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|project| project.name)
+        .collect();
+    names.sort();
+    names.join("\n")
+}
+
+/// Formats projects as an indented tree with children beneath their parents and siblings sorted by name
+fn format_project_tree(projects: &[Project], counts: &HashMap<String, String>) -> String {
+    let project_ids: Vec<&String> = projects.iter().map(|p| &p.id).collect();
+    let mut roots: Vec<&Project> = projects
+        .iter()
+        .filter(|p| match &p.parent_id {
+            None => true,
+            Some(parent_id) => !project_ids.contains(&parent_id),
+        })
+        .collect();
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut buffer = String::new();
+    for project in roots {
+        push_project_branch(&mut buffer, project, projects, counts, 0);
+    }
+    buffer
+}
+
+/// Adds a project and all of its children to the buffer, keeping the counts aligned
+fn push_project_branch(
+    buffer: &mut String,
+    project: &Project,
+    projects: &[Project],
+    counts: &HashMap<String, String>,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let count = counts.get(&project.id).cloned().unwrap_or_default();
+    let swatch = palette::swatch_for(&project.color);
+    let width = PAD_WIDTH.saturating_sub(indent.len() + 2);
+    buffer.push_str(&format!(
+        "\n{indent} - {swatch} {}{count}",
+        project.name.pad_to_width(width)
+    ));
+
+    let mut children: Vec<&Project> = projects
+        .iter()
+        .filter(|p| p.parent_id.as_ref() == Some(&project.id))
+        .collect();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
 
-    format!("{}{}", project.name.pad_to_width(PAD_WIDTH), count)
+    for child in children {
+        push_project_branch(buffer, child, projects, counts, depth + 1);
+    }
 }
 
-/// Gets the number of tasks for a project that are not in the future
-async fn count_processable_tasks(config: &Config, project: &Project) -> Result<u8, Error> {
-    let all_tasks = todoist::tasks_for_project(config, project).await?;
-    let count = tasks::filter_not_in_future(all_tasks, config)?.len();
+/// Fetches the open task count for every project at once, using (?) for any that fail
+async fn fetch_task_counts(config: &Config, projects: &[Project]) -> HashMap<String, String> {
+    let mut handles = Vec::new();
+
+    for project in projects.iter().cloned() {
+        let config = config.clone();
+        let handle = tokio::spawn(async move {
+            let count = match todoist::tasks_for_project(&config, &project).await {
+                Ok(tasks) => format!("({})", tasks.len()),
+                Err(_) => String::from("(?)"),
+            };
+            (project.id, count)
+        });
+
+        handles.push(handle);
+    }
 
-    Ok(count as u8)
+    future::join_all(handles)
+        .await
+        .into_iter()
+        .filter_map(|c| c.ok())
+        .collect()
 }
 
 /// Add a project to the projects HashMap in Config
@@ -111,20 +182,67 @@ pub async fn add(config: &mut Config, project: &Project) -> Result<String, Error
     config.save().await
 }
 
+/// Create a project in Todoist and add it to config
+pub async fn create(
+    config: &mut Config,
+    name: &str,
+    color: &Option<ProjectColor>,
+    parent: &Option<Project>,
+) -> Result<String, Error> {
+    let project = todoist::add_project(config, name, color, parent).await?;
+    add(config, &project).await
+}
+
+/// Create a project in Todoist and config for a task that is about to be added, after confirming
+pub async fn create_for_task(config: &mut Config, name: &str) -> Result<Project, Error> {
+    if config
+        .projects
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .any(|project| project.name == name)
+    {
+        return Err(error::new(
+            "project_new",
+            &format!("'{name}' is already in config, use --project instead"),
+        ));
+    }
+
+    let confirmed = config.confirm(&format!("Create project '{name}' in Todoist?"), true)?;
+    if !confirmed {
+        return Err(error::new("project_new", "Cancelled, no task was created"));
+    }
+
+    let project = todoist::add_project(config, name, &None, &None).await?;
+    add(config, &project).await?;
+    Ok(project)
+}
+
 /// Remove a project from the projects HashMap in Config
 pub async fn remove(config: &mut Config, project: &Project) -> Result<String, Error> {
     config.remove_project(project);
     config.save().await
 }
 
-/// Rename a project in config
-pub async fn rename(config: Config, project: &Project) -> Result<String, Error> {
+/// Rename a project in config, optionally changing its color
+pub async fn rename(
+    config: Config,
+    project: &Project,
+    color: &Option<ProjectColor>,
+) -> Result<String, Error> {
     let new_name = input::string_with_default("Input new project name", &project.name)?;
 
     let mut config = config;
+    if let Some(color) = color {
+        todoist::update_project_color(&config, project, color).await?;
+    }
 
     let new_project = Project {
         name: new_name,
+        color: match color {
+            Some(color) => color.to_string(),
+            None => project.color.clone(),
+        },
         ..project.clone()
     };
     remove(&mut config, project).await?;
@@ -132,27 +250,66 @@ pub async fn rename(config: Config, project: &Project) -> Result<String, Error>
 }
 
 /// Get the next task by priority and save its id to config
-pub async fn next_task(config: Config, project: &Project) -> Result<String, Error> {
-    match fetch_next_task(&config, project).await {
-        Ok(Some((task, remaining))) => {
+pub async fn next_task(
+    config: Config,
+    project: &Project,
+    exclude_labels: &[String],
+    only_actionable: bool,
+) -> Result<String, Error> {
+    let tasks = fetch_next_tasks(&config, project, exclude_labels).await?;
+    let tasks = if only_actionable {
+        tasks::filter_actionable(tasks, &config)
+    } else {
+        tasks
+    };
+    match tasks.split_first() {
+        Some((task, upcoming)) => {
             config.set_next_id(&task.id).save().await?;
             let task_string = task.fmt(&config, FormatType::Single, false);
-            Ok(format!("{task_string}\n{remaining} task(s) remaining"))
+            let url = tasks::maybe_url_line(&config, task);
+            let preview = tasks::preview_lines(&config, upcoming);
+            let remaining = tasks::maybe_remaining_line(&config, tasks.len());
+            Ok(format!("{task_string}{url}{preview}{remaining}"))
         }
-        Ok(None) => Ok(color::green_string("No tasks on list")),
-        Err(e) => Err(e),
+        None => Ok(color::success_string("No tasks on list", &config.colors)),
     }
 }
 
-async fn fetch_next_task(
+/// Tasks that are not in the future, with the next task first
+async fn fetch_next_tasks(
     config: &Config,
     project: &Project,
-) -> Result<Option<(Task, usize)>, Error> {
+    exclude_labels: &[String],
+) -> Result<Vec<Task>, Error> {
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::reject_excluded_labels(tasks, config, exclude_labels);
     let filtered_tasks = tasks::filter_not_in_future(tasks, config)?;
-    let tasks = tasks::sort_by_value(filtered_tasks, config);
 
-    Ok(tasks.first().map(|task| (task.to_owned(), tasks.len())))
+    Ok(tasks::sort_by_value(filtered_tasks, config))
+}
+
+/// Prompt for a project with the one used last already selected
+pub fn select_project(config: &Config, projects: Vec<Project>) -> Result<Project, Error> {
+    let default = projects
+        .iter()
+        .position(|p| Some(&p.id) == config.last_project.as_ref())
+        .unwrap_or_default();
+
+    input::select_with_default("Select project", projects, default, config.mock_select)
+}
+
+/// Saves the project so that it is selected first the next time the prompt is shown
+pub async fn remember_last_project(config: &mut Config, project: &Project) -> Result<(), Error> {
+    if config.last_project.as_ref() == Some(&project.id) {
+        return Ok(());
+    }
+
+    *config = config.clone().set_last_project(project);
+    if !config.args.dry_run {
+        config.save().await?;
+    }
+
+    Ok(())
 }
 
 /// Removes all projects from config that don't exist in Todoist
@@ -161,37 +318,43 @@ pub async fn remove_auto(config: &mut Config) -> Result<String, Error> {
     let missing_projects = filter_missing_projects(config, projects);
 
     if missing_projects.is_empty() {
-        return Ok(color::green_string("No projects to auto remove"));
+        return Ok(color::success_string(
+            "No projects to auto remove",
+            &config.colors,
+        ));
     }
 
-    for project in &missing_projects {
-        config.remove_project(project);
-    }
-    config.save().await?;
     let project_names = missing_projects
         .iter()
         .map(|p| p.name.clone())
         .collect::<Vec<String>>()
         .join(", ");
+
+    if config.args.dry_run {
+        return Ok(format!("Would remove: '{project_names}'"));
+    }
+
+    for project in &missing_projects {
+        config.remove_project(project);
+    }
+    config.save().await?;
     let message = format!("Auto removed: '{project_names}'");
-    Ok(color::green_string(&message))
+    Ok(color::success_string(&message, &config.colors))
 }
 
 /// Removes all projects from config
 pub async fn remove_all(config: &mut Config) -> Result<String, Error> {
-    let options = vec!["Cancel", "Confirm"];
-    let selection = input::select(
-        "Confirm removing all projects from config",
-        options,
-        config.mock_select,
-    )?;
+    let confirmed = config.confirm("Remove all projects from config?", false)?;
 
-    if selection == "Cancel" {
+    if !confirmed {
         return Ok(String::from("Cancelled"));
     }
 
     if config.projects.clone().unwrap_or_default().is_empty() {
-        return Ok(color::green_string("No projects to remove"));
+        return Ok(color::success_string(
+            "No projects to remove",
+            &config.colors,
+        ));
     }
 
     for project in &config.projects.clone().unwrap_or_default() {
@@ -199,7 +362,64 @@ pub async fn remove_all(config: &mut Config) -> Result<String, Error> {
     }
     config.save().await?;
     let message = String::from("Removed all projects from config");
-    Ok(color::green_string(&message))
+    Ok(color::success_string(&message, &config.colors))
+}
+
+/// Tick the projects to import on one screen, saving config once at the end
+pub async fn import_selected(config: &mut Config) -> Result<String, Error> {
+    let projects = todoist::projects(config).await?;
+    let new_projects = filter_new_projects(config, projects);
+    if new_projects.is_empty() {
+        return Ok(color::success_string("No more projects", &config.colors));
+    }
+
+    let selected = input::multi_select(
+        "Select projects to import",
+        new_projects,
+        config.mock_multiselect.clone(),
+    )?;
+    let count = selected.len();
+    for project in selected {
+        config.add_project(project);
+    }
+    config.save().await?;
+
+    Ok(color::success_string(
+        &format!("Imported {count} projects"),
+        &config.colors,
+    ))
+}
+
+/// Whether a --project value refers to the inbox, which every account has
+pub fn is_inbox_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "inbox" || name == "i"
+}
+
+/// The account's inbox, fetched from Todoist the first time and cached in config after that
+pub async fn inbox(config: &mut Config) -> Result<Project, Error> {
+    if let Some(inbox) = &config.inbox {
+        return Ok(inbox.to_owned());
+    }
+
+    let inbox = todoist::projects(config)
+        .await?
+        .into_iter()
+        .find(|p| p.is_inbox_project)
+        .ok_or_else(|| {
+            error::with_kind(
+                Kind::NotFound,
+                "inbox",
+                "Could not find an inbox in Todoist",
+            )
+        })?;
+
+    *config = config.clone().set_inbox(&inbox);
+    if !config.args.dry_run {
+        config.save().await?;
+    }
+
+    Ok(inbox)
 }
 
 /// Returns the projects that are not already in config
@@ -221,11 +441,11 @@ pub async fn import(config: &mut Config, auto: &bool) -> Result<String, Error> {
     for project in new_projects {
         maybe_add_project(config, project, auto).await?;
     }
-    Ok(color::green_string("No more projects"))
+    Ok(color::success_string("No more projects", &config.colors))
 }
 
 /// Returns the projects that are not already in config
-fn filter_new_projects(config: &Config, projects: Vec<Project>) -> Vec<Project> {
+pub fn filter_new_projects(config: &Config, projects: Vec<Project>) -> Vec<Project> {
     let project_ids: Vec<String> = config
         .projects
         .clone()
@@ -269,24 +489,42 @@ async fn maybe_add_project(
 }
 
 /// Get next tasks and give an interactive prompt for completing them one by one
-pub async fn process_tasks(config: &Config, project: &Project) -> Result<String, Error> {
+pub async fn process_tasks(
+    config: &Config,
+    project: &Project,
+    no_recurring: bool,
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::maybe_reject_recurring(tasks, no_recurring);
     let tasks = tasks::filter_not_in_future(tasks, config)?;
     let tasks = tasks::sort_by_value(tasks, config);
-    let tasks = tasks::reject_parent_tasks(tasks, config).await;
+    let mut tasks = tasks::reject_parent_tasks(tasks, config).await;
     let mut task_count = tasks.len() as i32;
     let mut handles = Vec::new();
-    for task in tasks {
+    let mut index = 0;
+    config.cancel_on_ctrl_c();
+    while let Some(task) = tasks.get(index).cloned() {
+        if config.is_cancelled() {
+            return tasks::save_interrupted(config, handles).await;
+        }
         match tasks::process_task(&config.reload().await?, task, &mut task_count, false).await {
-            Some(handle) => handles.push(handle),
-            None => return Ok(color::green_string("Exited")),
+            Processed::Handle(handle) => handles.push(handle),
+            Processed::Jump(text) => {
+                if !tasks::jump_to(&mut tasks, index, &text) {
+                    println!("{}", tasks::no_task_to_jump_to(&text));
+                }
+                continue;
+            }
+            Processed::Quit => return Ok(color::success_string("Exited", &config.colors)),
         }
+        index += 1;
     }
     future::join_all(handles).await;
     let project_name = project.clone().name;
-    Ok(color::green_string(&format!(
-        "There are no more tasks in '{project_name}'"
-    )))
+    Ok(color::success_string(
+        &format!("There are no more tasks in '{project_name}'"),
+        &config.colors,
+    ))
 }
 
 pub async fn rename_task(config: &Config, project: &Project) -> Result<String, Error> {
@@ -302,40 +540,261 @@ pub async fn rename_task(config: &Config, project: &Project) -> Result<String, E
     let new_task_content = input::string_with_default("Edit the task you selected:", task_content)?;
 
     if task_content == new_task_content {
-        return Ok(color::green_string(
+        return Ok(color::success_string(
             "The content is the same, no need to change it",
+            &config.colors,
         ));
     }
 
     todoist::update_task_name(config, selected_task, new_task_content).await
 }
 
+/// Choose a task of the project and add a line to its description
+pub async fn append_task_description(
+    config: &Config,
+    project: &Project,
+    note: &str,
+) -> Result<String, Error> {
+    let project_tasks = todoist::tasks_for_project(config, project).await?;
+
+    let selected_task = input::select(
+        "Choose a task of the project:",
+        project_tasks,
+        config.mock_select,
+    )?;
+
+    tasks::append_to_description(config, &selected_task, note).await
+}
+
 /// All tasks for a project
-pub async fn all_tasks(config: &Config, project: &Project) -> Result<String, Error> {
+pub async fn all_tasks(
+    config: &Config,
+    project: &Project,
+    options: &ListOptions,
+) -> Result<String, Error> {
+    let ListOptions {
+        format: list_format,
+        created_since,
+        due_before,
+        section,
+        label,
+        stale_days,
+        recurring,
+        untriaged,
+        agenda,
+        ..
+    } = options;
+    let config = &with_collaborators(config, project).await?;
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::filter_by_label(tasks, label);
+    let tasks = tasks::maybe_only_untriaged(tasks, config, *untriaged);
+    let tasks = match created_since {
+        Some(date) => tasks::filter_created_since(tasks, *date, config),
+        None => tasks,
+    };
+    let tasks = match due_before {
+        Some(date) => tasks::filter_due_before(tasks, *date, config),
+        None => tasks,
+    };
+    let tasks = match stale_days {
+        Some(days) => tasks::mark_stale(tasks, config, *days),
+        None => tasks,
+    };
+    let (tasks, scope) = match section {
+        None => (tasks, project.name.clone()),
+        Some(section_name) => {
+            let section = find_section(config, project, section_name).await?;
+            let scope = format!("{}/{}", project.name, section.name);
+            (tasks_in_section(tasks, &section), scope)
+        }
+    };
 
-    let mut buffer = String::new();
-    buffer.push_str(&color::green_string(&format!(
-        "Tasks for '{}'",
-        project.name
-    )));
+    if *recurring {
+        let tasks: Vec<Task> = tasks
+            .into_iter()
+            .filter(|task| task.filter(config, &TaskFilter::Recurring))
+            .collect();
+        let tasks = tasks::sort_by_due(tasks, config);
+        let title = format!("Recurring tasks for '{scope}'");
+        return Ok(tasks::format_sorted_list(
+            config,
+            &title,
+            tasks,
+            list_format,
+            false,
+        ));
+    }
+
+    if *agenda {
+        let title = format!("Agenda for '{scope}'");
+        return Ok(tasks::format_agenda(config, &title, tasks, list_format));
+    }
+
+    if let Some(date) = due_before {
+        let tasks = tasks::sort_by_due(tasks, config);
+        let title = format!("Tasks due before {date} for '{scope}'");
+        return Ok(tasks::format_sorted_list(
+            config,
+            &title,
+            tasks,
+            list_format,
+            false,
+        ));
+    }
+
+    Ok(tasks::format_list(
+        config,
+        &format!("Tasks for '{scope}'"),
+        tasks,
+        list_format,
+        false,
+    ))
+}
+
+/// Tasks completed in the project over the last few days with their completion time, newest first
+pub async fn recently_completed(config: &Config, project: &Project) -> Result<String, Error> {
+    recently_completed_until(config, project, time::now(config)?).await
+}
+
+async fn recently_completed_until(
+    config: &Config,
+    project: &Project,
+    until: DateTime<Tz>,
+) -> Result<String, Error> {
+    let since = until - Duration::days(RECENTLY_COMPLETED_DAYS);
+    let mut tasks: Vec<_> = todoist::completed_tasks(config, &Some(project.clone()), since, until)
+        .await?
+        .into_iter()
+        .filter(|task| match task.get_completed_at(config) {
+            Ok(completed_at) => completed_at >= since && completed_at < until,
+            Err(_) => false,
+        })
+        .collect();
+
+    if tasks.is_empty() {
+        return Ok(format!(
+            "No tasks completed in the last {RECENTLY_COMPLETED_DAYS} days for '{}'",
+            project.name
+        ));
+    }
 
-    for task in tasks::sort_by_datetime(tasks, config) {
-        buffer.push('\n');
-        buffer.push_str(&task.fmt(config, FormatType::List, false));
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.get_completed_at(config).ok()));
+    let mut buffer = color::success_string(
+        &format!(
+            "Completed in the last {RECENTLY_COMPLETED_DAYS} days for '{}'",
+            project.name
+        ),
+        &config.colors,
+    );
+    for task in tasks {
+        let completed_at = task
+            .get_completed_at(config)
+            .map(|datetime| datetime.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        buffer.push_str(&format!(
+            "\n- {} {}",
+            color::purple_string(&completed_at),
+            task.content
+        ));
     }
+
     Ok(buffer)
 }
 
-/// Empty a project by sending tasks to other projects one at a time
-pub async fn empty(config: &mut Config, project: &Project) -> Result<String, Error> {
+/// Shared projects need their collaborators to show who tasks are assigned to
+async fn with_collaborators(config: &Config, project: &Project) -> Result<Config, Error> {
+    if !project.is_shared {
+        return Ok(config.clone());
+    }
+
+    let collaborators = todoist::collaborators_for_project(config, &project.id).await?;
+    Ok(config.clone().with_collaborators(&collaborators))
+}
+
+/// True when a project name given on the command line uses * or ? wildcards
+pub fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Projects whose names match the pattern, where * matches any run of characters and ? matches one
+pub fn matching(projects: &[Project], pattern: &str) -> Vec<Project> {
+    let regex: String = pattern
+        .chars()
+        .map(|char| match char {
+            '*' => String::from(".*"),
+            '?' => String::from("."),
+            char => regex::escape(&char.to_string()),
+        })
+        .collect();
+    let regex = Regex::new(&format!("^{regex}$")).unwrap();
+
+    projects
+        .iter()
+        .filter(|project| regex.is_match(&project.name))
+        .cloned()
+        .collect()
+}
+
+/// Look up a section of the project by name
+async fn find_section(
+    config: &Config,
+    project: &Project,
+    section_name: &str,
+) -> Result<Section, Error> {
+    let sections = todoist::sections_for_project(config, project).await?;
+    section_by_name(sections, section_name, project)
+}
+
+/// Errors instead of panicking when the section is gone, i.e. deleted while a prompt was open
+fn section_by_name(
+    sections: Vec<Section>,
+    section_name: &str,
+    project: &Project,
+) -> Result<Section, Error> {
+    sections
+        .into_iter()
+        .find(|s| s.name == section_name)
+        .ok_or_else(|| {
+            error::with_kind(
+                Kind::NotFound,
+                "find_section",
+                &format!(
+                    "Could not find section '{section_name}' in '{}'",
+                    project.name
+                ),
+            )
+        })
+}
+
+fn tasks_in_section(tasks: Vec<Task>, section: &Section) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| task.section_id.as_ref() == Some(&section.id))
+        .collect()
+}
+
+/// Empty a project by sending tasks to other projects one at a time, optionally only from one section
+pub async fn empty(
+    config: &mut Config,
+    project: &Project,
+    section: &Option<String>,
+    verify: bool,
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let (tasks, name) = match section {
+        None => (tasks, project.name.clone()),
+        Some(section_name) => {
+            let section = find_section(config, project, section_name).await?;
+            let name = format!("{}/{}", project.name, section.name);
+            (tasks_in_section(tasks, &section), name)
+        }
+    };
 
     if tasks.is_empty() {
-        Ok(color::green_string(&format!(
-            "No tasks to empty from '{}'",
-            project.name
-        )))
+        Ok(color::success_string(
+            &format!("No tasks to empty from '{name}'"),
+            &config.colors,
+        ))
     } else {
         let tasks = tasks
             .into_iter()
@@ -344,34 +803,51 @@ pub async fn empty(config: &mut Config, project: &Project) -> Result<String, Err
 
         let mut handles = Vec::new();
         for task in tasks.iter() {
-            match move_task_to_project(config, task.to_owned()).await {
+            match move_task_to_project(config, task.to_owned(), verify).await {
                 Ok(handle) => handles.push(handle),
                 Err(e) => return Err(e),
             };
         }
         future::join_all(handles).await;
-        Ok(color::green_string(&format!(
-            "Successfully emptied '{}'",
-            project.name
-        )))
+        Ok(color::success_string(
+            &format!("Successfully emptied '{name}'"),
+            &config.colors,
+        ))
     }
 }
 
-/// Prioritize all unprioritized tasks in a project
-pub async fn prioritize_tasks(config: &Config, project: &Project) -> Result<String, Error> {
+/// Prioritize all unprioritized tasks in a project, or every task matching one of the rules
+pub async fn prioritize_tasks(
+    config: &Config,
+    project: &Project,
+    rules: &[Rule],
+    label: &Option<String>,
+) -> Result<String, Error> {
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::filter_by_label(tasks, label);
 
+    if !rules.is_empty() {
+        let count = tasks::prioritize_by_rules(config, tasks, rules).await;
+        return Ok(color::success_string(
+            &format!("Prioritized {count} task(s) in '{}'", project.name),
+            &config.colors,
+        ));
+    }
+
+    let total = tasks.len();
     let unprioritized_tasks: Vec<Task> = tasks
         .into_iter()
         .filter(|task| task.priority == Priority::None)
         .collect::<Vec<Task>>();
 
     if unprioritized_tasks.is_empty() {
-        Ok(color::green_string(&format!(
-            "No tasks to prioritize in '{}'",
-            project.name
-        )))
+        Ok(color::success_string(
+            &format!("No tasks to prioritize in '{}'", project.name),
+            &config.colors,
+        ))
     } else {
+        let remaining = unprioritized_tasks.len();
+        println!("{}", prioritize_summary(total - remaining, remaining));
         let mut handles = Vec::new();
         for task in unprioritized_tasks.iter() {
             let handle = tasks::set_priority(config, task.to_owned(), false).await?;
@@ -379,21 +855,28 @@ pub async fn prioritize_tasks(config: &Config, project: &Project) -> Result<Stri
         }
 
         future::join_all(handles).await;
-        Ok(color::green_string(&format!(
-            "Successfully prioritized '{}'",
-            project.name
-        )))
+        Ok(color::success_string(
+            &format!("Successfully prioritized '{}'", project.name),
+            &config.colors,
+        ))
     }
 }
 
+/// Shown before prioritizing so a mostly triaged project doesn't look stuck
+fn prioritize_summary(prioritized: usize, remaining: usize) -> String {
+    format!("{prioritized} already prioritized, {remaining} to go")
+}
+
 /// Put dates on all tasks without dates
 pub async fn schedule(
     config: &Config,
     project: &Project,
     filter: TaskFilter,
     skip_recurring: bool,
+    label: &Option<String>,
 ) -> Result<String, Error> {
     let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::filter_by_label(tasks, label);
 
     let filtered_tasks: Vec<Task> = if skip_recurring {
         tasks
@@ -410,58 +893,37 @@ pub async fn schedule(
     };
 
     if filtered_tasks.is_empty() {
-        Ok(color::green_string(&format!(
-            "No tasks to schedule in '{}'",
-            project.name
-        )))
+        Ok(color::success_string(
+            &format!("No tasks to schedule in '{}'", project.name),
+            &config.colors,
+        ))
     } else {
-        let mut handles = Vec::new();
-        for task in filtered_tasks.iter() {
-            println!("{}", task.fmt(config, FormatType::Single, false));
-            let datetime_input = input::datetime(
-                config.mock_select,
-                config.mock_string.clone(),
-                config.natural_language_only,
-            )?;
-            match datetime_input {
-                input::DateTimeInput::Complete => {
-                    let handle = tasks::spawn_complete_task(config.clone(), task.clone());
-                    handles.push(handle);
-                }
-
-                DateTimeInput::Skip => (),
-
-                input::DateTimeInput::Text(due_string) => {
-                    let handle =
-                        tasks::spawn_update_task_due(config.clone(), task.clone(), due_string);
-                    handles.push(handle);
-                }
-                input::DateTimeInput::None => {
-                    let handle = tasks::spawn_update_task_due(
-                        config.clone(),
-                        task.clone(),
-                        "No date".to_string(),
-                    );
-                    handles.push(handle);
-                }
-            };
-        }
-
-        future::join_all(handles).await;
-        Ok(color::green_string(&format!(
-            "Successfully scheduled tasks in '{}'",
-            project.name
-        )))
+        tasks::schedule(config, filtered_tasks, false).await?;
+        Ok(color::success_string(
+            &format!("Successfully scheduled tasks in '{}'", project.name),
+            &config.colors,
+        ))
     }
 }
 
-pub async fn move_task_to_project(config: &Config, task: Task) -> Result<JoinHandle<()>, Error> {
+/// Prompt for what to do with a task, with verify the task is fetched again after a move to check it moved
+pub async fn move_task_to_project(
+    config: &Config,
+    task: Task,
+    verify: bool,
+) -> Result<JoinHandle<()>, Error> {
     println!("{}", task.fmt(config, FormatType::Single, false));
 
-    let options = ["Pick project", "Complete", "Skip", "Delete"]
-        .iter()
-        .map(|o| o.to_string())
-        .collect::<Vec<String>>();
+    let options = [
+        "Pick project",
+        "Prioritize and pick project",
+        "Complete",
+        "Skip",
+        "Delete",
+    ]
+    .iter()
+    .map(|o| o.to_string())
+    .collect::<Vec<String>>();
     let selection = input::select("Choose", options, config.mock_select)?;
 
     match selection.as_str() {
@@ -469,48 +931,104 @@ pub async fn move_task_to_project(config: &Config, task: Task) -> Result<JoinHan
 
         "Delete" => Ok(tasks::spawn_delete_task(config.clone(), task)),
         "Skip" => Ok(tokio::spawn(async move {})),
-        _ => {
-            let projects = config.projects.clone().unwrap_or_default();
-            let project = input::select("Select project", projects, config.mock_select)?;
-
-            let sections = todoist::sections_for_project(config, &project).await?;
-            let section_names: Vec<String> = sections.clone().into_iter().map(|x| x.name).collect();
-            if section_names.is_empty() || config.no_sections.unwrap_or_default() {
-                let config = config.clone();
-                Ok(tokio::spawn(async move {
-                    if let Err(e) =
-                        todoist::move_task_to_project(&config, task, &project, false).await
-                    {
-                        config.tx().send(e).unwrap();
-                    }
-                }))
-            } else {
-                let section_name =
-                    input::select("Select section", section_names, config.mock_select)?;
-                let section = sections
-                    .iter()
-                    .find(|x| x.name == section_name.as_str())
-                    .expect("Section does not exist")
-                    .clone();
-                let config = config.clone();
-                Ok(tokio::spawn(async move {
-                    if let Err(e) =
-                        todoist::move_task_to_section(&config, task, &section, false).await
-                    {
-                        config.tx().send(e).unwrap();
-                    }
-                }))
-            }
+        "Prioritize and pick project" => {
+            let priority = tasks::select_priority(config)?;
+            let priority_handle =
+                tasks::spawn_update_task_priority(config.clone(), task.clone(), priority);
+            let move_handle = move_to_selected_project(config, task, verify).await?;
+
+            Ok(tokio::spawn(async move {
+                let _ = tokio::join!(priority_handle, move_handle);
+            }))
         }
+        _ => move_to_selected_project(config, task, verify).await,
+    }
+}
+
+/// Prompt for a project (and section if there are any) and move the task there
+async fn move_to_selected_project(
+    config: &Config,
+    task: Task,
+    verify: bool,
+) -> Result<JoinHandle<()>, Error> {
+    let projects = config.projects.clone().unwrap_or_default();
+    let project = input::select("Select project", projects, config.mock_select)?;
+
+    let sections = todoist::sections_for_project(config, &project).await?;
+    let section_names: Vec<String> = sections.clone().into_iter().map(|x| x.name).collect();
+    if section_names.is_empty() || config.no_sections.unwrap_or_default() {
+        let config = config.clone();
+        Ok(tokio::spawn(async move {
+            let task_id = task.id.clone();
+            let moved = todoist::move_task_to_project(&config, task, &project, false).await;
+            report_move(&config, moved, &task_id, &project.id, verify).await;
+        }))
+    } else {
+        let section_name = input::select("Select section", section_names, config.mock_select)?;
+        let section = section_by_name(sections, &section_name, &project)?;
+        let config = config.clone();
+        Ok(tokio::spawn(async move {
+            let task_id = task.id.clone();
+            let moved = todoist::move_task_to_section(&config, task, &section, false).await;
+            report_move(&config, moved, &task_id, &section.project_id, verify).await;
+        }))
+    }
+}
+
+/// Sends any error from a move to the error channel, checking where the task ended up with verify
+async fn report_move(
+    config: &Config,
+    moved: Result<String, Error>,
+    task_id: &str,
+    project_id: &str,
+    verify: bool,
+) {
+    let result = match moved {
+        Ok(_) if verify => todoist::verify_task_project(config, task_id, project_id).await,
+        moved => moved.map(|_| ()),
+    };
+    if let Err(e) = result {
+        config.clone().tx().send(e).unwrap();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Args;
+    use crate::tasks::DateInfo;
     use crate::test;
+    use chrono::TimeZone;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn matching_resolves_wildcards() {
+        let project_named = |id: &str, name: &str| Project {
+            id: String::from(id),
+            name: String::from(name),
+            ..test::fixtures::project()
+        };
+        let projects = vec![
+            project_named("1", "Work"),
+            project_named("2", "Home"),
+            project_named("3", "Work Admin"),
+            project_named("4", "Homework"),
+        ];
+
+        let ids = |pattern: &str| -> Vec<String> {
+            matching(&projects, pattern)
+                .into_iter()
+                .map(|project| project.id)
+                .collect()
+        };
+        assert_eq!(ids("Work*"), vec![String::from("1"), String::from("3")]);
+        assert_eq!(ids("Hom?"), vec![String::from("2")]);
+        assert_eq!(ids("*work"), vec![String::from("4")]);
+        assert_eq!(ids("Play*"), Vec::<String>::new());
+        assert!(is_pattern("Work*"));
+        assert!(!is_pattern("Work"));
+    }
+
     #[tokio::test]
     async fn should_add_and_remove_projects() {
         let config = test::fixtures::config().await.create().await.unwrap();
@@ -525,36 +1043,276 @@ mod tests {
         assert_eq!(Ok("✓".to_string()), result);
     }
     #[tokio::test]
-    async fn test_list() {
+    async fn test_create() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("GET", "/rest/v2/projects")
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""type":"project_add""#,
+            )))
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(test::responses::projects())
+            .with_body(test::responses::project_add())
             .create_async()
             .await;
 
-        let mut config = test::fixtures::config().await.mock_url(server.url());
-
-        let str = "Projects                           # Tasks\n - Doomsday                      ";
-
-        assert_eq!(list(&mut config).await, Ok(String::from(str)));
-        mock.expect(3);
-    }
-
-    #[tokio::test]
-    async fn test_get_next_task() {
-        let mut server = mockito::Server::new_async().await;
-        let _mock = server
-            .mock("POST", "/sync/v9/projects/get_data")
+        let mock2 = server
+            .mock("GET", "/rest/v2/projects/999")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(test::responses::post_tasks().await)
+            .with_body(test::responses::project())
             .create_async()
             .await;
 
-        let config = test::fixtures::config().await.mock_url(server.url());
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .create()
+            .await
+            .unwrap();
+
+        let result = create(&mut config, "Doomsday", &None, &None).await;
+        assert_eq!(result, Ok(String::from("✓")));
+        mock.assert();
+        mock2.assert();
+
+        let config = config.reload().await.unwrap();
+        let project_ids: Vec<String> = config
+            .projects
+            .unwrap_or_default()
+            .iter()
+            .map(|p| p.id.to_owned())
+            .collect();
+        assert!(project_ids.contains(&String::from("999")));
+    }
+
+    #[tokio::test]
+    async fn task_created_in_new_project() {
+        let mut server = mockito::Server::new_async().await;
+        let project_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""type":"project_add""#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::project_add())
+            .create_async()
+            .await;
+        let get_mock = server
+            .mock("GET", "/rest/v2/projects/999")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::project())
+            .create_async()
+            .await;
+        let task_mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "content": "Pick a date",
+                "project_id": "999"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_confirm(true)
+            .create()
+            .await
+            .unwrap();
+
+        let project = create_for_task(&mut config, "Doomsday").await.unwrap();
+        let task = todoist::add_task(
+            &config,
+            &String::from("Pick a date"),
+            &project,
+            None,
+            Priority::None,
+            &String::new(),
+            &None,
+            &None,
+            &[],
+        )
+        .await;
+        assert!(task.is_ok());
+        project_mock.assert();
+        get_mock.assert();
+        task_mock.assert();
+
+        let config = config.reload().await.unwrap();
+        assert!(config
+            .projects
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .any(|p| p.id == "999"));
+        let mut config = config.mock_confirm(false);
+        assert_eq!(
+            create_for_task(&mut config, "Doomsday").await,
+            Err(error::new(
+                "project_new",
+                "'Doomsday' is already in config, use --project instead"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::projects())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.mock_url(server.url());
+
+        let str = "Projects\n - ■ Doomsday                    ";
+        assert_eq!(list(&mut config, false).await, Ok(String::from(str)));
+
+        // Task counts can't be fetched without a project data mock
+        let str =
+            "Projects                           # Tasks\n - ■ Doomsday                    (?)";
+        assert_eq!(list(&mut config, true).await, Ok(String::from(str)));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn completion_list_is_plain_names() {
+        let project = |name: &str| Project {
+            name: String::from(name),
+            ..test::fixtures::project()
+        };
+        let config = Config {
+            projects: Some(vec![
+                project("Work"),
+                project("Home"),
+                project("Side Project"),
+            ]),
+            ..test::fixtures::config().await
+        };
+
+        assert_eq!(completion_list(&config), "Home\nSide Project\nWork");
+    }
+
+    #[tokio::test]
+    async fn list_with_counts() {
+        let work = Project {
+            id: String::from("123"),
+            name: String::from("Work"),
+            ..test::fixtures::project()
+        };
+        let home = Project {
+            id: String::from("456"),
+            name: String::from("Home"),
+            ..test::fixtures::project()
+        };
+        let projects = vec![work, home];
+
+        let mut server = mockito::Server::new_async().await;
+        let projects_mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&projects).unwrap())
+            .create_async()
+            .await;
+        let work_mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""project_id":"123""#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::post_tasks().await)
+            .create_async()
+            .await;
+        let home_mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""project_id":"456""#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"items":[]}"#)
+            .create_async()
+            .await;
+
+        let mut config = Config {
+            projects: Some(projects),
+            ..test::fixtures::config().await
+        }
+        .mock_url(server.url());
+
+        let expected = format!(
+            "Projects                           # Tasks\n - ■ {}(0)\n - ■ {}(1)",
+            "Home".pad_to_width(PAD_WIDTH - 2),
+            "Work".pad_to_width(PAD_WIDTH - 2),
+        );
+        assert_eq!(list(&mut config, true).await, Ok(expected));
+        projects_mock.assert();
+        work_mock.assert();
+        home_mock.assert();
+    }
+
+    #[test]
+    fn test_format_project_tree() {
+        let parent = Project {
+            id: String::from("1"),
+            name: String::from("Work"),
+            ..test::fixtures::project()
+        };
+        let child = Project {
+            id: String::from("2"),
+            name: String::from("Meetings"),
+            parent_id: Some(String::from("1")),
+            ..test::fixtures::project()
+        };
+        let other = Project {
+            id: String::from("3"),
+            name: String::from("Home"),
+            ..test::fixtures::project()
+        };
+        let counts = HashMap::from([(String::from("2"), String::from("4"))]);
+
+        let other = Project {
+            color: String::from("not_a_color"),
+            ..other
+        };
+
+        let expected = format!(
+            "\n -   {}\n - ■ {}\n   - ■ {}4",
+            "Home".pad_to_width(PAD_WIDTH - 2),
+            "Work".pad_to_width(PAD_WIDTH - 2),
+            "Meetings".pad_to_width(PAD_WIDTH - 4)
+        );
+
+        assert_eq!(
+            format_project_tree(&[child, parent, other], &counts),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_next_task() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::post_tasks().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
 
         let config_dir = dirs::config_dir().unwrap().to_str().unwrap().to_owned();
 
@@ -569,9 +1327,12 @@ mod tests {
 
         config_with_timezone.clone().create().await.unwrap();
 
-        let task = next_task(config_with_timezone, project).await.unwrap();
+        let task = next_task(config_with_timezone, project, &[], false)
+            .await
+            .unwrap();
 
         assert!(task.contains("Put out recycling"));
+        assert!(task.contains("https://app.todoist.com/app/task/999999\n"));
         assert!(task.contains("1 task(s) remaining"));
     }
 
@@ -597,7 +1358,9 @@ mod tests {
         let binding = config_with_timezone.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
 
-        let tasks = all_tasks(&config_with_timezone, project).await.unwrap();
+        let tasks = all_tasks(&config_with_timezone, project, &ListOptions::default())
+            .await
+            .unwrap();
 
         assert!(tasks.contains("Tasks for 'myproject'\n"));
         assert!(tasks.contains("- Put out recycling\n"));
@@ -639,6 +1402,87 @@ mod tests {
         assert!(config_keys.contains(&"Doomsday".to_string()))
     }
 
+    #[tokio::test]
+    async fn import_selected_adds_only_ticked_projects() {
+        let projects: Vec<Project> = ["1", "2", "3"]
+            .into_iter()
+            .map(|id| Project {
+                id: String::from(id),
+                name: format!("Project {id}"),
+                ..test::fixtures::project()
+            })
+            .collect();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&projects).unwrap())
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_multiselect(vec![0, 2])
+            .create()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            import_selected(&mut config).await,
+            Ok(String::from("Imported 2 projects"))
+        );
+        mock.assert();
+
+        let config = config.reload().await.unwrap();
+        let project_ids: Vec<String> = config
+            .projects
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(project_ids, vec!["123", "1", "3"]);
+    }
+
+    #[tokio::test]
+    async fn inbox_resolves_without_being_in_config() {
+        let inbox_project = Project {
+            id: String::from("789"),
+            name: String::from("Inbox"),
+            is_inbox_project: true,
+            ..test::fixtures::project()
+        };
+        let projects = vec![test::fixtures::project(), inbox_project.clone()];
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&projects).unwrap())
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .create()
+            .await
+            .unwrap();
+
+        assert!(is_inbox_name("Inbox"));
+        assert!(is_inbox_name("i"));
+        assert_eq!(inbox(&mut config).await, Ok(inbox_project.clone()));
+        assert_eq!(config.inbox, Some(inbox_project.clone()));
+
+        // The second lookup comes from the cached copy saved in config
+        let mut config = config.reload().await.unwrap();
+        assert_eq!(inbox(&mut config).await, Ok(inbox_project));
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_process_tasks() {
         let mut server = mockito::Server::new_async().await;
@@ -669,7 +1513,7 @@ mod tests {
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
 
-        let result = process_tasks(&config, project).await;
+        let result = process_tasks(&config, project, false).await;
         assert_eq!(
             result,
             Ok("There are no more tasks in 'myproject'".to_string())
@@ -704,15 +1548,160 @@ mod tests {
         assert_eq!(projects.is_empty(), true);
     }
 
+    #[tokio::test]
+    async fn test_remove_auto_dry_run() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::new_projects())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .create()
+            .await
+            .unwrap();
+        let mut config = Config {
+            args: Args {
+                dry_run: true,
+                ..config.args.clone()
+            },
+            ..config
+        };
+
+        let result = remove_auto(&mut config).await;
+        assert_eq!(result, Ok(String::from("Would remove: 'myproject'")));
+        mock.assert();
+        assert_eq!(config.projects.clone().unwrap_or_default().len(), 1);
+
+        let saved = config.reload().await.unwrap();
+        assert_eq!(saved.projects.unwrap_or_default().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn select_project_starts_on_last_project() {
+        let config = test::fixtures::config().await;
+        let projects = vec![
+            test::fixtures::project(),
+            Project {
+                id: String::from("789"),
+                name: String::from("errands"),
+                ..test::fixtures::project()
+            },
+        ];
+
+        let selected = select_project(&config, projects.clone()).unwrap();
+        assert_eq!(selected.name, "newproject");
+
+        let config = config.set_last_project(&projects[1]);
+        let selected = select_project(&config, projects).unwrap();
+        assert_eq!(selected.name, "errands");
+    }
+
+    #[tokio::test]
+    async fn remember_last_project_saves_to_config_file() {
+        let mut config = test::fixtures::config().await.create().await.unwrap();
+        let project = test::fixtures::project();
+
+        remember_last_project(&mut config, &project).await.unwrap();
+        assert_eq!(config.last_project, Some(project.id.clone()));
+
+        let reloaded = Config::load(&config.path).await.unwrap();
+        assert_eq!(reloaded.last_project, Some(project.id));
+    }
+
+    #[test]
+    fn section_by_name_errors_when_section_is_gone() {
+        let project = test::fixtures::project();
+        let sections = vec![test::fixtures::section()];
+
+        assert_eq!(
+            section_by_name(sections.clone(), "Cool stuff", &project),
+            Ok(test::fixtures::section())
+        );
+        assert_eq!(
+            section_by_name(sections, "Deleted", &project),
+            Err(error::with_kind(
+                Kind::NotFound,
+                "find_section",
+                "Could not find section 'Deleted' in 'newproject'"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_tasks_recurring() {
+        let recurring_on = |id: &str, content: &str, date: &str| Task {
+            id: String::from(id),
+            content: String::from(content),
+            due: Some(DateInfo {
+                date: String::from(date),
+                is_recurring: true,
+                string: String::from("every week"),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let tasks = vec![
+            recurring_on("1", "Water plants", "2061-11-20"),
+            Task {
+                id: String::from("2"),
+                content: String::from("Buy a gift"),
+                ..test::fixtures::task()
+            },
+            recurring_on("3", "Take out trash", "2061-11-14T08:00:00Z"),
+            Task {
+                id: String::from("4"),
+                content: String::from("Someday"),
+                due: None,
+                ..test::fixtures::task()
+            },
+        ];
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::json!({ "items": tasks }).to_string())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let project = test::fixtures::project();
+
+        let options = ListOptions {
+            recurring: true,
+            ..ListOptions::default()
+        };
+        let list = all_tasks(&config, &project, &options).await.unwrap();
+        mock.assert();
+
+        assert!(list.starts_with("Recurring tasks for 'newproject'"));
+        let trash = list.find("Take out trash").unwrap();
+        let plants = list.find("Water plants").unwrap();
+        assert!(trash < plants);
+        assert!(!list.contains("Buy a gift"));
+        assert!(!list.contains("Someday"));
+    }
+
     #[tokio::test]
     async fn test_remove_all() {
         let mut config = test::fixtures::config()
             .await
-            .mock_select(1)
+            .mock_confirm(false)
             .create()
             .await
             .unwrap();
 
+        let result = remove_all(&mut config).await;
+        assert_eq!(result, Ok(String::from("Cancelled")));
+        assert!(!config.projects.clone().unwrap_or_default().is_empty());
+
+        let mut config = config.mock_confirm(true);
         let result = remove_all(&mut config).await;
         let expected: Result<String, Error> = Ok(String::from("Removed all projects from config"));
         assert_eq!(result, expected);
@@ -721,6 +1710,23 @@ mod tests {
         assert_eq!(projects.is_empty(), true);
     }
 
+    #[tokio::test]
+    async fn test_remove_all_with_yes() {
+        // No mock_confirm, so a prompt would panic
+        let config = test::fixtures::config().await.create().await.unwrap();
+        let mut config = Config {
+            args: Args {
+                yes: true,
+                ..config.args.clone()
+            },
+            ..config
+        };
+
+        let result = remove_all(&mut config).await;
+        assert_eq!(result, Ok(String::from("Removed all projects from config")));
+        assert!(config.projects.clone().unwrap_or_default().is_empty());
+    }
+
     #[tokio::test]
     async fn test_empty() {
         let mut server = mockito::Server::new_async().await;
@@ -756,7 +1762,7 @@ mod tests {
 
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
-        let result = empty(&mut config, project);
+        let result = empty(&mut config, project, &None, false);
         assert_eq!(
             result.await,
             Ok(String::from("Successfully emptied 'myproject'"))
@@ -766,6 +1772,97 @@ mod tests {
         mock3.assert();
     }
 
+    #[tokio::test]
+    async fn test_empty_section() {
+        let mut server = mockito::Server::new_async().await;
+        let tasks_mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::post_tasks().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let move_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let sections_mock = server
+            .mock("GET", "/rest/v2/sections?project_id=123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sections())
+            .expect(4)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_string("newtext")
+            .mock_select(0);
+
+        let binding = config.projects.clone().unwrap_or_default();
+        let project = binding.first().unwrap();
+
+        // The only task is in Bread, so nothing in Meat gets moved
+        assert_eq!(
+            empty(&mut config, project, &Some(String::from("Meat")), false).await,
+            Ok(String::from("No tasks to empty from 'myproject/Meat'"))
+        );
+        assert_eq!(
+            empty(&mut config, project, &Some(String::from("Bread")), false).await,
+            Ok(String::from("Successfully emptied 'myproject/Bread'"))
+        );
+        assert_eq!(
+            empty(&mut config, project, &Some(String::from("Cheese")), false).await,
+            Err(error::with_kind(
+                Kind::NotFound,
+                "find_section",
+                "Could not find section 'Cheese' in 'myproject'"
+            ))
+        );
+
+        tasks_mock.assert();
+        move_mock.assert();
+        sections_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_recently_completed() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/completed/get_all")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"project_id": "456"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::completed_tasks())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let project = test::fixtures::project();
+        let until = chrono_tz::US::Pacific
+            .with_ymd_and_hms(2024, 5, 3, 0, 0, 0)
+            .unwrap();
+
+        assert_eq!(
+            recently_completed_until(&config, &project, until).await,
+            Ok(String::from(
+                "Completed in the last 7 days for 'newproject'\n- 2024-05-02 23:30 Write the report\n- 2024-05-01 00:15 Take out the trash\n- 2024-04-30 23:59 Too early"
+            ))
+        );
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_prioritize_tasks_with_no_tasks() {
         let mut server = mockito::Server::new_async().await;
@@ -782,7 +1879,7 @@ mod tests {
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
 
-        let result = prioritize_tasks(&config, project);
+        let result = prioritize_tasks(&config, project, &[], &None);
         assert_eq!(
             result.await,
             Ok(String::from("No tasks to prioritize in 'myproject'"))
@@ -790,16 +1887,68 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn test_prioritize_summary() {
+        assert_eq!(prioritize_summary(8, 3), "8 already prioritized, 3 to go");
+        assert_eq!(prioritize_summary(0, 1), "0 already prioritized, 1 to go");
+    }
+
     #[tokio::test]
     async fn test_move_task_to_project() {
-        let config = test::fixtures::config().await.mock_select(2);
+        let config = test::fixtures::config().await.mock_select(3);
+        let task = test::fixtures::task();
+
+        move_task_to_project(&config, task, false)
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_and_move_task_to_project() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rest/v2/sections?project_id=456")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sections())
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/rest/v2/tasks/222")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let mock3 = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+
+        // Index 1 selects the combined option, Low priority, the second project and the second section
+        let mut config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_select(1);
+        config.add_project(test::fixtures::project());
         let task = test::fixtures::task();
 
-        move_task_to_project(&config, task)
+        move_task_to_project(&config, task, false)
             .await
             .unwrap()
             .await
             .unwrap();
+
+        mock.assert();
+        mock2.assert();
+        mock3.assert();
     }
 
     #[tokio::test]
@@ -854,7 +2003,7 @@ mod tests {
 
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
-        let result = schedule(&config, project, TaskFilter::Unscheduled, false);
+        let result = schedule(&config, project, TaskFilter::Unscheduled, false, &None);
         assert_eq!(
             result.await,
             Ok("Successfully scheduled tasks in 'myproject'".to_string())
@@ -864,7 +2013,7 @@ mod tests {
 
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
-        let result = schedule(&config, project, TaskFilter::Overdue, false);
+        let result = schedule(&config, project, TaskFilter::Overdue, false, &None);
         assert_eq!(
             result.await,
             Ok("No tasks to schedule in 'myproject'".to_string())
@@ -874,13 +2023,13 @@ mod tests {
 
         let binding = config.projects.clone().unwrap_or_default();
         let project = binding.first().unwrap();
-        let result = schedule(&config, project, TaskFilter::Unscheduled, false);
+        let result = schedule(&config, project, TaskFilter::Unscheduled, false, &None);
         assert_eq!(
             result.await,
             Ok("Successfully scheduled tasks in 'myproject'".to_string())
         );
 
-        let result = schedule(&config, project, TaskFilter::Unscheduled, true);
+        let result = schedule(&config, project, TaskFilter::Unscheduled, true, &None);
         assert_eq!(
             result.await,
             Ok("Successfully scheduled tasks in 'myproject'".to_string())
@@ -0,0 +1,180 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::color;
+use crate::error::{self, Error};
+
+/// The named colors Todoist accepts for projects
+/// See https://developer.todoist.com/guides/#colors
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProjectColor {
+    BerryRed,
+    Red,
+    Orange,
+    Yellow,
+    OliveGreen,
+    LimeGreen,
+    Green,
+    MintGreen,
+    Teal,
+    SkyBlue,
+    LightBlue,
+    Blue,
+    Grape,
+    Violet,
+    Lavender,
+    Magenta,
+    Salmon,
+    Charcoal,
+    Grey,
+    Taupe,
+}
+
+const ALL: [ProjectColor; 20] = [
+    ProjectColor::BerryRed,
+    ProjectColor::Red,
+    ProjectColor::Orange,
+    ProjectColor::Yellow,
+    ProjectColor::OliveGreen,
+    ProjectColor::LimeGreen,
+    ProjectColor::Green,
+    ProjectColor::MintGreen,
+    ProjectColor::Teal,
+    ProjectColor::SkyBlue,
+    ProjectColor::LightBlue,
+    ProjectColor::Blue,
+    ProjectColor::Grape,
+    ProjectColor::Violet,
+    ProjectColor::Lavender,
+    ProjectColor::Magenta,
+    ProjectColor::Salmon,
+    ProjectColor::Charcoal,
+    ProjectColor::Grey,
+    ProjectColor::Taupe,
+];
+
+impl Display for ProjectColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ProjectColor::BerryRed => "berry_red",
+            ProjectColor::Red => "red",
+            ProjectColor::Orange => "orange",
+            ProjectColor::Yellow => "yellow",
+            ProjectColor::OliveGreen => "olive_green",
+            ProjectColor::LimeGreen => "lime_green",
+            ProjectColor::Green => "green",
+            ProjectColor::MintGreen => "mint_green",
+            ProjectColor::Teal => "teal",
+            ProjectColor::SkyBlue => "sky_blue",
+            ProjectColor::LightBlue => "light_blue",
+            ProjectColor::Blue => "blue",
+            ProjectColor::Grape => "grape",
+            ProjectColor::Violet => "violet",
+            ProjectColor::Lavender => "lavender",
+            ProjectColor::Magenta => "magenta",
+            ProjectColor::Salmon => "salmon",
+            ProjectColor::Charcoal => "charcoal",
+            ProjectColor::Grey => "grey",
+            ProjectColor::Taupe => "taupe",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ProjectColor {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let name = str.trim().to_lowercase().replace([' ', '-'], "_");
+        ALL.into_iter()
+            .find(|color| color.to_string() == name)
+            .ok_or_else(|| {
+                let names: Vec<String> = ALL.iter().map(|c| c.to_string()).collect();
+                error::new(
+                    "project_color",
+                    &format!(
+                        "'{str}' is not a Todoist color, use one of: {}",
+                        names.join(", ")
+                    ),
+                )
+            })
+    }
+}
+
+impl ProjectColor {
+    /// RGB values as shown in the Todoist apps
+    fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            ProjectColor::BerryRed => (0xb8, 0x25, 0x5f),
+            ProjectColor::Red => (0xdb, 0x40, 0x35),
+            ProjectColor::Orange => (0xff, 0x99, 0x33),
+            ProjectColor::Yellow => (0xfa, 0xd0, 0x00),
+            ProjectColor::OliveGreen => (0xaf, 0xb8, 0x3b),
+            ProjectColor::LimeGreen => (0x7e, 0xcc, 0x49),
+            ProjectColor::Green => (0x29, 0x94, 0x38),
+            ProjectColor::MintGreen => (0x6a, 0xcc, 0xbc),
+            ProjectColor::Teal => (0x15, 0x8f, 0xad),
+            ProjectColor::SkyBlue => (0x14, 0xaa, 0xf5),
+            ProjectColor::LightBlue => (0x96, 0xc3, 0xeb),
+            ProjectColor::Blue => (0x40, 0x73, 0xff),
+            ProjectColor::Grape => (0x88, 0x4d, 0xff),
+            ProjectColor::Violet => (0xaf, 0x38, 0xeb),
+            ProjectColor::Lavender => (0xeb, 0x96, 0xeb),
+            ProjectColor::Magenta => (0xe0, 0x51, 0x94),
+            ProjectColor::Salmon => (0xff, 0x8d, 0x85),
+            ProjectColor::Charcoal => (0x80, 0x80, 0x80),
+            ProjectColor::Grey => (0xb8, 0xb8, 0xb8),
+            ProjectColor::Taupe => (0xcc, 0xac, 0x93),
+        }
+    }
+
+    /// A small block in the project's color
+    pub fn swatch(&self) -> String {
+        let (red, green, blue) = self.rgb();
+        color::swatch_string(red, green, blue)
+    }
+}
+
+/// Swatch for a color name from Todoist, blank when we don't recognize it
+pub fn swatch_for(name: &str) -> String {
+    match ProjectColor::from_str(name) {
+        Ok(color) => color.swatch(),
+        Err(_) => String::from(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_parse_valid_colors() {
+        assert_eq!(
+            ProjectColor::from_str("berry_red"),
+            Ok(ProjectColor::BerryRed)
+        );
+        assert_eq!(
+            ProjectColor::from_str("Sky Blue"),
+            Ok(ProjectColor::SkyBlue)
+        );
+        assert_eq!(ProjectColor::from_str("taupe"), Ok(ProjectColor::Taupe));
+        assert_eq!(ProjectColor::Grey.to_string(), "grey");
+    }
+
+    #[test]
+    fn should_reject_invalid_colors() {
+        let error = ProjectColor::from_str("plaid").unwrap_err();
+        assert_eq!(error.source, "project_color");
+        assert!(error
+            .message
+            .starts_with("'plaid' is not a Todoist color, use one of: berry_red, red"));
+    }
+
+    #[test]
+    fn should_roundtrip_all_colors() {
+        for color in ALL {
+            assert_eq!(ProjectColor::from_str(&color.to_string()), Ok(color));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::ErrorKind;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Config;
+use crate::error::{self, Error};
+use crate::{color, todoist};
+
+/// A change to Todoist that could not be sent and is waiting to be replayed with `tod sync`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueuedOp {
+    /// Body of a REST task creation, with the request ID from the first attempt so Todoist can drop duplicates
+    #[serde(rename = "create_task")]
+    Create {
+        body: Value,
+        #[serde(default)]
+        request_id: Option<String>,
+    },
+    #[serde(rename = "complete_task")]
+    Complete { id: String },
+    /// Body of a REST task update
+    #[serde(rename = "update_task")]
+    Update { id: String, body: Value },
+}
+
+/// The queue lives next to the config file, one JSON operation per line
+fn path(config: &Config) -> String {
+    format!("{}.queue", config.path)
+}
+
+pub async fn load(config: &Config) -> Result<Vec<QueuedOp>, Error> {
+    match fs::read_to_string(path(config)).await {
+        Ok(lines) => lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect(),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(Error::from(e)),
+    }
+}
+
+fn to_line(op: &QueuedOp) -> Result<String, Error> {
+    Ok(format!("{}\n", serde_json::to_string(op)?))
+}
+
+async fn save(config: &Config, ops: &[QueuedOp]) -> Result<(), Error> {
+    if ops.is_empty() {
+        return match fs::remove_file(path(config)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(Error::from(e)),
+            _ => Ok(()),
+        };
+    }
+
+    let lines = ops.iter().map(to_line).collect::<Result<String, Error>>()?;
+    fs::write(path(config), lines).await?;
+    Ok(())
+}
+
+/// Add an operation to the end of the queue.
+/// Appends a single line rather than rewriting the file, so that changes queued at the same time are all kept
+pub async fn push(config: &Config, op: QueuedOp) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path(config))
+        .await?;
+    file.write_all(to_line(&op)?.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// Queue an operation, returning the error to show the user
+pub async fn enqueue(config: &Config, op: QueuedOp) -> Error {
+    match push(config, op).await {
+        Ok(()) => error::new(
+            "offline queue",
+            "Could not reach Todoist, the change has been queued. Run `tod sync` when back online",
+        ),
+        Err(e) => e,
+    }
+}
+
+/// Replay queued operations in order, removing each one once it succeeds
+pub async fn sync(config: &Config) -> Result<String, Error> {
+    let mut ops = load(config).await?;
+
+    if ops.is_empty() {
+        return Ok(color::success_string("Nothing to sync", &config.colors));
+    }
+
+    let count = ops.len();
+    while let Some(op) = ops.first() {
+        todoist::send_queued_op(config, op).await?;
+        ops.remove(0);
+        save(config, &ops).await?;
+    }
+
+    Ok(color::success_string(
+        &format!("Synced {count} queued change(s)"),
+        &config.colors,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tasks::priority::Priority, test};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn failed_create_should_be_queued_and_synced() {
+        // Nothing listens on port 1, so the connection is refused
+        let config = test::fixtures::config()
+            .await
+            .mock_url(String::from("http://127.0.0.1:1"));
+        let project = test::fixtures::project();
+
+        let result = todoist::add_task(
+            &config,
+            &String::from("New task"),
+            &project,
+            None,
+            Priority::None,
+            &String::new(),
+            &None,
+            &None,
+            &[],
+        )
+        .await;
+        assert_eq!(result.unwrap_err().source, String::from("offline queue"));
+
+        let ops = load(&config).await.unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_matches!(ops.first(), Some(QueuedOp::Create { .. }));
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"content": "New task", "project_id": "456"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = config.mock_url(server.url());
+        assert_eq!(
+            sync(&config).await,
+            Ok(String::from("Synced 1 queued change(s)"))
+        );
+        mock.assert();
+        assert_eq!(load(&config).await, Ok(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn retried_create_keeps_request_id() {
+        let config = test::fixtures::config().await;
+        let op = QueuedOp::Create {
+            body: json!({"content": "New task"}),
+            request_id: Some(String::from("first-attempt-id")),
+        };
+        push(&config, op).await.unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_header("X-Request-Id", "first-attempt-id")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = config.mock_url(server.url());
+        assert_eq!(
+            sync(&config).await,
+            Ok(String::from("Synced 1 queued change(s)"))
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn create_queued_before_request_ids_still_loads() {
+        let op: QueuedOp =
+            serde_json::from_str(r#"{"type": "create_task", "body": {"content": "Old"}}"#).unwrap();
+        assert_eq!(
+            op,
+            QueuedOp::Create {
+                body: json!({"content": "Old"}),
+                request_id: None
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_pushes_are_all_kept() {
+        let config = test::fixtures::config().await;
+        let pushes = (0..20).map(|n| {
+            let config = config.clone();
+            tokio::spawn(async move {
+                let id = n.to_string();
+                push(&config, QueuedOp::Complete { id }).await
+            })
+        });
+        for result in futures::future::join_all(pushes).await {
+            result.unwrap().unwrap();
+        }
+
+        let mut ids = load(&config)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|op| match op {
+                QueuedOp::Complete { id } => id.parse::<u32>().unwrap(),
+                _ => panic!("Expected a completion"),
+            })
+            .collect::<Vec<u32>>();
+        ids.sort();
+        assert_eq!(ids, (0..20).collect::<Vec<u32>>());
+    }
+
+    #[tokio::test]
+    async fn sync_with_empty_queue() {
+        let config = test::fixtures::config().await;
+        assert_eq!(sync(&config).await, Ok(String::from("Nothing to sync")));
+    }
+}
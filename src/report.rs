@@ -0,0 +1,348 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate};
+use chrono_tz::Tz;
+use std::cmp::Reverse;
+
+use crate::config::Config;
+use crate::error::{self, Error};
+use crate::projects::Project;
+use crate::tasks::priority::Priority;
+use crate::tasks::{self, CompletedTask, FormatType, Task};
+use crate::{color, time, todoist};
+
+/// An ISO week, Monday to Sunday, relative to today
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Week {
+    /// The week that today is in
+    This,
+    /// The week before this one
+    Last,
+}
+
+/// The Monday and Sunday of the week
+pub fn week_bounds(week: Week, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday().into());
+    let monday = match week {
+        Week::This => monday,
+        Week::Last => monday - Duration::weeks(1),
+    };
+
+    (monday, monday + Duration::days(6))
+}
+
+/// Writes command output to a file without colors, returning a confirmation to print instead
+pub async fn write_output(text: &str, file: &str, config: &Config) -> Result<String, Error> {
+    tokio::fs::write(file, format!("{}\n", color::strip_ansi(text))).await?;
+    Ok(color::success_string(
+        &format!("✓ Written to {file}"),
+        &config.colors,
+    ))
+}
+
+/// Tasks completed between two dates in the configured timezone, both days included.
+/// With a label only the tasks that had it are kept.
+pub async fn completed(
+    config: &Config,
+    project: &Option<Project>,
+    since: NaiveDate,
+    until: NaiveDate,
+    label: &Option<String>,
+) -> Result<String, Error> {
+    if since > until {
+        return Err(error::new(
+            "report",
+            &format!("--since {since} must be on or before --until {until}"),
+        ));
+    }
+
+    let tz = time::timezone_from_str(&config.timezone)?;
+    let start = start_of_day(since, tz)?;
+    let end = start_of_day(until + Duration::days(1), tz)?;
+    let tasks = todoist::completed_tasks(config, project, start, end).await?;
+    let tasks = filter_completed_between(tasks, since, until, config);
+    let tasks: Vec<CompletedTask> = match label {
+        None => tasks,
+        Some(label) => tasks.into_iter().filter(|t| t.has_label(label)).collect(),
+    };
+
+    if tasks.is_empty() {
+        return Ok(format!("No tasks completed from {since} to {until}"));
+    }
+
+    let mut buffer = color::success_string(
+        &format!("Completed from {since} to {until}"),
+        &config.colors,
+    );
+    for task in tasks {
+        let completed_at = task
+            .get_completed_at(config)
+            .map(|datetime| datetime.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        buffer.push_str(&format!(
+            "\n- {} {}",
+            color::purple_string(&completed_at),
+            task.content
+        ));
+    }
+
+    Ok(buffer)
+}
+
+/// Current tasks for a project under a header for each priority, highest first
+pub async fn by_priority(
+    config: &Config,
+    project: &Project,
+    label: &Option<String>,
+) -> Result<String, Error> {
+    let tasks = todoist::tasks_for_project(config, project).await?;
+    let tasks = tasks::filter_by_label(tasks, label);
+
+    if tasks.is_empty() {
+        return Ok(format!("No tasks for '{}'", project.name));
+    }
+
+    let mut buffer = color::success_string(
+        &format!("Tasks for '{}' by priority", project.name),
+        &config.colors,
+    );
+    for (priority, tasks) in group_by_priority(tasks, config) {
+        buffer.push_str(&format!(
+            "\n\n{}",
+            color::header_string(&priority.to_string(), &config.colors)
+        ));
+        for task in tasks {
+            buffer.push('\n');
+            buffer.push_str(&task.fmt(config, FormatType::List, false));
+        }
+    }
+
+    Ok(buffer)
+}
+
+/// Groups ordered from High (P1) to None (P4), tasks in each group sorted by due
+fn group_by_priority(tasks: Vec<Task>, config: &Config) -> Vec<(Priority, Vec<Task>)> {
+    let mut groups: Vec<(Priority, Vec<Task>)> = Vec::new();
+    for task in tasks::sort_by_datetime(tasks, config) {
+        match groups
+            .iter_mut()
+            .find(|(priority, _)| *priority == task.priority)
+        {
+            Some((_, group)) => group.push(task),
+            None => groups.push((task.priority.clone(), vec![task])),
+        }
+    }
+    groups.sort_by_key(|(priority, _)| Reverse(priority.to_integer()));
+    groups
+}
+
+fn start_of_day(date: NaiveDate, tz: Tz) -> Result<DateTime<Tz>, Error> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|datetime| datetime.and_local_timezone(tz).earliest())
+        .ok_or_else(|| error::new("report", &format!("Could not find the start of {date}")))
+}
+
+/// Keeps tasks completed within the dates, oldest first
+fn filter_completed_between(
+    tasks: Vec<CompletedTask>,
+    since: NaiveDate,
+    until: NaiveDate,
+    config: &Config,
+) -> Vec<CompletedTask> {
+    let mut tasks: Vec<CompletedTask> = tasks
+        .into_iter()
+        .filter(|task| match task.get_completed_at(config) {
+            Ok(completed_at) => {
+                let date = completed_at.date_naive();
+                date >= since && date <= until
+            }
+            Err(_) => false,
+        })
+        .collect();
+    tasks.sort_by_key(|task| task.get_completed_at(config).ok());
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_completed() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/completed/get_all")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "since": "2024-05-01T07:00:00",
+                "until": "2024-05-03T07:00:00"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::completed_tasks())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let since = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        let report = completed(&config, &None, since, until, &None).await;
+        mock.assert();
+        assert_eq!(
+            report,
+            Ok(String::from(
+                "Completed from 2024-05-01 to 2024-05-02\n- 2024-05-01 00:15 Take out the trash\n- 2024-05-02 23:30 Write the report"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn completed_report_filters_by_label() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/completed/get_all")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "annotate_items": true
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::completed_tasks())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let since = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        let report = completed(&config, &None, since, until, &Some(String::from("@Work"))).await;
+        mock.assert();
+        assert_eq!(
+            report,
+            Ok(String::from(
+                "Completed from 2024-05-01 to 2024-05-02\n- 2024-05-02 23:30 Write the report"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn completed_report_written_to_file() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/completed/get_all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::completed_tasks())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let since = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+        let report = completed(&config, &None, since, until, &None)
+            .await
+            .unwrap();
+        mock.assert();
+
+        let file = std::env::temp_dir().join(format!("tod_report_{}.txt", uuid::Uuid::new_v4()));
+        let file = file.to_str().unwrap();
+        let colored = format!("\x1b[32m{report}\x1b[0m");
+
+        assert_eq!(
+            write_output(&colored, file, &config).await,
+            Ok(format!("✓ Written to {file}"))
+        );
+        assert_eq!(
+            std::fs::read_to_string(file).unwrap(),
+            "Completed from 2024-05-01 to 2024-05-02\n- 2024-05-01 00:15 Take out the trash\n- 2024-05-02 23:30 Write the report\n"
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn week_bounds_are_monday_to_sunday() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        // Wednesday
+        let today = date(15);
+
+        assert_eq!(week_bounds(Week::This, today), (date(13), date(19)));
+        assert_eq!(week_bounds(Week::Last, today), (date(6), date(12)));
+
+        // Mondays and Sundays stay in their own week
+        assert_eq!(week_bounds(Week::This, date(13)), (date(13), date(19)));
+        assert_eq!(week_bounds(Week::This, date(19)), (date(13), date(19)));
+        assert_eq!(
+            week_bounds(Week::Last, NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()),
+            (
+                NaiveDate::from_ymd_opt(2023, 12, 25).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_by_priority() {
+        let config = test::fixtures::config().await;
+        let task_with = |id: &str, priority: Priority| Task {
+            id: String::from(id),
+            priority,
+            ..test::fixtures::task()
+        };
+        let tasks = vec![
+            task_with("1", Priority::Low),
+            task_with("2", Priority::High),
+            task_with("3", Priority::None),
+            task_with("4", Priority::High),
+            task_with("5", Priority::Medium),
+        ];
+
+        let groups: Vec<(Priority, Vec<String>)> = group_by_priority(tasks, &config)
+            .into_iter()
+            .map(|(priority, tasks)| (priority, tasks.into_iter().map(|t| t.id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Priority::High, vec![String::from("2"), String::from("4")]),
+                (Priority::Medium, vec![String::from("5")]),
+                (Priority::Low, vec![String::from("1")]),
+                (Priority::None, vec![String::from("3")]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_by_priority() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/projects/get_data")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::post_unscheduled_tasks())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let project = test::fixtures::project();
+
+        let report = by_priority(&config, &project, &None).await.unwrap();
+        mock.assert();
+        assert!(report.starts_with("Tasks for 'newproject' by priority\n\nMEDIUM (P2)\n"));
+        assert!(report.contains("- Put out recycling"));
+        assert!(!report.contains("HIGH (P1)"));
+    }
+
+    #[tokio::test]
+    async fn test_completed_rejects_backwards_range() {
+        let config = test::fixtures::config().await;
+        let since = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        assert_eq!(
+            completed(&config, &None, since, until, &None).await,
+            Err(error::new(
+                "report",
+                "--since 2024-05-02 must be on or before --until 2024-05-01"
+            ))
+        );
+    }
+}
@@ -7,12 +7,16 @@ use crate::error::Error;
 pub struct Section {
     pub id: String,
     pub project_id: String,
+    /// Position in the project, called section_order by the sync API
+    #[serde(alias = "section_order")]
     pub order: u8,
     pub name: String,
 }
 
+/// Sections in the order they are shown in the Todoist apps
 pub fn json_to_sections(json: String) -> Result<Vec<Section>, Error> {
-    let sections: Vec<Section> = serde_json::from_str(&json)?;
+    let mut sections: Vec<Section> = serde_json::from_str(&json)?;
+    sections.sort_by_key(|section| section.order);
     Ok(sections)
 }
 
@@ -42,4 +46,21 @@ mod tests {
         let result = json_to_sections(test::responses::sections());
         assert_eq!(result, Ok(sections));
     }
+
+    #[test]
+    fn should_sort_sections_by_order() {
+        let json = String::from(
+            "[
+              {\"id\": \"3\", \"project_id\": \"5678\", \"section_order\": 3, \"name\": \"Dairy\"},
+              {\"id\": \"1\", \"project_id\": \"5678\", \"order\": 1, \"name\": \"Bread\"},
+              {\"id\": \"2\", \"project_id\": \"5678\", \"order\": 2, \"name\": \"Meat\"}
+            ]",
+        );
+        let names: Vec<String> = json_to_sections(json)
+            .unwrap()
+            .into_iter()
+            .map(|section| section.name)
+            .collect();
+        assert_eq!(names, vec!["Bread", "Meat", "Dairy"]);
+    }
 }
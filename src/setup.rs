@@ -0,0 +1,196 @@
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::config::{Config, Internal};
+use crate::error::{self, Error, Kind};
+use crate::{color, input, projects, time, todoist};
+
+const TOKEN_PROMPT: &str =
+    "Please enter your Todoist API token from https://todoist.com/prefs/integrations";
+/// How many tokens can be entered before giving up
+const TOKEN_ATTEMPTS: u8 = 3;
+
+const IMPORT_ALL: &str = "Import all projects";
+const IMPORT_SOME: &str = "Choose projects to import";
+const IMPORT_NONE: &str = "Skip";
+
+/// The config that setup starts from, an existing config keeps its settings
+pub async fn starting_config(path: String, tx: UnboundedSender<Error>) -> Result<Config, Error> {
+    let config = match Config::load(&path).await {
+        Ok(config) => config,
+        Err(_) => Config::new("", tx.clone()).await?,
+    };
+
+    Ok(Config {
+        path,
+        internal: Internal {
+            tx: Some(tx),
+            ..Internal::default()
+        },
+        ..config
+    })
+}
+
+/// Asks for the token, timezone, and projects to import, then writes the config once at the end
+pub async fn run(config: Config) -> Result<Config, Error> {
+    let (config, todoist_timezone) = ask_for_token(config).await?;
+    let config = choose_timezone(config, todoist_timezone)?;
+    let mut config = choose_projects(config).await?;
+    config.save().await?;
+
+    Ok(config)
+}
+
+/// Keeps asking until Todoist accepts the token, returning the timezone set in Todoist
+async fn ask_for_token(config: Config) -> Result<(Config, String), Error> {
+    let mut attempt = 1;
+    loop {
+        let token = input::string(TOKEN_PROMPT, config.mock_string.clone())?;
+        let config = Config {
+            token: token.trim().to_string(),
+            ..config.clone()
+        };
+
+        match todoist::user_timezone(&config).await {
+            Ok(timezone) => return Ok((config, timezone)),
+            Err(Error {
+                kind: Kind::Api { status: 401 | 403 },
+                ..
+            }) => {
+                if attempt == TOKEN_ATTEMPTS {
+                    return Err(error::with_kind(
+                        Kind::Config,
+                        "setup",
+                        "Todoist did not accept the token",
+                    ));
+                }
+                println!(
+                    "{}",
+                    color::red_string("Todoist did not accept that token, please try again")
+                );
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Offers the timezone from Todoist first, falling back to searching for one
+fn choose_timezone(config: Config, todoist_timezone: String) -> Result<Config, Error> {
+    let todoist_timezone = Some(todoist_timezone);
+    let use_todoist = time::timezone_from_str(&todoist_timezone).is_ok()
+        && config.confirm(
+            &format!(
+                "Use {}, the timezone set in Todoist?",
+                todoist_timezone.clone().unwrap_or_default()
+            ),
+            true,
+        )?;
+
+    let timezone = if use_todoist {
+        todoist_timezone
+    } else {
+        Some(config.prompt_timezone()?)
+    };
+
+    Ok(Config { timezone, ..config })
+}
+
+/// Adds all, some, or none of the Todoist projects that aren't in config yet
+async fn choose_projects(mut config: Config) -> Result<Config, Error> {
+    let options = vec![IMPORT_ALL, IMPORT_SOME, IMPORT_NONE];
+    let choice = input::select("Import your Todoist projects?", options, config.mock_select)?;
+    if choice == IMPORT_NONE {
+        return Ok(config);
+    }
+
+    let new_projects = projects::filter_new_projects(&config, todoist::projects(&config).await?);
+    for project in new_projects {
+        let add =
+            choice == IMPORT_ALL || config.confirm(&format!("Import {}?", project.name), true)?;
+        if add {
+            config.add_project(project);
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn setup_writes_token_timezone_and_projects() {
+        let mut server = mockito::Server::new_async().await;
+        let user_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_header("authorization", "Bearer abc123")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""resource_types":\["user"\]"#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::user())
+            .create_async()
+            .await;
+        let projects_mock = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::new_projects())
+            .create_async()
+            .await;
+
+        let config = Config {
+            token: String::new(),
+            timezone: None,
+            projects: None,
+            ..test::fixtures::config().await
+        }
+        .mock_url(server.url())
+        .mock_string(" abc123 ")
+        .mock_confirm(true)
+        .mock_select(0);
+
+        let config = run(config).await.unwrap();
+        user_mock.assert();
+        projects_mock.assert();
+
+        let saved = Config::load(&config.path).await.unwrap();
+        assert_eq!(saved.token, String::from("abc123"));
+        assert_eq!(saved.timezone, Some(String::from("America/Vancouver")));
+        let project_ids: Vec<String> = saved
+            .projects
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(project_ids, vec![String::from("890")]);
+
+        tokio::fs::remove_file(&config.path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn setup_gives_up_on_rejected_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(401)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .mock_url(server.url())
+            .mock_string("wrong");
+
+        assert_eq!(
+            run(config).await.unwrap_err(),
+            error::with_kind(Kind::Config, "setup", "Todoist did not accept the token")
+        );
+        mock.assert();
+    }
+}
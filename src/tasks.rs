@@ -2,8 +2,9 @@ use chrono::DateTime;
 use chrono::NaiveDate;
 use chrono_tz::Tz;
 use futures::future;
+use pad::PadStr;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 use std::cmp::Reverse;
 use std::fmt::Display;
@@ -11,15 +12,22 @@ use supports_hyperlinks::Stream;
 use tokio::task::JoinHandle;
 
 pub mod priority;
+use crate::collaborators;
 use crate::color;
 use crate::config::Config;
 use crate::config::SortValue;
-use crate::error::Error;
+use crate::error::{self, Error};
+use crate::input::DateTimeInput;
 use crate::projects;
 use crate::projects::Project;
-use crate::tasks::priority::Priority;
+use crate::tasks::priority::{Priority, Rule};
 use crate::{input, time, todoist};
 
+const PRIORITY_HEADER: &str = "Priority";
+const DUE_HEADER: &str = "Due";
+const CONTENT_HEADER: &str = "Content";
+const MIN_CONTENT_WIDTH: usize = 20;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Task {
     pub id: String,
@@ -29,6 +37,8 @@ pub struct Task {
     pub labels: Vec<String>,
     pub parent_id: Option<String>,
     pub project_id: String,
+    #[serde(default, deserialize_with = "optional_id")]
+    pub section_id: Option<String>,
     pub due: Option<DateInfo>,
     /// Only on rest api return value
     pub is_completed: Option<bool>,
@@ -36,6 +46,12 @@ pub struct Task {
     /// only on sync api return value
     pub checked: Option<bool>,
     pub duration: Option<Duration>,
+    /// When the task was created, added_at on sync api and created_at on rest api
+    #[serde(default, alias = "created_at")]
+    pub added_at: Option<String>,
+    /// Who the task is assigned to in a shared project, responsible_uid on sync api
+    #[serde(default, alias = "responsible_uid")]
+    pub assignee_id: Option<String>,
 }
 
 impl Display for Task {
@@ -44,6 +60,22 @@ impl Display for Task {
     }
 }
 
+/// Older Todoist responses have ids as numbers instead of strings
+fn optional_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        String(String),
+        Number(u64),
+    }
+
+    Ok(match Option::<Id>::deserialize(deserializer)? {
+        Some(Id::String(id)) => Some(id),
+        Some(Id::Number(id)) => Some(id.to_string()),
+        None => None,
+    })
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct DateInfo {
     pub date: String,
@@ -54,10 +86,19 @@ pub struct DateInfo {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Duration {
-    pub amount: u8,
+    pub amount: u32,
     pub unit: Unit,
 }
 
+impl Duration {
+    pub fn num_minutes(&self) -> i64 {
+        match self.unit {
+            Unit::Minute => self.amount.into(),
+            Unit::Day => i64::from(self.amount) * 24 * 60,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Body {
     items: Vec<Task>,
@@ -76,6 +117,41 @@ pub enum FormatType {
     Single,
 }
 
+/// How tasks from a filter can be split into sections
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GroupBy {
+    /// One section per project
+    Project,
+}
+
+/// How a list of tasks is rendered
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ListFormat {
+    /// Colored output for the terminal
+    #[default]
+    Text,
+    /// GitHub flavored markdown checklist
+    Markdown,
+    /// Aligned columns for priority, due, and content
+    Table,
+}
+
+/// Which tasks to list and how, from the flags of `list view`.
+/// Section, label, recurring, and agenda only apply to projects, group_by only to filters.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ListOptions {
+    pub format: ListFormat,
+    pub group_by: Option<GroupBy>,
+    pub created_since: Option<NaiveDate>,
+    pub due_before: Option<NaiveDate>,
+    pub section: Option<String>,
+    pub label: Option<String>,
+    pub stale_days: Option<u32>,
+    pub recurring: bool,
+    pub untriaged: bool,
+    pub agenda: bool,
+}
+
 enum DateTimeInfo {
     NoDateTime,
     Date {
@@ -90,14 +166,51 @@ enum DateTimeInfo {
     },
 }
 
+/// A task returned by the completed tasks endpoint, which has a different shape from Task
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CompletedTask {
+    pub id: String,
+    pub task_id: String,
+    pub content: String,
+    pub project_id: String,
+    pub completed_at: String,
+    /// The task as it was when completed, only sent when annotate_items is true
+    #[serde(default)]
+    pub item_object: Option<CompletedItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CompletedItem {
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+impl CompletedTask {
+    /// True if the task had the label when it was completed, the name can start with @
+    pub fn has_label(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('@');
+        self.item_object
+            .as_ref()
+            .is_some_and(|item| item.labels.iter().any(|l| l.eq_ignore_ascii_case(name)))
+    }
+
+    /// When the task was completed, in the configured timezone
+    pub fn get_completed_at(&self, config: &Config) -> Result<DateTime<Tz>, Error> {
+        let tz = time::timezone_from_str(&config.timezone)?;
+        DateTime::parse_from_rfc3339(&self.completed_at)
+            .map(|datetime| datetime.with_timezone(&tz))
+            .map_err(|_| {
+                error::new(
+                    "get_completed_at",
+                    &format!("Could not parse completed_at: {}", self.completed_at),
+                )
+            })
+    }
+}
+
 impl Task {
     pub fn fmt(&self, config: &Config, format: FormatType, with_project: bool) -> String {
-        let content = match self.priority {
-            priority::Priority::Low => color::blue_string(&self.content),
-            priority::Priority::Medium => color::yellow_string(&self.content),
-            priority::Priority::High => color::red_string(&self.content),
-            priority::Priority::None => color::normal_string(&self.content),
-        };
+        let content = color::priority_string(&self.content, &self.priority, &config.colors);
         let content = if config.disable_links || !supports_hyperlinks::on(Stream::Stdout) {
             content
         } else {
@@ -109,10 +222,12 @@ impl Task {
             FormatType::Single => String::from(""),
         };
 
-        let description = match &*self.description {
-            "" => String::from(""),
-            _ => format!("\n{buffer}{}", self.description),
-        };
+        // Every line is indented so that multiline descriptions stay under the task
+        let description: String = self
+            .description
+            .lines()
+            .map(|line| format!("\n{buffer}{line}"))
+            .collect();
 
         let project = if with_project {
             let project_icon = color::purple_string("#");
@@ -142,7 +257,16 @@ impl Task {
         let url = if config.disable_links || !supports_hyperlinks::on(Stream::Stdout) {
             String::new()
         } else {
-            format!("\x1B]8;;https://app.todoist.com/app/task/{id}\x1B\\[{link}]\x1B]8;;\x1B\\")
+            let url = task_url(&id);
+            format!("\x1B]8;;{url}\x1B\\[{link}]\x1B]8;;\x1B\\")
+        };
+        let overdue = self.is_overdue(config).unwrap_or_default();
+        let highlight_overdue = |string: String| {
+            if overdue {
+                color::red_string(&string)
+            } else {
+                string
+            }
         };
         let due = match &self.datetimeinfo(config) {
             Ok(DateTimeInfo::Date {
@@ -155,7 +279,8 @@ impl Task {
                 } else {
                     String::new()
                 };
-                let date_string = time::format_date(date, config).unwrap_or_default();
+                let date_string =
+                    highlight_overdue(time::format_date(date, config).unwrap_or_default());
 
                 format!("\n{buffer}{due_icon} {date_string}{recurring_icon}")
             }
@@ -169,7 +294,14 @@ impl Task {
                 } else {
                     String::new()
                 };
-                let datetime_string = time::format_datetime(datetime, config).unwrap_or_default();
+                let datetime_string =
+                    highlight_overdue(time::format_datetime(datetime, config).unwrap_or_default());
+                let relative_string = match time::datetime_is_today(*datetime, config) {
+                    Ok(true) => time::now(config)
+                        .map(|now| format!(" ({})", time::format_relative(*datetime - now)))
+                        .unwrap_or_default(),
+                    _ => String::new(),
+                };
 
                 let duration_string = match self.duration {
                     None => String::new(),
@@ -187,12 +319,19 @@ impl Task {
                     }) => format!(" for {amount} min"),
                 };
 
-                format!("\n{buffer}{due_icon} {datetime_string}{duration_string}{recurring_icon}")
+                format!(
+                    "\n{buffer}{due_icon} {datetime_string}{relative_string}{duration_string}{recurring_icon}"
+                )
             }
             Ok(DateTimeInfo::NoDateTime) => String::from(""),
             Err(e) => e.to_string(),
         };
 
+        let next_occurrence = match format {
+            FormatType::List => String::new(),
+            FormatType::Single => self.fmt_next_occurrence(config),
+        };
+
         let prefix = match format {
             FormatType::List => String::from("- "),
             FormatType::Single => String::from(""),
@@ -204,7 +343,74 @@ impl Task {
             format!(" {} {}", color::purple_string("@"), self.labels.join(" "))
         };
 
-        format!("{prefix}{content}{description}{due}{labels}{project} {url}\n")
+        // Falls back to the uid when the project's collaborators weren't fetched
+        let assignee = match &self.assignee_id {
+            None => String::new(),
+            Some(id) => {
+                let name = config.internal.collaborators.get(id).unwrap_or(id);
+                format!(" {} {name}", color::purple_string("→"))
+            }
+        };
+
+        format!("{prefix}{content}{description}{due}{next_occurrence}{labels}{assignee}{project} {url}\n")
+    }
+
+    /// The following occurrence of a recurring task, when we can work it out
+    fn fmt_next_occurrence(&self, config: &Config) -> String {
+        let Some(due) = &self.due else {
+            return String::new();
+        };
+
+        let next = match time::next_occurrence(due, config) {
+            Some(next) if due.date.len() == 10 => time::format_date(&next.date_naive(), config),
+            Some(next) => time::format_datetime(&next, config),
+            None => return String::new(),
+        };
+
+        match next {
+            Ok(next) => format!(" (next {next})"),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// Formats the task as a GitHub flavored markdown checkbox, without any colors or links
+    pub fn fmt_markdown(&self, config: &Config) -> String {
+        let checkbox = if self.checked.unwrap_or_default() || self.is_completed.unwrap_or_default()
+        {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+
+        let priority = match self.priority {
+            Priority::High => "🔴 ",
+            Priority::Medium => "🟠 ",
+            Priority::Low => "🔵 ",
+            Priority::None => "",
+        };
+
+        let due = match self.datetimeinfo(config) {
+            Ok(DateTimeInfo::Date { date, .. }) => format!(" `{}`", date.format("%Y-%m-%d")),
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+                let tz = time::timezone_from_str(&config.timezone).unwrap_or(Tz::UTC);
+                format!(
+                    " `{}`",
+                    datetime.with_timezone(&tz).format("%Y-%m-%d %H:%M")
+                )
+            }
+            Ok(DateTimeInfo::NoDateTime) | Err(_) => String::new(),
+        };
+
+        format!("- {checkbox} {priority}{}{due}", self.content)
+    }
+
+    /// The day the task was created on in the configured timezone
+    fn created_date(&self, config: &Config) -> Option<NaiveDate> {
+        let added_at = self.added_at.as_ref()?;
+        let tz = time::timezone_from_str(&config.timezone).ok()?;
+        DateTime::parse_from_rfc3339(added_at)
+            .ok()
+            .map(|datetime| datetime.with_timezone(&tz).date_naive())
     }
 
     /// Determines the numeric value of an task for sorting
@@ -223,6 +429,7 @@ impl Task {
             overdue,
             now,
             not_recurring,
+            recurring_overdue_weight,
             ..
         } = config.sort_value.clone().unwrap_or_default();
 
@@ -236,10 +443,15 @@ impl Task {
                 } else {
                     0
                 };
-                let overdue_value = if self.is_overdue(config).unwrap_or_default() {
-                    overdue
-                } else {
-                    0
+                let overdue_value = match self.is_overdue(config).unwrap_or_default() {
+                    // A missed habit shouldn't outrank one-off tasks that are due
+                    true if *is_recurring => {
+                        let weighted =
+                            u16::from(overdue) * u16::from(recurring_overdue_weight) / 100;
+                        u8::try_from(weighted).unwrap_or(u8::MAX)
+                    }
+                    true => overdue,
+                    false => 0,
                 };
                 let recurring_value = if is_recurring.to_owned() {
                     0
@@ -259,13 +471,19 @@ impl Task {
                     not_recurring
                 };
 
-                let duration = match time::now(config) {
+                let minutes_until = match time::now(config) {
                     Ok(tz) => (*datetime - tz).num_minutes(),
                     _ => 0,
                 };
+                // A task that started earlier is still current until its duration runs out
+                let in_progress = match &self.duration {
+                    Some(duration) => minutes_until < 0 && -minutes_until <= duration.num_minutes(),
+                    None => false,
+                };
 
-                match duration {
+                match minutes_until {
                     -15..=15 => now + recurring_value,
+                    _ if in_progress => now + recurring_value,
                     _ => recurring_value,
                 }
             }
@@ -273,6 +491,66 @@ impl Task {
         }
     }
 
+    /// The first occurrence of a recurring task after today, skipping any that were missed.
+    /// None when the recurrence isn't understood by time::next_occurrence.
+    fn next_occurrence_after_today(&self, config: &Config) -> Option<NaiveDate> {
+        let today = time::today_date(config).ok()?;
+        let mut date_info = self.due.clone()?;
+        let next = time::next_occurrence(&date_info, config)?.date_naive();
+        if next > today {
+            return Some(next);
+        }
+
+        // After the first occurrence the recurrence repeats every `interval` days,
+        // so the missed ones are skipped in one step however far behind the task is
+        date_info.date = next.format("%Y-%m-%d").to_string();
+        let interval = (time::next_occurrence(&date_info, config)?.date_naive() - next).num_days();
+        if interval <= 0 {
+            return None;
+        }
+        let missed = (today - next).num_days() / interval + 1;
+        Some(next + chrono::Duration::days(missed * interval))
+    }
+
+    /// The due string for this task moved forward by some amount of time.
+    /// Undated tasks are moved forward from today, or from now when the amount includes hours.
+    pub fn snoozed_due(&self, by: chrono::Duration, config: &Config) -> Result<String, Error> {
+        let whole_days = by.num_seconds() % 86400 == 0;
+        let datetime_format = "%Y-%m-%d %H:%M";
+        let date_format = "%Y-%m-%d";
+
+        let due = match self.datetimeinfo(config)? {
+            DateTimeInfo::DateTime { datetime, .. } => datetime
+                .checked_add_signed(by)
+                .map(|datetime| datetime.format(datetime_format).to_string()),
+            DateTimeInfo::Date { date, .. } if whole_days => date
+                .checked_add_signed(by)
+                .map(|date| date.format(date_format).to_string()),
+            DateTimeInfo::Date { date, .. } => date
+                .and_time(chrono::NaiveTime::MIN)
+                .checked_add_signed(by)
+                .map(|datetime| datetime.format(datetime_format).to_string()),
+            DateTimeInfo::NoDateTime if whole_days => time::today_date(config)?
+                .checked_add_signed(by)
+                .map(|date| date.format(date_format).to_string()),
+            DateTimeInfo::NoDateTime => time::now(config)?
+                .checked_add_signed(by)
+                .map(|datetime| datetime.format(datetime_format).to_string()),
+        };
+
+        due.ok_or_else(|| error::new("snooze", "Snoozing by that much is past the last date"))
+    }
+
+    /// The due date or time as shown in lists, empty when there is none
+    fn fmt_due(&self, config: &Config) -> String {
+        match self.datetimeinfo(config) {
+            Ok(DateTimeInfo::Date { date, .. }) => time::format_date(&date, config),
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => time::format_datetime(&datetime, config),
+            Ok(DateTimeInfo::NoDateTime) | Err(_) => Ok(String::new()),
+        }
+        .unwrap_or_default()
+    }
+
     /// Return the value of the due field
     fn datetime(&self, config: &Config) -> Option<DateTime<Tz>> {
         match self.datetimeinfo(config) {
@@ -281,7 +559,7 @@ impl Task {
         }
     }
 
-    fn priority_value(&self, config: &Config) -> u8 {
+    pub fn priority_value(&self, config: &Config) -> u8 {
         let SortValue {
             priority_none,
             priority_low,
@@ -342,6 +620,9 @@ impl Task {
             }
             projects::TaskFilter::Overdue => self.is_overdue(config).unwrap_or_default(),
             projects::TaskFilter::Recurring => self.is_recurring(),
+            projects::TaskFilter::Untriaged => {
+                self.priority == Priority::None && self.has_no_date()
+            }
         }
     }
 
@@ -368,7 +649,7 @@ impl Task {
             Ok(DateTimeInfo::NoDateTime) => false,
             Ok(DateTimeInfo::Date { date, .. }) => time::is_date_in_past(date, config)?,
             Ok(DateTimeInfo::DateTime { datetime, .. }) => {
-                time::is_date_in_past(datetime.date_naive(), config)?
+                time::is_date_in_past(time::date_for_datetime(datetime, config), config)?
             }
             Err(_) => false,
         };
@@ -385,6 +666,29 @@ impl Task {
     }
 }
 
+/// Link to a task in the Todoist app
+pub fn task_url(id: &str) -> String {
+    format!("https://app.todoist.com/app/task/{id}")
+}
+
+/// The task URL on its own line for single task views, unless turned off with show_urls
+pub fn maybe_url_line(config: &Config, task: &Task) -> String {
+    if config.show_urls.unwrap_or(true) {
+        format!("{}\n", task_url(&task.id))
+    } else {
+        String::new()
+    }
+}
+
+/// How many tasks are left for `task next`, unless turned off with show_remaining
+pub fn maybe_remaining_line(config: &Config, remaining: usize) -> String {
+    if config.show_remaining.unwrap_or(true) {
+        format!("\n{remaining} task(s) remaining")
+    } else {
+        String::new()
+    }
+}
+
 fn create_links(content: &str) -> String {
     // Define the regex pattern for Markdown links
     let link_regex = Regex::new(r"\[([^\]]+)\]\(([^)]+)\)").unwrap();
@@ -399,41 +703,175 @@ fn create_links(content: &str) -> String {
     result.into_owned()
 }
 
+/// The choice made for a single task in process
+pub enum Processed {
+    Handle(JoinHandle<()>),
+    /// Jump to the first remaining task whose content contains the text
+    Jump(String),
+    Quit,
+}
+
 pub async fn process_task(
     config: &Config,
     task: Task,
     task_count: &mut i32,
     with_project: bool,
-) -> Option<JoinHandle<()>> {
-    let options = ["Complete", "Skip", "Delete", "Quit"]
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
+) -> Processed {
+    let options = if task.is_recurring() {
+        vec!["Complete", "Done", "Skip", "Delete", "Jump", "Quit"]
+    } else {
+        vec!["Complete", "Skip", "Delete", "Jump", "Quit"]
+    }
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
     let formatted_task = task.fmt(config, FormatType::Single, with_project);
     println!("{formatted_task}{task_count} task(s) remaining");
+    let selection = input::select("Select an option", options, config.mock_select);
+    if let Ok("Jump") = selection.as_deref() {
+        return match input::string("Jump to task containing:", config.mock_string.clone()) {
+            Ok(text) => Processed::Jump(text),
+            Err(e) => Processed::Handle(spawn_send_error(config.clone(), e)),
+        };
+    }
     *task_count -= 1;
-    match input::select("Select an option", options, config.mock_select) {
+    match selection {
         Ok(string) => {
             if string == "Complete" {
-                Some(spawn_complete_task(config.clone(), task))
+                Processed::Handle(spawn_complete_task(config.clone(), task))
+            } else if string == "Done" {
+                Processed::Handle(spawn_roll_forward(config.clone(), task))
             } else if string == "Delete" {
-                Some(spawn_delete_task(config.clone(), task))
+                Processed::Handle(spawn_delete_task(config.clone(), task))
             } else if string == "Skip" {
                 // Do nothing
-                Some(tokio::spawn(async move {}))
+                Processed::Handle(tokio::spawn(async move {}))
             } else {
                 // The quit clause
-                None
+                Processed::Quit
             }
         }
-        Err(e) => {
-            let config = config.clone();
-            let handle = tokio::spawn(async move {
-                config.tx().send(e).unwrap();
-            });
-            Some(handle)
+        Err(Error {
+            kind: error::Kind::Interrupted,
+            ..
+        }) => {
+            // Ctrl-C at the prompt stops the loop the same way as Ctrl-C elsewhere
+            config.cancel();
+            Processed::Handle(tokio::spawn(async move {}))
+        }
+        Err(e) => Processed::Handle(spawn_send_error(config.clone(), e)),
+    }
+}
+
+/// Lets the updates already sent finish and clears next_id, for when an interactive loop is cancelled
+pub async fn save_interrupted(
+    config: &Config,
+    handles: Vec<JoinHandle<()>>,
+) -> Result<String, Error> {
+    future::join_all(handles).await;
+    config.reload().await?.clear_next_id().save().await?;
+
+    Ok(color::success_string(
+        "Interrupted, progress saved",
+        &config.colors,
+    ))
+}
+
+fn spawn_send_error(config: Config, e: Error) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        config.tx().send(e).unwrap();
+    })
+}
+
+/// Moves the first task from index onwards whose content contains text (ignoring case) to index.
+/// Returns false and leaves the tasks alone when nothing matches.
+pub fn jump_to(tasks: &mut Vec<Task>, index: usize, text: &str) -> bool {
+    let text = text.to_lowercase();
+    let found = tasks
+        .iter()
+        .skip(index)
+        .position(|task| task.content.to_lowercase().contains(&text));
+
+    match found {
+        Some(offset) => {
+            let task = tasks.remove(index + offset);
+            tasks.insert(index, task);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Printed when a jump in process matches none of the remaining tasks
+pub fn no_task_to_jump_to(text: &str) -> String {
+    color::red_string(&format!("No remaining task contains '{text}'"))
+}
+
+/// Prompt for a due date on each task. Going back returns to the previous task and skipping
+/// defers a task to the end of the list once. Changes are sent after every task has been seen.
+pub async fn schedule(config: &Config, tasks: Vec<Task>, with_project: bool) -> Result<(), Error> {
+    let decisions = collect_schedule_decisions(tasks, |task| {
+        println!("{}", task.fmt(config, FormatType::Single, with_project));
+        input::datetime(
+            config.mock_select,
+            config.mock_string.clone(),
+            config.natural_language_only,
+        )
+    })?;
+
+    let handles = decisions
+        .into_iter()
+        .filter_map(|(task, datetime_input)| match datetime_input {
+            DateTimeInput::Complete => Some(spawn_complete_task(config.clone(), task)),
+            DateTimeInput::Text(due_string) => {
+                Some(spawn_update_task_due(config.clone(), task, due_string))
+            }
+            DateTimeInput::None => Some(spawn_update_task_due(
+                config.clone(),
+                task,
+                "No date".to_string(),
+            )),
+            DateTimeInput::Skip | DateTimeInput::Back => None,
+        })
+        .collect::<Vec<JoinHandle<()>>>();
+
+    future::join_all(handles).await;
+    Ok(())
+}
+
+/// Walks through the tasks with an index so that we can go back, returning the final choice for each task
+fn collect_schedule_decisions<F>(
+    mut tasks: Vec<Task>,
+    mut prompt: F,
+) -> Result<Vec<(Task, DateTimeInput)>, Error>
+where
+    F: FnMut(&Task) -> Result<DateTimeInput, Error>,
+{
+    let mut decisions: Vec<(Task, DateTimeInput)> = Vec::new();
+    let mut deferred_ids: Vec<String> = Vec::new();
+    let mut index = 0;
+
+    while let Some(task) = tasks.get(index).cloned() {
+        let datetime_input = prompt(&task)?;
+        decisions.retain(|(t, _)| t.id != task.id);
+
+        match datetime_input {
+            DateTimeInput::Back => {
+                index = index.saturating_sub(1);
+                continue;
+            }
+            DateTimeInput::Skip => {
+                if !deferred_ids.contains(&task.id) {
+                    deferred_ids.push(task.id.clone());
+                    tasks.push(task);
+                }
+            }
+            datetime_input => decisions.push((task, datetime_input)),
         }
+        index += 1;
     }
+
+    Ok(decisions)
 }
 
 // Completes task inside another thread
@@ -445,6 +883,27 @@ pub fn spawn_complete_task(config: Config, task: Task) -> JoinHandle<()> {
     })
 }
 
+/// Moves a recurring task to its first occurrence after today, so missed occurrences are skipped.
+/// Falls back to completing it when the recurrence isn't understood, which Todoist advances by one.
+pub async fn roll_forward(config: &Config, task: Task) -> Result<String, Error> {
+    match task.next_occurrence_after_today(config) {
+        Some(date) => {
+            let date = date.format("%Y-%m-%d").to_string();
+            todoist::update_task_due(config, task, date, false).await
+        }
+        None => todoist::complete_task(config, &task.id, false).await,
+    }
+}
+
+// Rolls a recurring task forward inside another thread
+pub fn spawn_roll_forward(config: Config, task: Task) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = roll_forward(&config, task).await {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
 // Deletes task inside another thread
 pub fn spawn_delete_task(config: Config, task: Task) -> JoinHandle<()> {
     tokio::spawn(async move {
@@ -478,134 +937,803 @@ pub fn json_to_task(json: String) -> Result<Task, Error> {
     Ok(task)
 }
 
-pub fn sort_by_value(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
-    tasks.sort_by_key(|b| Reverse(b.value(config)));
-    tasks
+/// Asks for a description when none was given and always_prompt_description is on, empty is allowed
+pub fn maybe_prompt_description(config: &Config, description: &str) -> Result<String, Error> {
+    if description.is_empty() && config.always_prompt_description.unwrap_or_default() {
+        input::string("DESCRIPTION (optional)", config.mock_string.clone())
+    } else {
+        Ok(description.to_string())
+    }
 }
 
-pub fn sort_by_datetime(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
-    tasks.sort_by_key(|i| i.datetime(config));
-    tasks
+/// Appends a link to the description on its own line so that Todoist renders it as clickable
+pub fn description_with_link(description: &str, link: &Option<String>) -> String {
+    match link {
+        None => description.to_string(),
+        Some(url) if description.is_empty() => format!("[{url}]({url})"),
+        Some(url) => format!("{description}\n[{url}]({url})"),
+    }
 }
 
-pub fn filter_not_in_future(tasks: Vec<Task>, config: &Config) -> Result<Vec<Task>, Error> {
-    let tasks = tasks
+/// Returns true if the task has the label, the name can start with @
+pub fn has_label(task: &Task, name: &str) -> bool {
+    let name = name.trim_start_matches('@');
+    task.labels
+        .iter()
+        .any(|label| label.eq_ignore_ascii_case(name))
+}
+
+/// Some sync responses include tasks that are already completed or deleted
+pub fn filter_active(tasks: Vec<Task>) -> Vec<Task> {
+    tasks
         .into_iter()
         .filter(|task| {
-            task.is_today(config).unwrap_or_default()
-                || task.has_no_date()
-                || task.is_overdue(config).unwrap_or_default()
+            !task.checked.unwrap_or_default()
+                && !task.is_completed.unwrap_or_default()
+                && !task.is_deleted.unwrap_or_default()
         })
-        .collect();
+        .collect()
+}
 
-    Ok(tasks)
+/// Keeps only tasks that have the label, or all of them when there is no label
+pub fn filter_by_label(tasks: Vec<Task>, label: &Option<String>) -> Vec<Task> {
+    match label {
+        None => tasks,
+        Some(name) => tasks
+            .into_iter()
+            .filter(|task| has_label(task, name))
+            .collect(),
+    }
 }
 
-// We don't want to process parent tasks when child tasks are unchecked, or child tasks when they are checked
-// We additionally need to make sure that parent tasks are not in the future
+/// Drops recurring tasks when no_recurring is true, so that their cadence isn't reset
+pub fn maybe_reject_recurring(tasks: Vec<Task>, no_recurring: bool) -> Vec<Task> {
+    if !no_recurring {
+        return tasks;
+    }
 
-pub async fn reject_parent_tasks(tasks: Vec<Task>, config: &Config) -> Vec<Task> {
-    let parent_ids: Vec<String> = tasks
-        .clone()
+    tasks
         .into_iter()
-        .filter(|task| task.parent_id.is_some() && !task.checked.unwrap_or_default())
-        .map(|task| task.parent_id.unwrap_or_default())
-        .collect();
-
-    let mut handles = Vec::new();
-
-    for task in tasks.clone() {
-        let config = config.clone();
-        let parent_ids = parent_ids.clone();
-        let tasks = tasks.clone();
-
-        let config = config.clone();
-        let handle = tokio::spawn(async move {
-            if !parent_ids.contains(&task.id)
-                && !task.checked.unwrap_or_default()
-                && !parent_in_future(task.clone(), tasks, &config).await
-            {
-                Some(task)
-            } else {
-                None
-            }
-        });
+        .filter(|task| !task.is_recurring())
+        .collect()
+}
 
-        handles.push(handle);
+/// Keeps only tasks without a priority or a date when untriaged is true
+pub fn maybe_only_untriaged(tasks: Vec<Task>, config: &Config, untriaged: bool) -> Vec<Task> {
+    if !untriaged {
+        return tasks;
     }
 
-    future::join_all(handles)
-        .await
+    tasks
         .into_iter()
-        .filter_map(|t| t.ok())
-        .flatten()
-        .collect::<Vec<Task>>()
+        .filter(|task| task.filter(config, &projects::TaskFilter::Untriaged))
+        .collect()
 }
 
-// Need to make sure that we are not completing a subtask for a parent task that is in the future
-async fn parent_in_future(task: Task, tasks: Vec<Task>, config: &Config) -> bool {
-    let task_ids: Vec<String> = tasks.clone().into_iter().map(|task| task.id).collect();
+/// Keeps tasks created on or after the date, tasks without a creation time are dropped
+pub fn filter_created_since(tasks: Vec<Task>, date: NaiveDate, config: &Config) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| match task.created_date(config) {
+            Some(created) => created >= date,
+            None => false,
+        })
+        .collect()
+}
 
-    match task {
-        Task {
-            parent_id: None, ..
-        } => false,
-        Task {
-            parent_id: Some(parent_id),
-            ..
-        } => {
-            if task_ids.contains(&parent_id) {
-                false
-            } else {
-                // look up id and see if it is in the future
-                match todoist::get_task(config, &parent_id).await {
-                    Err(e) => {
-                        config.clone().tx().send(e).unwrap();
-                        false
-                    }
-                    Ok(task) => {
-                        !(task.is_overdue(config).unwrap_or_default()
-                            || task.has_no_date()
-                            || task.is_today(config).unwrap_or_default())
-                    }
-                }
+/// Keeps tasks whose due date, or the date of their due datetime, is strictly before the date.
+/// Undated tasks are dropped.
+pub fn filter_due_before(tasks: Vec<Task>, date: NaiveDate, config: &Config) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| match task.datetimeinfo(config) {
+            Ok(DateTimeInfo::Date { date: due, .. }) => due < date,
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+                time::date_for_datetime(datetime, config) < date
             }
-        }
-    }
+            Ok(DateTimeInfo::NoDateTime) | Err(_) => false,
+        })
+        .collect()
 }
 
-pub async fn set_priority(
-    config: &Config,
-    task: Task,
-    with_project: bool,
-) -> Result<JoinHandle<()>, Error> {
-    println!("{}", task.fmt(config, FormatType::Single, with_project));
+/// Keeps tasks due at a time today, leaving out ones due today without a time
+pub fn filter_today_and_has_time(tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| match task.datetimeinfo(config) {
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+                time::datetime_is_today(datetime, config).unwrap_or_default()
+            }
+            _ => false,
+        })
+        .collect()
+}
 
-    let options = vec![
-        Priority::None,
-        Priority::Low,
-        Priority::Medium,
-        Priority::High,
-    ];
-    let priority = input::select(
-        "Choose a priority that should be assigned to task: ",
-        options,
-        config.mock_select,
-    )?;
+/// How many days ago the task was due, None when it is not overdue
+pub fn days_overdue(task: &Task, config: &Config) -> Option<i64> {
+    let due = match task.datetimeinfo(config).ok()? {
+        DateTimeInfo::Date { date, .. } => date,
+        DateTimeInfo::DateTime { datetime, .. } => time::date_for_datetime(datetime, config),
+        DateTimeInfo::NoDateTime => return None,
+    };
+    let days = time::today_date(config)
+        .ok()?
+        .signed_duration_since(due)
+        .num_days();
+
+    (days > 0).then_some(days)
+}
 
-    let config = config.clone();
-    Ok(tokio::spawn(async move {
-        if let Err(e) = todoist::update_task_priority(&config, task, priority).await {
-            config.tx().send(e).unwrap();
-        }
-    }))
+/// Adds "(stale Nd)" to the content of tasks overdue by more than the number of days, only for display
+pub fn mark_stale(tasks: Vec<Task>, config: &Config, stale_days: u32) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .map(|task| match days_overdue(&task, config) {
+            Some(days) if days > i64::from(stale_days) => Task {
+                content: format!("{} (stale {days}d)", task.content),
+                ..task
+            },
+            _ => task,
+        })
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test;
-    use pretty_assertions::assert_eq;
+/// Reopen the task that was completed last, can only be done once per completion
+pub async fn undo_complete(config: Config) -> Result<String, Error> {
+    let Some(task_id) = config.last_completed_id.clone() else {
+        return Err(error::new(
+            "undo_complete",
+            "There is nothing to undo. A task must first be completed.",
+        ));
+    };
+
+    todoist::uncomplete_task(&config, &task_id, true).await?;
+    config.clear_last_completed_id().save().await
+}
+
+/// Complete the task at a 1-based position in the list, in the same order as `list view`
+/// where subtasks follow their parent
+pub async fn complete_by_index(
+    config: &Config,
+    tasks: Vec<Task>,
+    index: usize,
+) -> Result<String, Error> {
+    let tasks: Vec<Task> = nest_subtasks(sort_by_datetime(tasks, config))
+        .into_iter()
+        .map(|(_, task)| task)
+        .collect();
+    let count = tasks.len();
+    let Some(task) = index.checked_sub(1).and_then(|i| tasks.get(i)) else {
+        return Err(error::new(
+            "complete_by_index",
+            &format!("There is no task {index}, the list has {count} task(s)"),
+        ));
+    };
+
+    todoist::complete_task(config, &task.id, true).await?;
+    Ok(format!(
+        "{} {}",
+        color::success_string("✓ Completed", &config.colors),
+        task.content
+    ))
+}
+
+/// Complete a task, first asking whether its open subtasks should be completed too.
+/// Declining leaves the task open, as Todoist would otherwise complete the subtasks with it.
+pub async fn complete_with_subtasks(config: &Config, task_id: &str) -> Result<String, Error> {
+    let task = match todoist::get_task(config, task_id).await {
+        Ok(task) => task,
+        // Completing still gets queued while offline
+        Err(e) if e.is_network() => return todoist::complete_task(config, task_id, true).await,
+        Err(e) => return Err(e),
+    };
+
+    let subtasks = todoist::open_subtasks(config, &task).await?;
+    if !subtasks.is_empty() {
+        let question = format!(
+            "'{}' has {} open subtasks, complete them too?",
+            task.content,
+            subtasks.len()
+        );
+        if !config.confirm(&question, false)? {
+            return Ok(format!("Left '{}' open", task.content));
+        }
+        for subtask in subtasks {
+            todoist::complete_task(config, &subtask.id, false).await?;
+        }
+    }
+
+    todoist::complete_task(config, &task.id, true).await
+}
+
+/// Every task nested under the parent, at any depth
+pub fn descendants(tasks: &[Task], parent_id: &str) -> Vec<Task> {
+    tasks
+        .iter()
+        .filter(|task| task.parent_id.as_deref() == Some(parent_id))
+        .flat_map(|child| {
+            let mut branch = vec![child.clone()];
+            branch.extend(descendants(tasks, &child.id));
+            branch
+        })
+        .collect()
+}
+
+/// Orders tasks so that subtasks follow their parent, paired with how deeply each one is nested.
+/// Each family goes where its first task was in the sorted list, so a subtask that sorts first
+/// brings its parent up with it. Subtasks whose parent isn't in the list are treated as top level.
+fn nest_subtasks(tasks: Vec<Task>) -> Vec<(usize, Task)> {
+    let ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
+    let (roots, children): (Vec<Task>, Vec<Task>) =
+        tasks.into_iter().partition(|task| match &task.parent_id {
+            Some(parent_id) => !ids.contains(parent_id),
+            None => true,
+        });
+
+    let mut families: Vec<Vec<(usize, Task)>> = roots
+        .into_iter()
+        .map(|root| {
+            let mut family = Vec::new();
+            push_with_subtasks(&mut family, root, &children, 0);
+            family
+        })
+        .collect();
+    let first_position = |family: &Vec<(usize, Task)>| {
+        family
+            .iter()
+            .filter_map(|(_, task)| ids.iter().position(|id| id == &task.id))
+            .min()
+    };
+    families.sort_by_key(|family| first_position(family));
+    families.into_iter().flatten().collect()
+}
+
+/// Indents every line of a formatted task by depth, keeping the rest of it as is
+fn indent_lines(formatted: &str, depth: usize) -> String {
+    if depth == 0 {
+        return formatted.to_string();
+    }
+    let indent = "  ".repeat(depth);
+    formatted
+        .split_inclusive('\n')
+        .map(|line| format!("{indent}{line}"))
+        .collect()
+}
+
+fn push_with_subtasks(
+    nested: &mut Vec<(usize, Task)>,
+    task: Task,
+    children: &[Task],
+    depth: usize,
+) {
+    let id = task.id.clone();
+    nested.push((depth, task));
+    for child in children
+        .iter()
+        .filter(|child| child.parent_id.as_ref() == Some(&id))
+    {
+        push_with_subtasks(nested, child.clone(), children, depth + 1);
+    }
+}
+
+/// Quick add a task, then set the description and the priority when they are given.
+/// Setting the priority afterwards means it doesn't rely on p1 to p4 in the text.
+pub async fn quick_add(
+    config: &Config,
+    content: &str,
+    literal: bool,
+    priority: Option<Priority>,
+    description: &Option<String>,
+) -> Result<String, Error> {
+    let task = if literal {
+        todoist::quick_add_literal_task(config, content).await?
+    } else {
+        todoist::quick_add_task(config, content).await?
+    };
+
+    if let Some(description) = description {
+        todoist::update_task_description(config, &task, description).await?;
+    }
+
+    if let Some(priority) = priority {
+        todoist::update_task_priority(config, task, priority).await?;
+    }
+
+    Ok(color::success_string("✓", &config.colors))
+}
+
+/// Complete a task and add the next action for it to the same project
+pub async fn complete_with_followup(
+    config: &Config,
+    task_id: &str,
+    content: &str,
+) -> Result<String, Error> {
+    let task = todoist::get_task(config, task_id).await?;
+    todoist::complete_task(config, &task.id, true).await?;
+    todoist::add_followup_task(config, content, &task).await?;
+
+    Ok(format!(
+        "{} {}\n{} {content}",
+        color::success_string("✓ Completed", &config.colors),
+        task.content,
+        color::success_string("✓ Added", &config.colors),
+    ))
+}
+
+/// The note on a new line after the description, or on its own when there is no description
+pub fn append_description(description: &str, note: &str) -> String {
+    let description = description.trim_end();
+    if description.is_empty() {
+        note.to_string()
+    } else {
+        format!("{description}\n{note}")
+    }
+}
+
+/// Adds a line to the end of the description, fetching the task first so recent edits are kept
+pub async fn append_to_description(
+    config: &Config,
+    task: &Task,
+    note: &str,
+) -> Result<String, Error> {
+    let task = todoist::get_task(config, &task.id).await?;
+    let description = append_description(&task.description, note);
+    todoist::update_task_description(config, &task, &description).await?;
+    Ok(format!(
+        "{} '{}'",
+        color::success_string("✓ Added to the description of", &config.colors),
+        task.content
+    ))
+}
+
+/// Assign a task to someone the project is shared with, by name or email
+pub async fn assign(config: &Config, task: Task, name: &str) -> Result<String, Error> {
+    let collaborators = todoist::collaborators_for_project(config, &task.project_id).await?;
+    let collaborator = collaborators::by_name(&collaborators, name)?;
+    todoist::assign_task(config, &task, &collaborator).await?;
+    Ok(format!(
+        "{} '{}' to {}",
+        color::success_string("✓ Assigned", &config.colors),
+        task.content,
+        collaborator.name
+    ))
+}
+
+/// Push the due date of a task forward by some amount of time
+pub async fn snooze(config: &Config, task: Task, by: chrono::Duration) -> Result<String, Error> {
+    let due = task.snoozed_due(by, config)?;
+    todoist::update_task_due(config, task, due.clone(), true).await?;
+    Ok(format!(
+        "{} {due}",
+        color::success_string("✓ Snoozed until", &config.colors)
+    ))
+}
+
+/// Drops tasks that have a label from exclude_labels in config or from the extra labels given
+pub fn reject_excluded_labels(
+    tasks: Vec<Task>,
+    config: &Config,
+    extra_labels: &[String],
+) -> Vec<Task> {
+    let excluded: Vec<String> = config
+        .exclude_labels
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .chain(extra_labels)
+        .map(|label| label.trim_start_matches('@').to_string())
+        .collect();
+
+    tasks
+        .into_iter()
+        .filter(|task| !task.labels.iter().any(|label| excluded.contains(label)))
+        .collect()
+}
+
+/// One compact line per upcoming task, limited by preview in config
+pub fn preview_lines(config: &Config, upcoming: &[Task]) -> String {
+    let count = config.preview.unwrap_or_default() as usize;
+    let arrow = color::purple_string("→");
+
+    upcoming
+        .iter()
+        .take(count)
+        .map(|task| format!("\n{arrow} {}", task.content))
+        .collect()
+}
+
+/// Sorts tasks by datetime and renders them under a title
+pub fn format_list(
+    config: &Config,
+    title: &str,
+    tasks: Vec<Task>,
+    list_format: &ListFormat,
+    with_project: bool,
+) -> String {
+    let tasks = sort_by_datetime(tasks, config);
+    format_sorted_list(config, title, tasks, list_format, with_project)
+}
+
+/// Renders tasks under a title in the order given
+pub fn format_sorted_list(
+    config: &Config,
+    title: &str,
+    tasks: Vec<Task>,
+    list_format: &ListFormat,
+    with_project: bool,
+) -> String {
+    match list_format {
+        ListFormat::Text => {
+            let mut buffer = color::success_string(title, &config.colors);
+            for (depth, task) in nest_subtasks(tasks) {
+                let formatted = task.fmt(config, FormatType::List, with_project);
+                buffer.push('\n');
+                buffer.push_str(&indent_lines(&formatted, depth));
+            }
+            buffer
+        }
+        ListFormat::Markdown => {
+            let mut buffer = format!("## {title}\n");
+            for task in tasks {
+                buffer.push('\n');
+                buffer.push_str(&task.fmt_markdown(config));
+            }
+            buffer
+        }
+        ListFormat::Table => {
+            format!(
+                "{}\n{}",
+                color::success_string(title, &config.colors),
+                format_table(config, &tasks)
+            )
+        }
+    }
+}
+
+/// Renders a titled section for each group, keeping the tasks in each in the order given
+pub fn format_groups(
+    config: &Config,
+    title: &str,
+    groups: Vec<(String, Vec<Task>)>,
+    list_format: &ListFormat,
+) -> String {
+    let mut buffer = match list_format {
+        ListFormat::Text | ListFormat::Table => color::success_string(title, &config.colors),
+        ListFormat::Markdown => format!("## {title}\n"),
+    };
+
+    for (name, tasks) in groups {
+        match list_format {
+            ListFormat::Text => {
+                buffer.push_str(&format!(
+                    "\n\n{}",
+                    color::header_string(&name, &config.colors)
+                ));
+                for task in tasks {
+                    // Without the newline each task ends with, there is a single blank line before the next header
+                    let formatted = task.fmt(config, FormatType::List, false);
+                    buffer.push('\n');
+                    buffer.push_str(formatted.trim_end_matches('\n'));
+                }
+            }
+            ListFormat::Table => {
+                buffer.push_str(&format!(
+                    "\n\n{}\n",
+                    color::header_string(&name, &config.colors)
+                ));
+                buffer.push_str(&format_table(config, &tasks));
+            }
+            ListFormat::Markdown => {
+                buffer.push_str(&format!("\n### {name}\n"));
+                for task in tasks {
+                    buffer.push('\n');
+                    buffer.push_str(&task.fmt_markdown(config));
+                }
+            }
+        }
+    }
+
+    buffer
+}
+
+/// Splits tasks into Overdue, Today, Upcoming, and No date sections, leaving out empty ones
+pub fn format_agenda(
+    config: &Config,
+    title: &str,
+    tasks: Vec<Task>,
+    list_format: &ListFormat,
+) -> String {
+    let mut overdue = Vec::new();
+    let mut today = Vec::new();
+    let mut upcoming = Vec::new();
+    let mut undated = Vec::new();
+
+    for task in tasks {
+        if task.has_no_date() {
+            undated.push(task);
+        } else if task.is_overdue(config).unwrap_or_default() {
+            overdue.push(task);
+        } else if task.is_today(config).unwrap_or_default() {
+            today.push(task);
+        } else {
+            upcoming.push(task);
+        }
+    }
+
+    let groups: Vec<(String, Vec<Task>)> = [
+        ("Overdue", overdue),
+        ("Today", today),
+        ("Upcoming", upcoming),
+        ("No date", undated),
+    ]
+    .into_iter()
+    .filter(|(_, tasks)| !tasks.is_empty())
+    .map(|(name, tasks)| (String::from(name), sort_by_due(tasks, config)))
+    .collect();
+
+    format_groups(config, title, groups, list_format)
+}
+
+/// A header row and one row per task, content is cut off at the edge of the terminal
+pub fn format_table(config: &Config, tasks: &[Task]) -> String {
+    let rows: Vec<(String, String, &Task)> = tasks
+        .iter()
+        .map(|task| (task.priority.to_string(), task.fmt_due(config), task))
+        .collect();
+    let priority_width = rows
+        .iter()
+        .map(|(priority, _, _)| priority.chars().count())
+        .chain([PRIORITY_HEADER.len()])
+        .max()
+        .unwrap_or_default();
+    let due_width = rows
+        .iter()
+        .map(|(_, due, _)| due.chars().count())
+        .chain([DUE_HEADER.len()])
+        .max()
+        .unwrap_or_default();
+    let content_width = terminal_width()
+        .saturating_sub(priority_width + due_width + 2)
+        .max(MIN_CONTENT_WIDTH);
+
+    let mut buffer = color::header_string(
+        &format!(
+            "{} {} {CONTENT_HEADER}",
+            PRIORITY_HEADER.pad_to_width(priority_width),
+            DUE_HEADER.pad_to_width(due_width)
+        ),
+        &config.colors,
+    );
+    for (priority, due, task) in rows {
+        let due = due.pad_to_width(due_width);
+        let due = if task.is_overdue(config).unwrap_or_default() {
+            color::red_string(&due)
+        } else {
+            due
+        };
+        let content = truncate(&task.content, content_width);
+        let content = color::priority_string(&content, &task.priority, &config.colors);
+        buffer.push_str(&format!(
+            "\n{} {due} {content}",
+            priority.pad_to_width(priority_width)
+        ));
+    }
+    buffer
+}
+
+fn truncate(string: &str, width: usize) -> String {
+    if string.chars().count() <= width {
+        return string.to_string();
+    }
+    let cut: String = string.chars().take(width.saturating_sub(1)).collect();
+    format!("{cut}…")
+}
+
+fn terminal_width() -> usize {
+    if cfg!(test) {
+        return 80;
+    }
+    crossterm::terminal::size()
+        .map(|(columns, _)| columns as usize)
+        .unwrap_or(80)
+}
+
+/// Sorts by value, highest first. Tasks with the same value are ordered by id, oldest first,
+/// so the order doesn't depend on the order Todoist returned them in.
+pub fn sort_by_value(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    tasks.sort_by_cached_key(|task| (Reverse(task.value(config)), id_order(&task.id)));
+    tasks
+}
+
+/// Numeric ids of different lengths compare in number order, i.e. "99" before "100"
+fn id_order(id: &str) -> (usize, String) {
+    (id.len(), id.to_owned())
+}
+
+/// Sorts by the day that tasks are due, then by time. Tasks without a date go last.
+pub fn sort_by_due(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    tasks.sort_by_key(|task| match task.datetimeinfo(config) {
+        Ok(DateTimeInfo::Date { date, .. }) => (false, Some(date), None),
+        Ok(DateTimeInfo::DateTime { datetime, .. }) => (
+            false,
+            Some(time::date_for_datetime(datetime, config)),
+            Some(datetime),
+        ),
+        Ok(DateTimeInfo::NoDateTime) | Err(_) => (true, None, None),
+    });
+    tasks
+}
+
+pub fn sort_by_datetime(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    tasks.sort_by_key(|i| i.datetime(config));
+    tasks
+}
+
+pub fn filter_not_in_future(tasks: Vec<Task>, config: &Config) -> Result<Vec<Task>, Error> {
+    let tasks = tasks
+        .into_iter()
+        .filter(|task| {
+            task.is_today(config).unwrap_or_default()
+                || task.has_no_date()
+                || task.is_overdue(config).unwrap_or_default()
+        })
+        .collect();
+
+    Ok(tasks)
+}
+
+/// False for tasks with a time that hasn't arrived yet, or dated after today.
+/// Tasks with a date and no time, or no date at all, can always be done now.
+pub fn is_actionable_now(task: &Task, config: &Config) -> bool {
+    match task.datetimeinfo(config) {
+        Ok(DateTimeInfo::DateTime { datetime, .. }) => match time::now(config) {
+            Ok(now) => datetime <= now,
+            Err(_) => true,
+        },
+        Ok(DateTimeInfo::Date { date, .. }) => match time::today_date(config) {
+            Ok(today) => date <= today,
+            Err(_) => true,
+        },
+        Ok(DateTimeInfo::NoDateTime) | Err(_) => true,
+    }
+}
+
+/// Keeps tasks that can be done right now, see is_actionable_now
+pub fn filter_actionable(tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| is_actionable_now(task, config))
+        .collect()
+}
+
+// We don't want to process parent tasks when child tasks are unchecked, or child tasks when they are checked
+// We additionally need to make sure that parent tasks are not in the future
+
+pub async fn reject_parent_tasks(tasks: Vec<Task>, config: &Config) -> Vec<Task> {
+    let parent_ids: Vec<String> = tasks
+        .clone()
+        .into_iter()
+        .filter(|task| task.parent_id.is_some() && !task.checked.unwrap_or_default())
+        .map(|task| task.parent_id.unwrap_or_default())
+        .collect();
+
+    let mut handles = Vec::new();
+
+    for task in tasks.clone() {
+        let config = config.clone();
+        let parent_ids = parent_ids.clone();
+        let tasks = tasks.clone();
+
+        let config = config.clone();
+        let handle = tokio::spawn(async move {
+            if !parent_ids.contains(&task.id)
+                && !task.checked.unwrap_or_default()
+                && !parent_in_future(task.clone(), tasks, &config).await
+            {
+                Some(task)
+            } else {
+                None
+            }
+        });
+
+        handles.push(handle);
+    }
+
+    future::join_all(handles)
+        .await
+        .into_iter()
+        .filter_map(|t| t.ok())
+        .flatten()
+        .collect::<Vec<Task>>()
+}
+
+// Need to make sure that we are not completing a subtask for a parent task that is in the future
+async fn parent_in_future(task: Task, tasks: Vec<Task>, config: &Config) -> bool {
+    let task_ids: Vec<String> = tasks.clone().into_iter().map(|task| task.id).collect();
+
+    match task {
+        Task {
+            parent_id: None, ..
+        } => false,
+        Task {
+            parent_id: Some(parent_id),
+            ..
+        } => {
+            if task_ids.contains(&parent_id) {
+                false
+            } else {
+                // look up id and see if it is in the future
+                match todoist::get_task(config, &parent_id).await {
+                    Err(e) => {
+                        config.clone().tx().send(e).unwrap();
+                        false
+                    }
+                    Ok(task) => {
+                        !(task.is_overdue(config).unwrap_or_default()
+                            || task.has_no_date()
+                            || task.is_today(config).unwrap_or_default())
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub async fn set_priority(
+    config: &Config,
+    task: Task,
+    with_project: bool,
+) -> Result<JoinHandle<()>, Error> {
+    println!("{}", task.fmt(config, FormatType::Single, with_project));
+
+    let priority = select_priority(config)?;
+
+    Ok(spawn_update_task_priority(config.clone(), task, priority))
+}
+
+/// Prompt the user to pick a priority
+pub fn select_priority(config: &Config) -> Result<Priority, Error> {
+    input::select(
+        "Choose a priority that should be assigned to task: ",
+        priority::prompt_order(config),
+        config.mock_select,
+    )
+}
+
+/// Sets the priority from the first matching rule without prompting, returns how many tasks changed
+pub async fn prioritize_by_rules(config: &Config, tasks: Vec<Task>, rules: &[Rule]) -> usize {
+    let handles: Vec<JoinHandle<()>> = tasks
+        .into_iter()
+        .filter_map(|task| match priority::from_rules(rules, &task, config) {
+            Some(priority) if priority != task.priority => {
+                Some(spawn_update_task_priority(config.clone(), task, priority))
+            }
+            _ => None,
+        })
+        .collect();
+    let count = handles.len();
+    future::join_all(handles).await;
+    count
+}
+
+// Updates task priority inside another thread
+pub fn spawn_update_task_priority(
+    config: Config,
+    task: Task,
+    priority: Priority,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = todoist::update_task_priority(&config, task, priority).await {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
 
     #[tokio::test]
     async fn date_value_can_handle_date() {
@@ -613,80 +1741,1243 @@ mod tests {
         // On another day
         assert_eq!(test::fixtures::task().date_value(&config), 50);
 
-        // Recurring
-        let task = Task {
+        // Recurring
+        let task = Task {
+            due: Some(DateInfo {
+                is_recurring: true,
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+        assert_eq!(task.date_value(&config), 0);
+
+        // Overdue
+        let task = Task {
+            due: Some(DateInfo {
+                date: String::from("2001-11-13"),
+                is_recurring: true,
+                timezone: Some(String::from("America/Los_Angeles")),
+                string: String::from("Every 2 weeks"),
+            }),
+            ..test::fixtures::task()
+        };
+        assert_eq!(task.date_value(&config), 75);
+
+        // No date
+        let task = Task { due: None, ..task };
+        assert_eq!(task.date_value(&config), 80);
+    }
+
+    #[tokio::test]
+    async fn overdue_recurring_ranks_below_one_off_due_today() {
+        let config = test::fixtures::config().await;
+        let overdue_recurring = Task {
+            due: Some(DateInfo {
+                date: String::from("2001-11-13"),
+                is_recurring: true,
+                timezone: None,
+                string: String::from("every day"),
+            }),
+            ..test::fixtures::task()
+        };
+        let today_one_off = Task {
+            due: Some(DateInfo {
+                date: time::today_string(&config).unwrap(),
+                is_recurring: false,
+                timezone: None,
+                string: String::from("today"),
+            }),
+            ..test::fixtures::task()
+        };
+        assert_eq!(overdue_recurring.date_value(&config), 75);
+        assert_eq!(today_one_off.date_value(&config), 150);
+
+        // A weight of 100 gives back the full overdue points
+        let config = Config {
+            sort_value: Some(SortValue {
+                recurring_overdue_weight: 100,
+                ..SortValue::default()
+            }),
+            ..config
+        };
+        assert_eq!(overdue_recurring.date_value(&config), 150);
+    }
+
+    #[tokio::test]
+    async fn date_value_can_handle_datetime() {
+        let config = test::fixtures::config().await;
+        let task = Task {
+            due: Some(DateInfo {
+                date: String::from("2021-02-27T19:41:56Z"),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+
+        assert_eq!(task.date_value(&config), 50);
+    }
+
+    #[tokio::test]
+    async fn is_actionable_now_excludes_times_later_today() {
+        let config = test::fixtures::config().await;
+        let due_at = |datetime: DateTime<Tz>| Task {
+            due: Some(DateInfo {
+                date: datetime
+                    .with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%dT%H:%M:%SZ")
+                    .to_string(),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+        let now = time::now(&config).unwrap();
+        let later = due_at(now + chrono::Duration::minutes(90));
+        let earlier = due_at(now - chrono::Duration::minutes(90));
+        let today = Task {
+            due: Some(DateInfo {
+                date: time::today_string(&config).unwrap(),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+        let undated = Task {
+            due: None,
+            ..test::fixtures::task()
+        };
+        let future_date = test::fixtures::task();
+
+        assert!(!is_actionable_now(&later, &config));
+        assert!(is_actionable_now(&earlier, &config));
+        assert!(is_actionable_now(&today, &config));
+        assert!(is_actionable_now(&undated, &config));
+        assert!(!is_actionable_now(&future_date, &config));
+
+        let ids: Vec<String> = filter_actionable(
+            vec![
+                Task {
+                    id: String::from("later"),
+                    ..later
+                },
+                Task {
+                    id: String::from("earlier"),
+                    ..earlier
+                },
+            ],
+            &config,
+        )
+        .into_iter()
+        .map(|task| task.id)
+        .collect();
+        assert_eq!(ids, vec![String::from("earlier")]);
+    }
+
+    #[tokio::test]
+    async fn date_value_counts_tasks_in_progress_as_now() {
+        let config = test::fixtures::config().await;
+        let started = time::now(&config).unwrap() - chrono::Duration::hours(3);
+        let task = Task {
+            due: Some(DateInfo {
+                date: started
+                    .with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%dT%H:%M:%SZ")
+                    .to_string(),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+        assert_eq!(task.date_value(&config), 50);
+
+        let all_day = Task {
+            duration: Some(Duration {
+                amount: 1,
+                unit: Unit::Day,
+            }),
+            ..task.clone()
+        };
+        assert_eq!(all_day.date_value(&config), 250);
+
+        let short = Task {
+            duration: Some(Duration {
+                amount: 30,
+                unit: Unit::Minute,
+            }),
+            ..task
+        };
+        assert_eq!(short.date_value(&config), 50);
+    }
+
+    #[tokio::test]
+    async fn can_format_task_with_a_date() {
+        let config = test::fixtures::config().await;
+        let task = Task {
+            content: String::from("Get gifts for the twins"),
+            due: Some(DateInfo {
+                date: String::from("2021-08-13"),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+
+        let task = task.fmt(&config, FormatType::Single, false);
+
+        assert!(task.contains("Get gifts for the twins"));
+        assert!(task.contains("2021-08-13"));
+    }
+
+    #[tokio::test]
+    async fn can_format_task_with_today() {
+        let config = test::fixtures::config().await;
+        let task = Task {
+            content: String::from("Get gifts for the twins"),
+            due: Some(DateInfo {
+                date: time::today_string(&config).unwrap(),
+                ..test::fixtures::task().due.unwrap()
+            }),
+            ..test::fixtures::task()
+        };
+
+        let task_text = task.fmt(&config, FormatType::Single, true);
+
+        assert!(task_text.contains("Today @ computer"));
+    }
+
+    #[tokio::test]
+    async fn can_format_task_as_markdown() {
+        let config = test::fixtures::config().await;
+        let task = test::fixtures::task();
+
+        assert_eq!(
+            task.fmt_markdown(&config),
+            String::from("- [ ] 🟠 Get gifts for the twins `2061-11-13`")
+        );
+
+        let task = Task {
+            checked: Some(true),
+            priority: Priority::None,
+            due: None,
+            ..task
+        };
+
+        assert_eq!(
+            task.fmt_markdown(&config),
+            String::from("- [x] Get gifts for the twins")
+        );
+
+        let list = format_list(
+            &config,
+            "Tasks for 'myproject'",
+            vec![task],
+            &ListFormat::Markdown,
+            false,
+        );
+        assert_eq!(
+            list,
+            String::from("## Tasks for 'myproject'\n\n- [x] Get gifts for the twins")
+        );
+    }
+
+    #[tokio::test]
+    async fn can_format_list_as_table() {
+        let config = test::fixtures::config().await;
+        let task = test::fixtures::task();
+        let long = Task {
+            content: "word ".repeat(20),
+            priority: Priority::None,
+            due: None,
+            ..test::fixtures::task()
+        };
+
+        let list = format_list(
+            &config,
+            "Tasks for 'myproject'",
+            vec![task, long],
+            &ListFormat::Table,
+            false,
+        );
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines[0], "Tasks for 'myproject'");
+        assert_eq!(lines[1], "Priority    Due        Content");
+        assert_eq!(lines[2], "MEDIUM (P2) 2061-11-13 Get gifts for the twins");
+        assert_eq!(lines[3].chars().count(), 80);
+        assert!(lines[3].starts_with("NONE (P4)              word word"));
+        assert!(lines[3].ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn test_task_url() {
+        assert_eq!(
+            task_url("222"),
+            String::from("https://app.todoist.com/app/task/222")
+        );
+
+        let config = test::fixtures::config().await;
+        let task = test::fixtures::task();
+        assert_eq!(
+            maybe_url_line(&config, &task),
+            String::from("https://app.todoist.com/app/task/222\n")
+        );
+
+        let config = Config {
+            show_urls: Some(false),
+            ..config
+        };
+        assert_eq!(maybe_url_line(&config, &task), String::new());
+    }
+
+    #[tokio::test]
+    async fn test_maybe_remaining_line() {
+        let config = test::fixtures::config().await;
+        assert_eq!(
+            maybe_remaining_line(&config, 3),
+            String::from("\n3 task(s) remaining")
+        );
+
+        let config = Config {
+            show_remaining: Some(false),
+            ..config
+        };
+        assert_eq!(maybe_remaining_line(&config, 3), String::new());
+    }
+
+    #[tokio::test]
+    async fn can_format_task_with_next_occurrence() {
+        let config = test::fixtures::config().await;
+        let task = Task {
+            due: Some(DateInfo {
+                date: String::from("2061-11-13"),
+                is_recurring: true,
+                string: String::from("every 2 weeks"),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+
+        let single = task.fmt(&config, FormatType::Single, false);
+        assert!(single.contains("every 2 weeks (next 2061-11-27)"));
+
+        let list = task.fmt(&config, FormatType::List, false);
+        assert!(!list.contains("(next"));
+    }
+
+    #[tokio::test]
+    async fn multiline_description_is_indented() {
+        let config = Config {
+            disable_links: true,
+            ..test::fixtures::config().await
+        };
+        let task = Task {
+            description: String::from("Bring the receipts\r\nAsk about the fee"),
+            due: None,
+            ..test::fixtures::task()
+        };
+
+        assert!(task
+            .fmt(&config, FormatType::List, false)
+            .starts_with("- Get gifts for the twins\n  Bring the receipts\n  Ask about the fee"));
+        assert!(task
+            .fmt(&config, FormatType::Single, false)
+            .starts_with("Get gifts for the twins\nBring the receipts\nAsk about the fee"));
+    }
+
+    #[test]
+    fn description_with_link_appends_markdown_link() {
+        let link = Some(String::from("https://example.com/doc"));
+
+        assert_eq!(description_with_link("Notes", &None), "Notes");
+        assert_eq!(
+            description_with_link("", &link),
+            "[https://example.com/doc](https://example.com/doc)"
+        );
+        assert_eq!(
+            description_with_link("Read this first", &link),
+            "Read this first\n[https://example.com/doc](https://example.com/doc)"
+        );
+    }
+
+    #[tokio::test]
+    async fn description_prompt_follows_config() {
+        let config = test::fixtures::config()
+            .await
+            .mock_string("Notes from the call");
+        assert_eq!(maybe_prompt_description(&config, ""), Ok(String::new()));
+
+        let config = Config {
+            always_prompt_description: Some(true),
+            ..config
+        };
+        assert_eq!(
+            maybe_prompt_description(&config, ""),
+            Ok(String::from("Notes from the call"))
+        );
+        assert_eq!(
+            maybe_prompt_description(&config, "Given with --description"),
+            Ok(String::from("Given with --description"))
+        );
+    }
+
+    #[test]
+    fn maybe_reject_recurring_drops_recurring_tasks() {
+        let recurring = Task {
+            id: String::from("1"),
+            due: Some(DateInfo {
+                date: String::from("2061-11-13"),
+                is_recurring: true,
+                string: String::from("every day"),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let once = test::fixtures::task();
+        let tasks = vec![recurring, once.clone()];
+
+        assert_eq!(maybe_reject_recurring(tasks.clone(), true), vec![once]);
+        assert_eq!(maybe_reject_recurring(tasks.clone(), false), tasks);
+    }
+
+    #[tokio::test]
+    async fn only_untriaged_needs_no_priority_and_no_date() {
+        let config = test::fixtures::config().await;
+        let untriaged = Task {
+            id: String::from("1"),
+            priority: Priority::None,
+            due: None,
+            ..test::fixtures::task()
+        };
+        let prioritized = Task {
+            id: String::from("2"),
+            priority: Priority::Low,
+            due: None,
+            ..test::fixtures::task()
+        };
+        let dated = Task {
+            id: String::from("3"),
+            priority: Priority::None,
+            ..test::fixtures::task()
+        };
+        let triaged = test::fixtures::task();
+        let tasks = vec![untriaged.clone(), prioritized, dated, triaged];
+
+        assert_eq!(
+            maybe_only_untriaged(tasks.clone(), &config, true),
+            vec![untriaged]
+        );
+        assert_eq!(maybe_only_untriaged(tasks.clone(), &config, false), tasks);
+    }
+
+    #[test]
+    fn has_label_ignores_at_sign_and_case() {
+        let task = Task {
+            labels: vec![String::from("errands"), String::from("Waiting")],
+            ..test::fixtures::task()
+        };
+
+        assert!(has_label(&task, "errands"));
+        assert!(has_label(&task, "@errands"));
+        assert!(has_label(&task, "waiting"));
+        assert!(!has_label(&task, "computer"));
+    }
+
+    #[test]
+    fn filter_by_label_keeps_matching_tasks() {
+        let errand = Task {
+            id: String::from("333"),
+            labels: vec![String::from("errands")],
+            ..test::fixtures::task()
+        };
+        let tasks = vec![test::fixtures::task(), errand.clone()];
+
+        assert_eq!(
+            filter_by_label(tasks.clone(), &Some(String::from("@errands"))),
+            vec![errand]
+        );
+        assert_eq!(filter_by_label(tasks.clone(), &None), tasks);
+    }
+
+    #[test]
+    fn filter_active_drops_checked_completed_and_deleted() {
+        let active = test::fixtures::task();
+        let checked = Task {
+            checked: Some(true),
+            ..test::fixtures::task()
+        };
+        let completed = Task {
+            is_completed: Some(true),
+            ..test::fixtures::task()
+        };
+        let deleted = Task {
+            is_deleted: Some(true),
+            ..test::fixtures::task()
+        };
+        let unchecked = Task {
+            checked: Some(false),
+            is_deleted: Some(false),
+            ..test::fixtures::task()
+        };
+
+        assert_eq!(
+            filter_active(vec![
+                active.clone(),
+                checked,
+                completed,
+                deleted,
+                unchecked.clone()
+            ]),
+            vec![active, unchecked]
+        );
+    }
+
+    #[tokio::test]
+    async fn format_agenda_buckets_tasks_by_due_date() {
+        let config = test::fixtures::config().await;
+        let today = time::today_date(&config).unwrap();
+        let due = |content: &str, date: NaiveDate| Task {
+            content: String::from(content),
+            due: Some(DateInfo {
+                date: date.format("%Y-%m-%d").to_string(),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let tasks = vec![
+            due("Next week", today + chrono::Duration::days(7)),
+            Task {
+                content: String::from("Someday"),
+                due: None,
+                ..test::fixtures::task()
+            },
+            due("Yesterday", today - chrono::Duration::days(1)),
+            due("Tomorrow", today + chrono::Duration::days(1)),
+            due("Now", today),
+        ];
+
+        let output = format_agenda(&config, "Agenda", tasks, &ListFormat::Text);
+
+        let position = |text: &str| output.find(text).unwrap();
+        assert!(output.starts_with("Agenda"));
+        assert!(position("\n\nOverdue\n- Yesterday") < position("\n\nToday\n- Now"));
+        assert!(position("\n\nToday\n- Now") < position("\n\nUpcoming\n- Tomorrow"));
+        assert!(position("- Tomorrow") < position("- Next week"));
+        assert!(position("- Next week") < position("\n\nNo date\n- Someday"));
+        assert!(!output.contains("\n\n\n"));
+    }
+
+    #[tokio::test]
+    async fn select_priority_follows_prompt_order() {
+        let config = test::fixtures::config().await.mock_select(0);
+        assert_eq!(select_priority(&config), Ok(Priority::None));
+
+        let config = Config {
+            priority_prompt_order: Some(vec![4, 3, 2, 1]),
+            ..config
+        };
+        assert_eq!(select_priority(&config), Ok(Priority::High));
+        assert_eq!(select_priority(&config.mock_select(2)), Ok(Priority::Low));
+    }
+
+    #[tokio::test]
+    async fn format_list_nests_subtasks_under_their_parent() {
+        let config = test::fixtures::config().await;
+        let parent = Task {
+            id: String::from("1"),
+            content: String::from("Plan the party"),
+            due: None,
+            ..test::fixtures::task()
+        };
+        let child = Task {
+            id: String::from("2"),
+            content: String::from("Book the venue"),
+            parent_id: Some(String::from("1")),
+            due: None,
+            ..test::fixtures::task()
+        };
+        let other = Task {
+            id: String::from("3"),
+            content: String::from("Water the plants"),
+            due: None,
+            ..test::fixtures::task()
+        };
+
+        let expected = format!(
+            "Tasks\n{}\n  {}\n{}",
+            parent.fmt(&config, FormatType::List, false),
+            child.fmt(&config, FormatType::List, false),
+            other.fmt(&config, FormatType::List, false),
+        );
+        let tasks = vec![child, other, parent];
+        assert_eq!(
+            format_sorted_list(&config, "Tasks", tasks, &ListFormat::Text, false),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn complete_with_subtasks_asks_before_completing_children() {
+        let mut server = mockito::Server::new_async().await;
+        let parent_mock = server
+            .mock("GET", "/rest/v2/tasks/222")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&test::fixtures::task()).unwrap())
+            .expect(2)
+            .create_async()
+            .await;
+        let child = Task {
+            id: String::from("333"),
+            parent_id: Some(String::from("222")),
+            ..test::fixtures::task()
+        };
+        let subtasks_mock = server
+            .mock("GET", "/rest/v2/tasks/?project_id=222")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&vec![test::fixtures::task(), child]).unwrap())
+            .expect(2)
+            .create_async()
+            .await;
+        let complete_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        assert_eq!(
+            complete_with_subtasks(&config.clone().mock_confirm(false), "222").await,
+            Ok(String::from("Left 'Get gifts for the twins' open"))
+        );
+        assert_eq!(
+            complete_with_subtasks(&config.mock_confirm(true), "222").await,
+            Ok(String::from("✓"))
+        );
+        parent_mock.assert();
+        subtasks_mock.assert();
+        complete_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn quick_add_sets_priority_after_adding() {
+        let mut server = mockito::Server::new_async().await;
+        let add_mock = server
+            .mock("POST", "/sync/v9/quick/add")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+        let priority_mock = server
+            .mock("POST", "/rest/v2/tasks/5149481867")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"priority": 4})))
+            .with_status(204)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        assert_eq!(
+            quick_add(&config, "Buy milk p3", false, Some(Priority::High), &None).await,
+            Ok(String::from("✓"))
+        );
+        add_mock.assert();
+        priority_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn quick_add_sets_description_after_adding() {
+        let mut server = mockito::Server::new_async().await;
+        let add_mock = server
+            .mock("POST", "/sync/v9/quick/add")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+        let description_mock = server
+            .mock("POST", "/rest/v2/tasks/5149481867")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "description": "The oat kind"
+            })))
+            .with_status(204)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let description = Some(String::from("The oat kind"));
+
+        assert_eq!(
+            quick_add(&config, "Buy milk", false, None, &description).await,
+            Ok(String::from("✓"))
+        );
+        add_mock.assert();
+        description_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn filter_due_before_excludes_the_bound() {
+        let config = test::fixtures::config().await;
+        let due = |content: &str, date: &str| Task {
+            content: String::from(content),
+            due: Some(DateInfo {
+                date: String::from(date),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+        let tasks = vec![
+            due("Day before", "2024-03-09"),
+            due("On the day", "2024-03-10"),
+            due("Late the night before", "2024-03-09T23:30:00"),
+            due("Early on the day", "2024-03-10T00:30:00"),
+            Task {
+                content: String::from("Undated"),
+                due: None,
+                ..test::fixtures::task()
+            },
+        ];
+
+        let before = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let contents: Vec<String> = filter_due_before(tasks, before, &config)
+            .into_iter()
+            .map(|task| task.content)
+            .collect();
+        assert_eq!(contents, vec!["Day before", "Late the night before"]);
+    }
+
+    #[tokio::test]
+    async fn filter_created_since_uses_creation_date() {
+        let config = test::fixtures::config().await;
+        let old = Task {
+            content: String::from("Old"),
+            added_at: Some(String::from("2024-03-01T10:00:00Z")),
+            ..test::fixtures::task()
+        };
+        let late_utc = Task {
+            content: String::from("Late UTC"),
+            // Still the 9th in US/Pacific
+            added_at: Some(String::from("2024-03-10T05:00:00.000000Z")),
+            ..test::fixtures::task()
+        };
+        let new = Task {
+            content: String::from("New"),
+            added_at: Some(String::from("2024-03-12T20:57:23.608723+00:00")),
+            ..test::fixtures::task()
+        };
+        let unknown = test::fixtures::task();
+
+        let since = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let contents: Vec<String> =
+            filter_created_since(vec![old, late_utc, new, unknown], since, &config)
+                .into_iter()
+                .map(|task| task.content)
+                .collect();
+
+        assert_eq!(contents, vec![String::from("New")]);
+    }
+
+    #[test]
+    fn section_id_is_optional() {
+        let task: Task = serde_json::from_str(&test::responses::sync()).unwrap();
+        assert_eq!(task.section_id, Some(String::from("7025")));
+
+        let mut json: serde_json::Value = serde_json::from_str(&test::responses::sync()).unwrap();
+        json["section_id"] = serde_json::json!(7025);
+        let task: Task = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(task.section_id, Some(String::from("7025")));
+
+        json["section_id"] = serde_json::Value::Null;
+        let task: Task = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(task.section_id, None);
+
+        json.as_object_mut().unwrap().remove("section_id");
+        let task: Task = serde_json::from_value(json).unwrap();
+        assert_eq!(task.section_id, None);
+    }
+
+    #[test]
+    fn added_at_reads_created_at_from_rest_api() {
+        let task: Task = serde_json::from_str(&test::responses::sync()).unwrap();
+        assert_eq!(
+            task.added_at,
+            Some(String::from("2019-12-11T22:36:50.000000Z"))
+        );
+    }
+
+    #[tokio::test]
+    async fn undo_complete_reopens_the_last_completed_task() {
+        let mut server = mockito::Server::new_async().await;
+        let complete = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from("item_close")))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let uncomplete = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(String::from("\"type\":\"item_uncomplete\"")),
+                mockito::Matcher::Regex(String::from("\"args\":\\{\"id\":\"112233\"\\}")),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+
+        let config_dir = dirs::config_dir().unwrap().to_str().unwrap().to_owned();
+        let config = Config {
+            path: format!("{config_dir}/test_undo_complete"),
+            ..test::fixtures::config().await.mock_url(server.url())
+        };
+
+        todoist::complete_task(&config, "112233", false)
+            .await
+            .unwrap();
+        // complete_task doesn't save config in tests, so record it the same way it would
+        let config = config
+            .set_last_completed_id("112233")
+            .create()
+            .await
+            .unwrap();
+
+        assert_eq!(undo_complete(config.clone()).await, Ok(String::from("✓")));
+        complete.assert();
+        uncomplete.assert();
+
+        let config = config.reload().await.unwrap();
+        assert_eq!(config.last_completed_id, None);
+        assert_eq!(
+            undo_complete(config).await,
+            Err(error::new(
+                "undo_complete",
+                "There is nothing to undo. A task must first be completed."
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn complete_by_index_uses_list_order() {
+        let task_at = |id: &str, content: &str, date: &str| Task {
+            id: String::from(id),
+            content: String::from(content),
             due: Some(DateInfo {
-                is_recurring: true,
-                ..test::fixtures::task().due.unwrap()
+                date: String::from(date),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
             }),
             ..test::fixtures::task()
         };
-        assert_eq!(task.date_value(&config), 0);
+        let tasks = vec![
+            task_at("3", "Evening", "2061-11-13T20:00:00Z"),
+            task_at("1", "Morning", "2061-11-13T08:00:00Z"),
+            task_at("2", "Afternoon", "2061-11-13T14:00:00Z"),
+        ];
 
-        // Overdue
-        let task = Task {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""args":\{"id":"2"\}"#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = complete_by_index(&config, tasks.clone(), 2).await;
+        mock.assert();
+        assert_eq!(response, Ok(String::from("✓ Completed Afternoon")));
+
+        assert_eq!(
+            complete_by_index(&config, tasks, 4).await,
+            Err(error::new(
+                "complete_by_index",
+                "There is no task 4, the list has 3 task(s)"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn complete_by_index_counts_subtasks_under_their_parent() {
+        let task_at = |id: &str, content: &str, date: &str| Task {
+            id: String::from(id),
+            content: String::from(content),
             due: Some(DateInfo {
-                date: String::from("2001-11-13"),
-                is_recurring: true,
-                timezone: Some(String::from("America/Los_Angeles")),
-                string: String::from("Every 2 weeks"),
+                date: String::from(date),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
             }),
             ..test::fixtures::task()
         };
-        assert_eq!(task.date_value(&config), 150);
+        // Listed as Parent, then Child indented under it, then Other
+        let tasks = vec![
+            task_at("1", "Parent", "2061-11-13T20:00:00Z"),
+            Task {
+                parent_id: Some(String::from("1")),
+                ..task_at("2", "Child", "2061-11-13T08:00:00Z")
+            },
+            task_at("3", "Other", "2061-11-13T14:00:00Z"),
+        ];
 
-        // No date
-        let task = Task { due: None, ..task };
-        assert_eq!(task.date_value(&config), 80);
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""args":\{"id":"2"\}"#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = complete_by_index(&config, tasks, 2).await;
+        mock.assert();
+        assert_eq!(response, Ok(String::from("✓ Completed Child")));
+    }
+
+    #[test]
+    fn append_description_adds_a_line() {
+        assert_eq!(
+            append_description("Call first", "Ask about prices"),
+            "Call first\nAsk about prices"
+        );
+        assert_eq!(
+            append_description("Call first\n", "Ask about prices"),
+            "Call first\nAsk about prices"
+        );
+        assert_eq!(
+            append_description("", "Ask about prices"),
+            "Ask about prices"
+        );
     }
 
     #[tokio::test]
-    async fn date_value_can_handle_datetime() {
-        let config = test::fixtures::config().await;
+    async fn append_to_description_keeps_the_current_description() {
+        let mut server = mockito::Server::new_async().await;
+        let get_mock = server
+            .mock("GET", "/rest/v2/tasks/5149481867")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                test::responses::task()
+                    .replace("\"description\":\"\"", "\"description\":\"Call first\""),
+            )
+            .create_async()
+            .await;
+        let update_mock = server
+            .mock("POST", "/rest/v2/tasks/5149481867")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "description": "Call first\nAsk about prices"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
         let task = Task {
-            due: Some(DateInfo {
-                date: String::from("2021-02-27T19:41:56Z"),
-                ..test::fixtures::task().due.unwrap()
-            }),
+            id: String::from("5149481867"),
             ..test::fixtures::task()
         };
 
-        assert_eq!(task.date_value(&config), 50);
+        assert_eq!(
+            append_to_description(&config, &task, "Ask about prices").await,
+            Ok(format!(
+                "{} 'testy test'",
+                color::success_string("✓ Added to the description of", &config.colors)
+            ))
+        );
+        get_mock.assert();
+        update_mock.assert();
     }
 
     #[tokio::test]
-    async fn can_format_task_with_a_date() {
+    async fn complete_with_followup_completes_then_creates() {
+        let mut server = mockito::Server::new_async().await;
+        let get_mock = server
+            .mock("GET", "/rest/v2/tasks/5149481867")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+        let complete_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""args":\{"id":"5149481867"\}.*"type":"item_close""#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let create_mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "content": "Send the minutes",
+                "project_id": "5555555"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = complete_with_followup(&config, "5149481867", "Send the minutes").await;
+        assert_eq!(
+            response,
+            Ok(String::from(
+                "✓ Completed testy test\n✓ Added Send the minutes"
+            ))
+        );
+        get_mock.assert();
+        complete_mock.assert();
+        create_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn assign_sets_assignee_and_fmt_shows_name() {
+        let task = test::fixtures::task();
+        let mut server = mockito::Server::new_async().await;
+        let collaborators_mock = server
+            .mock("GET", "/rest/v2/projects/222/collaborators")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::collaborators())
+            .create_async()
+            .await;
+        let assign_mock = server
+            .mock("POST", "/rest/v2/tasks/222")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"assignee_id": "2671366"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = assign(&config, task.clone(), "bob").await;
+        collaborators_mock.assert();
+        assign_mock.assert();
+        assert_eq!(
+            response,
+            Ok(String::from("✓ Assigned 'Get gifts for the twins' to Bob"))
+        );
+
+        let collaborators =
+            collaborators::json_to_collaborators(test::responses::collaborators()).unwrap();
+        let config = config.with_collaborators(&collaborators);
+        let assigned = Task {
+            assignee_id: Some(String::from("2671366")),
+            labels: vec![],
+            due: None,
+            ..task
+        };
+        assert_eq!(
+            assigned.fmt(&config, FormatType::Single, false),
+            "Get gifts for the twins → Bob \n"
+        );
+        let unknown = Task {
+            assignee_id: Some(String::from("999")),
+            ..assigned
+        };
+        assert!(unknown
+            .fmt(&config, FormatType::Single, false)
+            .contains("→ 999"));
+    }
+
+    #[tokio::test]
+    async fn snooze_moves_a_dated_task_by_a_day() {
+        let task = test::fixtures::task();
+        let url: &str = &format!("/rest/v2/tasks/{}", task.id);
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", url)
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"due_string": "2061-11-14"}),
+            ))
+            .with_status(204)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = snooze(&config, task, chrono::Duration::days(1)).await;
+        mock.assert();
+        assert_eq!(response, Ok(String::from("✓ Snoozed until 2061-11-14")));
+    }
+
+    #[tokio::test]
+    async fn snoozed_due_handles_hours_and_missing_dates() {
         let config = test::fixtures::config().await;
-        let task = Task {
-            content: String::from("Get gifts for the twins"),
+        let task = test::fixtures::task();
+        assert_eq!(
+            task.snoozed_due(chrono::Duration::hours(1), &config),
+            Ok(String::from("2061-11-13 01:00"))
+        );
+        assert_eq!(
+            task.snoozed_due(chrono::Duration::weeks(1), &config),
+            Ok(String::from("2061-11-20"))
+        );
+
+        let undated = Task {
+            due: None,
+            ..test::fixtures::task()
+        };
+        let tomorrow = time::today_date(&config).unwrap() + chrono::Duration::days(1);
+        assert_eq!(
+            undated.snoozed_due(chrono::Duration::days(1), &config),
+            Ok(tomorrow.format("%Y-%m-%d").to_string())
+        );
+        assert_eq!(
+            task.snoozed_due(chrono::Duration::weeks(1_000_000_000), &config),
+            Err(error::new(
+                "snooze",
+                "Snoozing by that much is past the last date"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn days_overdue_counts_from_today() {
+        let config = test::fixtures::config().await;
+        let today = time::today_date(&config).unwrap();
+        let due_on = |date: NaiveDate| Task {
             due: Some(DateInfo {
-                date: String::from("2021-08-13"),
-                ..test::fixtures::task().due.unwrap()
+                date: date.format("%Y-%m-%d").to_string(),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
             }),
             ..test::fixtures::task()
         };
 
-        let task = task.fmt(&config, FormatType::Single, false);
+        assert_eq!(days_overdue(&due_on(today), &config), None);
+        assert_eq!(
+            days_overdue(&due_on(today + chrono::Duration::days(1)), &config),
+            None
+        );
+        assert_eq!(
+            days_overdue(&due_on(today - chrono::Duration::days(1)), &config),
+            Some(1)
+        );
+        assert_eq!(
+            days_overdue(&due_on(today - chrono::Duration::days(10)), &config),
+            Some(10)
+        );
+        let undated = Task {
+            due: None,
+            ..test::fixtures::task()
+        };
+        assert_eq!(days_overdue(&undated, &config), None);
 
-        assert!(task.contains("Get gifts for the twins"));
-        assert!(task.contains("2021-08-13"));
+        let tasks = vec![
+            due_on(today - chrono::Duration::days(3)),
+            due_on(today - chrono::Duration::days(4)),
+        ];
+        let contents: Vec<String> = mark_stale(tasks, &config, 3)
+            .into_iter()
+            .map(|task| task.content)
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                String::from("Get gifts for the twins"),
+                String::from("Get gifts for the twins (stale 4d)")
+            ]
+        );
     }
 
     #[tokio::test]
-    async fn can_format_task_with_today() {
+    async fn reject_excluded_labels_skips_waiting_tasks() {
         let config = test::fixtures::config().await;
-        let task = Task {
-            content: String::from("Get gifts for the twins"),
-            due: Some(DateInfo {
-                date: time::today_string(&config).unwrap(),
-                ..test::fixtures::task().due.unwrap()
-            }),
+        let waiting = Task {
+            content: String::from("Blocked on review"),
+            labels: vec![String::from("waiting")],
+            priority: Priority::High,
+            ..test::fixtures::task()
+        };
+        let other = Task {
+            content: String::from("Write docs"),
+            labels: vec![],
+            priority: Priority::Low,
             ..test::fixtures::task()
         };
+        let tasks = vec![other, waiting];
 
-        let task_text = task.fmt(&config, FormatType::Single, true);
+        let sorted = sort_by_value(tasks.clone(), &config);
+        assert_eq!(sorted[0].content, "Blocked on review");
 
-        assert!(task_text.contains("Today @ computer"));
+        let config_excluding = Config {
+            exclude_labels: Some(vec![String::from("waiting")]),
+            ..config.clone()
+        };
+        let sorted = sort_by_value(
+            reject_excluded_labels(tasks.clone(), &config_excluding, &[]),
+            &config_excluding,
+        );
+        assert_eq!(sorted.len(), 1);
+        assert_eq!(sorted[0].content, "Write docs");
+
+        let sorted = sort_by_value(
+            reject_excluded_labels(tasks, &config, &[String::from("@waiting")]),
+            &config,
+        );
+        assert_eq!(sorted.len(), 1);
+        assert_eq!(sorted[0].content, "Write docs");
+    }
+
+    #[tokio::test]
+    async fn preview_lines_lists_upcoming_tasks() {
+        let config = test::fixtures::config().await;
+        let upcoming = vec![
+            Task {
+                content: String::from("First"),
+                ..test::fixtures::task()
+            },
+            Task {
+                content: String::from("Second"),
+                ..test::fixtures::task()
+            },
+            Task {
+                content: String::from("Third"),
+                ..test::fixtures::task()
+            },
+        ];
+
+        assert_eq!(preview_lines(&config, &upcoming), String::new());
+
+        let config = Config {
+            preview: Some(2),
+            ..config
+        };
+        assert_eq!(
+            preview_lines(&config, &upcoming),
+            String::from("\n→ First\n→ Second")
+        );
     }
 
     #[tokio::test]
@@ -809,6 +3100,41 @@ mod tests {
         assert_eq!(sort_by_value(input, &config), result);
     }
 
+    #[tokio::test]
+    async fn sort_by_value_breaks_ties_by_id() {
+        let config = test::fixtures::config().await;
+        let task_with_id = |id: &str| Task {
+            id: String::from(id),
+            ..test::fixtures::task()
+        };
+        let high = Task {
+            priority: Priority::High,
+            ..task_with_id("500")
+        };
+        let expected = vec!["500", "99", "100", "222"];
+
+        for input in [
+            vec![
+                task_with_id("222"),
+                task_with_id("100"),
+                high.clone(),
+                task_with_id("99"),
+            ],
+            vec![
+                task_with_id("99"),
+                task_with_id("222"),
+                task_with_id("100"),
+                high.clone(),
+            ],
+        ] {
+            let ids: Vec<String> = sort_by_value(input, &config)
+                .into_iter()
+                .map(|task| task.id)
+                .collect();
+            assert_eq!(ids, expected);
+        }
+    }
+
     #[tokio::test]
     async fn sort_by_datetime_works() {
         let config = test::fixtures::config().await;
@@ -828,6 +3154,9 @@ mod tests {
             priority: Priority::Medium,
             is_deleted: None,
             is_completed: None,
+            added_at: None,
+            section_id: None,
+            assignee_id: None,
         };
 
         let date_not_datetime = Task {
@@ -898,6 +3227,9 @@ mod tests {
             priority: Priority::Medium,
             is_deleted: None,
             is_completed: None,
+            added_at: None,
+            section_id: None,
+            assignee_id: None,
         };
 
         assert!(!task.is_overdue(&config).unwrap());
@@ -936,6 +3268,36 @@ mod tests {
         assert!(task_today.is_overdue(&config).unwrap());
     }
 
+    #[tokio::test]
+    async fn is_today_and_is_overdue_respect_day_start_hour() {
+        let config = Config {
+            day_start_hour: Some(4),
+            ..test::fixtures::config().await
+        };
+        let today = time::today_date(&config).unwrap();
+        let due_at = |date: NaiveDate, time: &str| Task {
+            due: Some(DateInfo {
+                date: format!("{}T{time}", date.format("%Y-%m-%d")),
+                is_recurring: false,
+                string: String::new(),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+
+        let before_start = due_at(today, "03:00:00");
+        assert!(!before_start.is_today(&config).unwrap());
+        assert!(before_start.is_overdue(&config).unwrap());
+
+        let after_start = due_at(today, "05:00:00");
+        assert!(after_start.is_today(&config).unwrap());
+        assert!(!after_start.is_overdue(&config).unwrap());
+
+        let early_tomorrow = due_at(today.succ_opt().unwrap(), "03:00:00");
+        assert!(early_tomorrow.is_today(&config).unwrap());
+        assert!(!early_tomorrow.is_overdue(&config).unwrap());
+    }
+
     #[test]
     fn test_to_integer() {
         assert_eq!(Priority::None.to_integer(), 1);
@@ -966,6 +3328,132 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn schedule_can_defer_a_skipped_task_to_the_end() {
+        let first = test::fixtures::task();
+        let second = Task {
+            id: String::from("333"),
+            ..test::fixtures::task()
+        };
+        let mut inputs = vec![
+            DateTimeInput::Skip,
+            DateTimeInput::Text(String::from("today")),
+            DateTimeInput::Text(String::from("tomorrow")),
+        ]
+        .into_iter();
+        let mut prompted = Vec::new();
+
+        let decisions = collect_schedule_decisions(vec![first.clone(), second.clone()], |task| {
+            prompted.push(task.id.clone());
+            Ok(inputs.next().unwrap())
+        })
+        .unwrap();
+
+        assert_eq!(prompted, vec!["222", "333", "222"]);
+        assert_eq!(
+            decisions,
+            vec![
+                (second, DateTimeInput::Text(String::from("today"))),
+                (first, DateTimeInput::Text(String::from("tomorrow"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn schedule_can_go_back_to_the_previous_task() {
+        let first = test::fixtures::task();
+        let second = Task {
+            id: String::from("333"),
+            ..test::fixtures::task()
+        };
+        let mut inputs = vec![
+            DateTimeInput::Text(String::from("today")),
+            DateTimeInput::Back,
+            DateTimeInput::Text(String::from("tomorrow")),
+            DateTimeInput::None,
+        ]
+        .into_iter();
+
+        let decisions = collect_schedule_decisions(vec![first.clone(), second.clone()], |_| {
+            Ok(inputs.next().unwrap())
+        })
+        .unwrap();
+
+        assert_eq!(
+            decisions,
+            vec![
+                (first, DateTimeInput::Text(String::from("tomorrow"))),
+                (second, DateTimeInput::None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn process_done_keeps_recurring_task() {
+        let config = test::fixtures::config().await;
+        let tomorrow = time::today_date(&config).unwrap() + chrono::Duration::days(1);
+        let task = Task {
+            due: Some(DateInfo {
+                date: String::from("2024-01-01"),
+                is_recurring: true,
+                string: String::from("every day"),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+
+        let mut server = mockito::Server::new_async().await;
+        let update_mock = server
+            .mock("POST", "/rest/v2/tasks/222")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "due_string": format!("every day starting {}", tomorrow.format("%Y-%m-%d"))
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = config.mock_url(server.url()).mock_select(1);
+        let mut task_count = 1;
+        let Processed::Handle(handle) = process_task(&config, task, &mut task_count, false).await
+        else {
+            panic!("Expected a handle");
+        };
+        handle.await.unwrap();
+        update_mock.assert();
+        close_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn next_occurrence_after_today_needs_a_positive_interval() {
+        let config = test::fixtures::config().await;
+        let due_every = |string: &str| Task {
+            due: Some(DateInfo {
+                date: String::from("2024-01-01"),
+                is_recurring: true,
+                string: String::from(string),
+                timezone: None,
+            }),
+            ..test::fixtures::task()
+        };
+
+        let today = time::today_date(&config).unwrap();
+        let next = due_every("every 2 days")
+            .next_occurrence_after_today(&config)
+            .unwrap();
+        assert!(next > today && next <= today + chrono::Duration::days(2));
+        assert_eq!(
+            due_every("every 0 days").next_occurrence_after_today(&config),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn test_process_task() {
         let mut server = mockito::Server::new_async().await;
@@ -983,11 +3471,40 @@ mod tests {
             .mock_url(server.url())
             .mock_select(0);
         let mut task_count = 3;
-        process_task(&config, task, &mut task_count, true)
-            .await
-            .unwrap()
-            .await
-            .unwrap();
+        let Processed::Handle(handle) = process_task(&config, task, &mut task_count, true).await
+        else {
+            panic!("Expected a handle");
+        };
+        handle.await.unwrap();
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn process_jump_to_matched_task() {
+        let config = test::fixtures::config()
+            .await
+            .mock_select(3)
+            .mock_string("LAUNDRY");
+        let current = test::fixtures::task();
+        let mut task_count = 3;
+        let processed = process_task(&config, current.clone(), &mut task_count, false).await;
+        assert!(matches!(processed, Processed::Jump(ref text) if text == "LAUNDRY"));
+        assert_eq!(task_count, 3);
+
+        let laundry = Task {
+            id: String::from("333"),
+            content: String::from("Do the laundry"),
+            ..test::fixtures::task()
+        };
+        let dishes = Task {
+            id: String::from("444"),
+            content: String::from("Wash the dishes"),
+            ..test::fixtures::task()
+        };
+        let mut tasks = vec![current.clone(), dishes.clone(), laundry.clone()];
+
+        assert!(jump_to(&mut tasks, 0, "LAUNDRY"));
+        assert_eq!(tasks, vec![laundry, current, dishes]);
+        assert!(!jump_to(&mut tasks, 1, "laundry"));
+    }
 }
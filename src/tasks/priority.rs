@@ -1,4 +1,11 @@
 use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::color;
+use crate::config::Config;
+use crate::error::{self, Error, Kind};
+use crate::projects::Project;
+use crate::tasks::{self, Task};
 
 #[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr, Debug, Clone, Eq, PartialEq)]
 #[repr(u8)]
@@ -41,3 +48,304 @@ pub fn from_integer(priority: &Option<u8>) -> Option<Priority> {
         Some(_) => None,
     }
 }
+
+/// How --priority numbers and p1 to p4 map to priorities, in the colors used for tasks
+pub fn legend(config: &Config) -> String {
+    let mut buffer = color::green_string("--priority  Priority (in content)");
+    for priority in [
+        Priority::High,
+        Priority::Medium,
+        Priority::Low,
+        Priority::None,
+    ] {
+        let line = format!("{:<12}{priority}", priority.to_integer());
+        buffer.push('\n');
+        buffer.push_str(&color::priority_string(&line, &priority, &config.colors));
+    }
+    buffer
+}
+
+/// The order priorities are offered in when prompting, from priority_prompt_order in config
+pub fn prompt_order(config: &Config) -> Vec<Priority> {
+    match &config.priority_prompt_order {
+        Some(order) => order
+            .iter()
+            .filter_map(|priority| from_integer(&Some(*priority)))
+            .collect(),
+        None => vec![
+            Priority::None,
+            Priority::Low,
+            Priority::Medium,
+            Priority::High,
+        ],
+    }
+}
+
+/// The prompt order has to list each priority from 1 to 4 exactly once
+pub fn validate_prompt_order(order: &[u8]) -> Result<(), Error> {
+    let mut sorted = order.to_vec();
+    sorted.sort_unstable();
+    if sorted == [1, 2, 3, 4] {
+        Ok(())
+    } else {
+        Err(error::with_kind(
+            Kind::Config,
+            "priority_prompt_order",
+            &format!("{order:?} must list each priority from 1 to 4 once, i.e. [4, 3, 2, 1]"),
+        ))
+    }
+}
+
+/// The priority from project_priorities in config, looked up by project name
+pub fn project_default(config: &Config, project: &Project) -> Option<Priority> {
+    let priority = config
+        .project_priorities
+        .as_ref()?
+        .get(&project.name)
+        .copied();
+    from_integer(&priority)
+}
+
+/// What a task is checked against in a priority rule
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Condition {
+    Overdue,
+    DueToday,
+    HasLabel(String),
+}
+
+/// Assigns a priority to tasks meeting a condition, written as "overdue=high" on the command line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rule {
+    pub condition: Condition,
+    pub priority: Priority,
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            error::new(
+                "priority_rule",
+                &format!("'{str}' is not a valid rule, {reason}"),
+            )
+        };
+        let (condition, priority) = str
+            .split_once('=')
+            .ok_or_else(|| invalid("use condition=priority, i.e. overdue=high"))?;
+
+        let condition = match condition.trim().to_lowercase().as_str() {
+            "overdue" => Condition::Overdue,
+            "due-today" | "today" => Condition::DueToday,
+            other => match other.strip_prefix("has-label:") {
+                Some(label) if !label.is_empty() => {
+                    Condition::HasLabel(label.trim_start_matches('@').to_string())
+                }
+                _ => {
+                    return Err(invalid(
+                        "conditions are overdue, due-today, and has-label:<label>",
+                    ))
+                }
+            },
+        };
+
+        let priority = match priority.trim().to_lowercase().as_str() {
+            "high" | "p1" => Priority::High,
+            "medium" | "p2" => Priority::Medium,
+            "low" | "p3" => Priority::Low,
+            "none" | "p4" => Priority::None,
+            _ => return Err(invalid("priorities are high, medium, low, and none")),
+        };
+
+        Ok(Rule {
+            condition,
+            priority,
+        })
+    }
+}
+
+impl Rule {
+    fn matches(&self, task: &Task, config: &Config) -> bool {
+        match &self.condition {
+            Condition::Overdue => task.is_overdue(config).unwrap_or(false),
+            Condition::DueToday => task.is_today(config).unwrap_or(false),
+            Condition::HasLabel(label) => tasks::has_label(task, label),
+        }
+    }
+}
+
+/// The priority of the first rule the task matches
+pub fn from_rules(rules: &[Rule], task: &Task, config: &Config) -> Option<Priority> {
+    rules
+        .iter()
+        .find(|rule| rule.matches(task, config))
+        .map(|rule| rule.priority.clone())
+}
+
+/// Splits a trailing Todoist priority such as "p1" from the content, where p1 is the highest
+pub fn split_inline(content: &str) -> (String, Option<Priority>) {
+    let trimmed = content.trim_end();
+    let Some((rest, token)) = trimmed.rsplit_once(' ') else {
+        return (content.to_string(), None);
+    };
+
+    let priority = match token.to_lowercase().as_str() {
+        "p1" => Priority::High,
+        "p2" => Priority::Medium,
+        "p3" => Priority::Low,
+        "p4" => Priority::None,
+        _ => return (content.to_string(), None),
+    };
+
+    (rest.trim_end().to_string(), Some(priority))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::DateInfo;
+    use crate::{test, time};
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn legend_lists_every_priority() {
+        let config = test::fixtures::config().await;
+        let legend = legend(&config);
+
+        for line in [
+            "4           HIGH (P1)",
+            "3           MEDIUM (P2)",
+            "2           LOW (P3)",
+            "1           NONE (P4)",
+        ] {
+            assert!(legend.contains(line), "{line} is missing from {legend}");
+        }
+    }
+
+    #[test]
+    fn test_validate_prompt_order() {
+        assert_eq!(validate_prompt_order(&[4, 1, 2, 3]), Ok(()));
+        assert!(validate_prompt_order(&[4, 3, 2]).is_err());
+        assert!(validate_prompt_order(&[4, 4, 2, 1]).is_err());
+        assert!(validate_prompt_order(&[5, 3, 2, 1]).is_err());
+    }
+
+    fn rule(str: &str) -> Rule {
+        Rule::from_str(str).unwrap()
+    }
+
+    #[test]
+    fn rule_parses_conditions_and_priorities() {
+        assert_eq!(
+            rule("overdue=high"),
+            Rule {
+                condition: Condition::Overdue,
+                priority: Priority::High
+            }
+        );
+        assert_eq!(
+            rule("due-today=p2"),
+            Rule {
+                condition: Condition::DueToday,
+                priority: Priority::Medium
+            }
+        );
+        assert_eq!(
+            rule("has-label:@waiting=low"),
+            Rule {
+                condition: Condition::HasLabel(String::from("waiting")),
+                priority: Priority::Low
+            }
+        );
+
+        let error = Rule::from_str("someday=high").unwrap_err();
+        assert_eq!(error.source, "priority_rule");
+        assert_eq!(
+            error.message,
+            "'someday=high' is not a valid rule, conditions are overdue, due-today, and has-label:<label>"
+        );
+        assert!(Rule::from_str("overdue").is_err());
+        assert!(Rule::from_str("overdue=urgent").is_err());
+    }
+
+    #[tokio::test]
+    async fn from_rules_uses_first_match() {
+        let config = test::fixtures::config().await;
+        let due_on = |date: String| DateInfo {
+            date,
+            string: String::new(),
+            is_recurring: false,
+            timezone: None,
+        };
+        let overdue = Task {
+            due: Some(due_on(String::from("2001-01-01"))),
+            labels: vec![],
+            ..test::fixtures::task()
+        };
+        let today = Task {
+            due: Some(due_on(time::today_string(&config).unwrap())),
+            labels: vec![],
+            ..test::fixtures::task()
+        };
+        let labelled = Task {
+            due: None,
+            labels: vec![String::from("errand")],
+            ..test::fixtures::task()
+        };
+        let rules = vec![
+            rule("overdue=high"),
+            rule("due-today=medium"),
+            rule("has-label:errand=low"),
+        ];
+
+        assert_eq!(from_rules(&rules, &overdue, &config), Some(Priority::High));
+        assert_eq!(from_rules(&rules, &today, &config), Some(Priority::Medium));
+        assert_eq!(from_rules(&rules, &labelled, &config), Some(Priority::Low));
+        assert_eq!(
+            from_rules(
+                &rules,
+                &Task {
+                    labels: vec![],
+                    ..labelled.clone()
+                },
+                &config
+            ),
+            None
+        );
+
+        let overdue_errand = Task {
+            labels: vec![String::from("errand")],
+            ..overdue
+        };
+        assert_eq!(
+            from_rules(&rules[1..], &overdue_errand, &config),
+            Some(Priority::Low)
+        );
+        assert_eq!(
+            from_rules(&rules, &overdue_errand, &config),
+            Some(Priority::High)
+        );
+    }
+
+    #[test]
+    fn split_inline_takes_trailing_priority() {
+        assert_eq!(
+            split_inline("fix bug p1"),
+            (String::from("fix bug"), Some(Priority::High))
+        );
+        assert_eq!(
+            split_inline("water plants P4"),
+            (String::from("water plants"), Some(Priority::None))
+        );
+        assert_eq!(
+            split_inline("read chapter p5"),
+            (String::from("read chapter p5"), None)
+        );
+        assert_eq!(split_inline("p1"), (String::from("p1"), None));
+        assert_eq!(
+            split_inline("p1 of the plan"),
+            (String::from("p1 of the plan"), None)
+        );
+    }
+}
@@ -33,6 +33,9 @@ pub mod fixtures {
             priority: crate::tasks::priority::Priority::Medium,
             is_deleted: None,
             is_completed: None,
+            added_at: None,
+            section_id: None,
+            assignee_id: None,
         }
     }
 
@@ -41,7 +44,16 @@ pub mod fixtures {
             token: String::from("alreadycreated"),
             sort_value: Some(SortValue::default()),
             disable_links: false,
-            internal: Internal { tx: tx() },
+            show_urls: None,
+            show_remaining: None,
+            preview: None,
+            exclude_labels: None,
+            project_priorities: None,
+            priority_prompt_order: None,
+            internal: Internal {
+                tx: tx(),
+                ..Internal::default()
+            },
             projects: Some(vec![Project {
                 id: "123".to_string(),
                 name: "myproject".to_string(),
@@ -58,9 +70,17 @@ pub mod fixtures {
             }]),
             path: config::generate_path().await.unwrap(),
             next_id: None,
+            last_completed_id: None,
+            last_project: None,
+            inbox: None,
+            roll_past_weekday_times: None,
             args: Args {
                 timeout: None,
                 verbose: false,
+                quiet: false,
+                dry_run: false,
+                yes: false,
+                timezone: None,
             },
             timezone: Some(String::from("US/Pacific")),
             timeout: None,
@@ -70,7 +90,15 @@ pub mod fixtures {
             mock_string: None,
             verbose: None,
             mock_select: None,
+            mock_confirm: None,
+            mock_multiselect: None,
             natural_language_only: None,
+            always_prompt_description: None,
+            language: None,
+            day_start_hour: None,
+            default_command: None,
+            date_input_format: None,
+            colors: None,
             spinners: Some(true),
         }
     }
@@ -169,7 +197,7 @@ pub mod responses {
                 \"priority\":3,\
                 \"project_id\":\"22222222\",\
                 \"responsible_uid\":null,\
-                \"section_id\":333333333,\
+                \"section_id\":\"1234\",\
                 \"sync_id\":null,\
                 \"user_id\":111111111\
                 }}
@@ -206,7 +234,7 @@ pub mod responses {
                 \"priority\":3,\
                 \"project_id\":\"22222222\",\
                 \"responsible_uid\":null,\
-                \"section_id\":333333333,\
+                \"section_id\":\"1234\",\
                 \"sync_id\":null,\
                 \"user_id\":111111111\
                 }}
@@ -240,7 +268,7 @@ pub mod responses {
                 \"priority\":3,\
                 \"project_id\":\"22222222\",\
                 \"responsible_uid\":null,\
-                \"section_id\":333333333,\
+                \"section_id\":\"1234\",\
                 \"sync_id\":null,\
                 \"user_id\":111111111\
                 }
@@ -272,7 +300,7 @@ pub mod responses {
                 \"priority\":3,\
                 \"project_id\":\"22222222\",\
                 \"responsible_uid\":null,\
-                \"section_id\":333333333,\
+                \"section_id\":\"1234\",\
                 \"sync_id\":null,\
                 \"user_id\":111111111\
                 }
@@ -309,6 +337,24 @@ pub mod responses {
     }",
         )
     }
+    pub fn collaborators() -> String {
+        String::from(
+            "[
+                {\"id\": \"2671362\", \"name\": \"Alice\", \"email\": \"alice@example.com\"},
+                {\"id\": \"2671366\", \"name\": \"Bob\", \"email\": \"bob@example.com\"}
+            ]",
+        )
+    }
+
+    pub fn labels() -> String {
+        String::from(
+            "[
+                {\"id\": \"2156154810\", \"name\": \"errands\", \"color\": \"charcoal\", \"order\": 1, \"is_favorite\": false},
+                {\"id\": \"2156154812\", \"name\": \"waiting\", \"color\": \"blue\", \"order\": 2, \"is_favorite\": true}
+            ]",
+        )
+    }
+
     pub fn sections() -> String {
         String::from(
             "[
@@ -329,6 +375,116 @@ pub mod responses {
         )
     }
 
+    pub fn user() -> String {
+        String::from(
+            "{
+              \"full_sync\": true,
+              \"sync_token\": \"abcdefg\",
+              \"user\": {
+                \"id\": \"2671355\",
+                \"full_name\": \"Alice\",
+                \"tz_info\": {
+                  \"gmt_string\": \"-07:00\",
+                  \"hours\": -7,
+                  \"is_dst\": 1,
+                  \"minutes\": 0,
+                  \"timezone\": \"America/Vancouver\"
+                }
+              }
+            }",
+        )
+    }
+
+    pub fn project_add() -> String {
+        String::from(
+            "{
+              \"sync_status\": {\"42963283-2bab-4b1f-bad2-278ef2b6ba2c\": \"ok\"},
+              \"temp_id_mapping\": {\"42963283-2bab-4b1f-bad2-278ef2b6ba2c\": \"999\"},
+              \"full_sync\": false,
+              \"sync_token\": \"abcdefg\"
+            }",
+        )
+    }
+
+    pub fn project() -> String {
+        String::from(
+            "{
+              \"id\": \"999\",
+              \"order\": 1,
+              \"comment_count\": 0,
+              \"is_shared\": false,
+              \"is_favorite\": false,
+              \"is_inbox_project\": false,
+              \"is_team_inbox\": false,
+              \"color\": \"blue\",
+              \"view_style\": \"list\",
+              \"url\": \"http://www.example.com/\",
+              \"name\": \"Doomsday\",
+              \"parent_id\": null
+            }",
+        )
+    }
+
+    pub fn completed_tasks() -> String {
+        String::from(
+            "{
+              \"items\": [
+                {
+                  \"completed_at\": \"2024-05-03T06:30:00.000000Z\",
+                  \"content\": \"Write the report\",
+                  \"id\": \"1182\",
+                  \"item_object\": {\"content\": \"Write the report\", \"labels\": [\"work\"]},
+                  \"meta_data\": null,
+                  \"note_count\": 0,
+                  \"notes\": [],
+                  \"project_id\": \"123\",
+                  \"section_id\": null,
+                  \"task_id\": \"2995104340\",
+                  \"user_id\": \"2671355\"
+                },
+                {
+                  \"completed_at\": \"2024-05-01T06:59:00.000000Z\",
+                  \"content\": \"Too early\",
+                  \"id\": \"1180\",
+                  \"meta_data\": null,
+                  \"note_count\": 0,
+                  \"notes\": [],
+                  \"project_id\": \"123\",
+                  \"section_id\": null,
+                  \"task_id\": \"2995104338\",
+                  \"user_id\": \"2671355\"
+                },
+                {
+                  \"completed_at\": \"2024-05-01T07:15:00.000000Z\",
+                  \"content\": \"Take out the trash\",
+                  \"id\": \"1181\",
+                  \"meta_data\": null,
+                  \"note_count\": 0,
+                  \"notes\": [],
+                  \"project_id\": \"123\",
+                  \"section_id\": null,
+                  \"task_id\": \"2995104339\",
+                  \"user_id\": \"2671355\"
+                },
+                {
+                  \"completed_at\": \"2024-05-03T07:00:00.000000Z\",
+                  \"content\": \"Too late\",
+                  \"id\": \"1183\",
+                  \"meta_data\": null,
+                  \"note_count\": 0,
+                  \"notes\": [],
+                  \"project_id\": \"123\",
+                  \"section_id\": null,
+                  \"task_id\": \"2995104341\",
+                  \"user_id\": \"2671355\"
+                }
+              ],
+              \"projects\": {},
+              \"sections\": {}
+            }",
+        )
+    }
+
     pub fn projects() -> String {
         String::from(
             "[
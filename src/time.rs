@@ -1,8 +1,10 @@
 use crate::config::Config;
-use crate::error::{self, Error};
+use crate::error::{self, Error, Kind};
+use crate::tasks::DateInfo;
+use chrono::format::{Item, StrftimeItems};
 use chrono::offset::Utc;
-use chrono::{DateTime, NaiveDate, NaiveDateTime};
-use chrono_tz::Tz;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::{Tz, TZ_VARIANTS};
 use regex::Regex;
 
 pub fn now(config: &Config) -> Result<DateTime<Tz>, Error> {
@@ -12,16 +14,23 @@ pub fn now(config: &Config) -> Result<DateTime<Tz>, Error> {
 
 /// Return today's date in format 2021-09-16
 pub fn today_string(config: &Config) -> Result<String, Error> {
-    Ok(now(config)?.format("%Y-%m-%d").to_string())
+    Ok(today_date(config)?.format("%Y-%m-%d").to_string())
 }
 
 /// Return today's date in Utc
 pub fn today_date(config: &Config) -> Result<NaiveDate, Error> {
-    Ok(now(config)?.date_naive())
+    Ok(date_for_datetime(now(config)?, config))
+}
+
+/// The date that a datetime falls on, treating anything before day_start_hour as the previous day
+pub fn date_for_datetime(datetime: DateTime<Tz>, config: &Config) -> NaiveDate {
+    let day_start_hour = config.day_start_hour.unwrap_or_default().min(23);
+
+    (datetime - Duration::hours(day_start_hour.into())).date_naive()
 }
 
 pub fn datetime_is_today(datetime: DateTime<Tz>, config: &Config) -> Result<bool, Error> {
-    date_is_today(datetime.date_naive(), config)
+    date_is_today(date_for_datetime(datetime, config), config)
 }
 
 pub fn date_is_today(date: NaiveDate, config: &Config) -> Result<bool, Error> {
@@ -48,7 +57,28 @@ pub fn format_datetime(datetime: &DateTime<Tz>, config: &Config) -> Result<Strin
     if datetime_is_today(*datetime, config)? {
         Ok(datetime.with_timezone(&tz).format("%H:%M").to_string())
     } else {
-        Ok(datetime.with_timezone(&tz).to_string())
+        Ok(datetime
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M")
+            .to_string())
+    }
+}
+
+/// How far away a duration is, i.e. "in 25m", "2h ago" or "now" when under a minute
+pub fn format_relative(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    let (hours, remainder) = (minutes.abs() / 60, minutes.abs() % 60);
+    let amount = match (hours, remainder) {
+        (0, 0) => return String::from("now"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    };
+
+    if minutes > 0 {
+        format!("in {amount}")
+    } else {
+        format!("{amount} ago")
     }
 }
 
@@ -77,11 +107,58 @@ pub fn timezone_from_str(timezone_string: &Option<String>) -> Result<Tz, Error>
         None => Ok(Tz::UTC),
         Some(string) => match string.parse::<Tz>() {
             Ok(tz) => Ok(tz),
-            Err(_) => parse_gmt_to_timezone(string),
+            Err(_) => parse_gmt_to_timezone(string).map_err(|_| {
+                error::new(
+                    "timezone_from_str",
+                    &format!("'{string}' is not a valid timezone"),
+                )
+            }),
         },
     }
 }
 
+/// Timezone names matching the query, ignoring case and treating underscores as spaces.
+/// An exact match wins, then names containing the query, then names containing its letters in order.
+pub fn fuzzy_timezones(query: &str) -> Result<Vec<String>, Error> {
+    let normalize = |str: &str| str.trim().to_lowercase().replace('_', " ");
+    let needle = normalize(query);
+    let mut names = TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.to_string())
+        .collect::<Vec<String>>();
+    names.sort();
+
+    if let Some(name) = names.iter().find(|name| normalize(name) == needle) {
+        return Ok(vec![name.to_owned()]);
+    }
+
+    let containing: Vec<String> = names
+        .iter()
+        .filter(|name| normalize(name).contains(&needle))
+        .cloned()
+        .collect();
+    if !containing.is_empty() {
+        return Ok(containing);
+    }
+
+    let in_order: Vec<String> = names
+        .into_iter()
+        .filter(|name| {
+            let normalized = normalize(name);
+            let mut chars = normalized.chars();
+            needle.chars().all(|n| chars.any(|c| c == n))
+        })
+        .collect();
+    if in_order.is_empty() {
+        Err(error::new(
+            "fuzzy_timezones",
+            &format!("No timezone matches '{query}'"),
+        ))
+    } else {
+        Ok(in_order)
+    }
+}
+
 /// For when we get offsets like GMT -7:00
 fn parse_gmt_to_timezone(gmt: &str) -> Result<Tz, Error> {
     let split: Vec<&str> = gmt.split_whitespace().collect();
@@ -90,7 +167,9 @@ fn parse_gmt_to_timezone(gmt: &str) -> Result<Tz, Error> {
         .ok_or_else(|| error::new("parse_timezone", "Could not get offset"))?;
     let offset = offset.replace(":00", "");
     let offset = offset.replace(':', "");
-    let offset_num = offset.parse::<i32>().unwrap();
+    let offset_num = offset
+        .parse::<i32>()
+        .map_err(|_| error::new("parse_timezone", "Could not parse offset"))?;
 
     let tz_string = format!(
         "Etc/GMT{}",
@@ -129,6 +208,211 @@ pub fn date_from_str(str: &str, timezone: Tz) -> Result<NaiveDate, Error> {
     Ok(date)
 }
 
+/// Best effort guess at the occurrence after the current due date of a recurring task.
+/// Understands daily, weekly, every N days or weeks, every other day or week, and weekdays.
+/// Returns None for anything else.
+pub fn next_occurrence(date_info: &DateInfo, config: &Config) -> Option<DateTime<Tz>> {
+    if !date_info.is_recurring {
+        return None;
+    }
+
+    let tz = timezone_from_str(&date_info.timezone.clone().or(config.timezone.clone())).ok()?;
+    let current = if date_info.date.len() == 10 {
+        date_from_str(&date_info.date, tz)
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+    } else {
+        datetime_from_str(&date_info.date, tz)
+            .ok()?
+            .with_timezone(&tz)
+            .naive_local()
+    };
+
+    let re = Regex::new(
+        r"^(?:every\s+(?:(other)\s+|(\d+)\s+)?(day|week|monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tue|wed|thu|fri|sat|sun)s?|(daily|weekly))\b",
+    )
+    .unwrap();
+    let string = date_info.string.to_lowercase();
+    let captures = re.captures(string.trim())?;
+
+    let interval: i64 = match (captures.get(1), captures.get(2)) {
+        (Some(_), _) => 2,
+        (None, Some(number)) => number.as_str().parse().ok()?,
+        (None, None) => 1,
+    };
+    let unit = captures
+        .get(3)
+        .or_else(|| captures.get(4))
+        .map(|m| m.as_str())?;
+
+    let days = match unit {
+        "day" | "daily" => interval,
+        "week" | "weekly" => interval * 7,
+        weekday => {
+            let weekday = weekday_from_str(weekday)?;
+            let current_day = current.weekday().num_days_from_monday() as i64;
+            let target_day = weekday.num_days_from_monday() as i64;
+            let days_ahead = match (target_day - current_day).rem_euclid(7) {
+                0 => 7,
+                days => days,
+            };
+            days_ahead + (interval - 1) * 7
+        }
+    };
+
+    (current + Duration::days(days))
+        .and_local_timezone(tz)
+        .single()
+}
+
+/// Parses a weekday and time such as "monday 3pm" or "fri 09:00" as the next such time in the config timezone.
+/// When the weekday is today and the time has passed, next week is used unless roll_past_weekday_times is false.
+pub fn parse_weekday_time(str: &str, config: &Config) -> Result<Option<DateTime<Tz>>, Error> {
+    let roll = config.roll_past_weekday_times.unwrap_or(true);
+    Ok(weekday_time_after(str, now(config)?, roll))
+}
+
+fn weekday_time_after(str: &str, now: DateTime<Tz>, roll: bool) -> Option<DateTime<Tz>> {
+    let re = Regex::new(
+        r"^(monday|tuesday|wednesday|thursday|friday|saturday|sunday|mon|tue|wed|thu|fri|sat|sun)\s+(\d{1,2})(?::(\d{2}))?\s*(am|pm)?$",
+    )
+    .unwrap();
+    let string = str.trim().to_lowercase();
+    let captures = re.captures(&string)?;
+
+    let weekday = weekday_from_str(captures.get(1)?.as_str())?;
+    let hour: u32 = captures.get(2)?.as_str().parse().ok()?;
+    let minute: u32 = match captures.get(3) {
+        Some(minute) => minute.as_str().parse().ok()?,
+        None => 0,
+    };
+    let hour = match captures.get(4).map(|m| m.as_str()) {
+        Some("am") if (1..=12).contains(&hour) => hour % 12,
+        Some("pm") if (1..=12).contains(&hour) => hour % 12 + 12,
+        Some(_) => return None,
+        // "monday 3" is too easy to misread, a bare hour needs minutes or am/pm
+        None if captures.get(3).is_none() => return None,
+        None => hour,
+    };
+    let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+
+    let current_day = now.weekday().num_days_from_monday() as i64;
+    let target_day = weekday.num_days_from_monday() as i64;
+    let days_ahead = match (target_day - current_day).rem_euclid(7) {
+        0 if roll && time <= now.time() => 7,
+        days => days,
+    };
+
+    (now.date_naive() + Duration::days(days_ahead))
+        .and_time(time)
+        .and_local_timezone(now.timezone())
+        .earliest()
+}
+
+/// Moves the start of a recurring due string, replacing any start it already has and keeping its end.
+/// i.e. "every 2 weeks starting 2024-05-01 until 2024-12-31" and "2024-06-01"
+/// become "every 2 weeks starting 2024-06-01 until 2024-12-31". Only English due strings are understood.
+pub fn recurrence_starting(recurrence: &str, due_string: &str) -> String {
+    let start = Regex::new(r"(?i)\s+(starting|from)\s+.*?(\s+(until|ending)\s+.*)?$").unwrap();
+    let end = Regex::new(r"(?i)\s+(until|ending)\s+.*$").unwrap();
+    let recurrence = recurrence.trim();
+
+    let (recurrence, until) = match start.captures(recurrence) {
+        Some(captures) => {
+            let whole = captures.get(0).unwrap();
+            let until = captures.get(2).map_or("", |until| until.as_str());
+            (&recurrence[..whole.start()], until)
+        }
+        None => match end.find(recurrence) {
+            Some(until) => (&recurrence[..until.start()], until.as_str()),
+            None => (recurrence, ""),
+        },
+    };
+    format!("{recurrence} starting {due_string}{until}")
+}
+
+/// True if the due string sets its own recurrence in English, i.e. "every monday" or "daily"
+pub fn is_recurring_string(due_string: &str) -> bool {
+    let re = Regex::new(r"(?i)^(every\s|daily\b|weekly\b|monthly\b|yearly\b)").unwrap();
+    re.is_match(due_string.trim())
+}
+
+/// Formats a datetime in UTC the way Todoist expects for due_datetime
+pub fn format_for_api(datetime: &DateTime<Tz>) -> String {
+    datetime
+        .with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+fn weekday_from_str(string: &str) -> Option<Weekday> {
+    match string {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parses a relative amount of time such as 1h, 2d, or 1w
+pub fn parse_duration(str: &str) -> Result<Duration, Error> {
+    let re = Regex::new(r"^(\d+)\s*([hdw])$").unwrap();
+    let invalid = || {
+        error::new(
+            "parse_duration",
+            &format!("'{str}' is not a valid duration, use something like 1h, 2d, or 1w"),
+        )
+    };
+    let captures = re.captures(str.trim()).ok_or_else(invalid)?;
+    let amount: i64 = captures[1].parse().map_err(|_| invalid())?;
+
+    let duration = match &captures[2] {
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    duration.ok_or_else(invalid)
+}
+
+/// Rewrites a date written in date_input_format from config as YYYY-MM-DD.
+/// Anything that doesn't match the format is returned unchanged.
+pub fn localized_date_to_iso(str: &str, config: &Config) -> String {
+    match &config.date_input_format {
+        Some(format) => match NaiveDate::parse_from_str(str.trim(), format) {
+            Ok(date) => date.format("%Y-%m-%d").to_string(),
+            Err(_) => str.to_string(),
+        },
+        None => str.to_string(),
+    }
+}
+
+/// Makes sure a date format only uses valid specifiers and includes a day, month, and year
+pub fn validate_date_format(format: &str) -> Result<(), Error> {
+    let invalid = || {
+        error::with_kind(
+            Kind::Config,
+            "date_input_format",
+            &format!("'{format}' is not a valid date format, use something like \"%d-%m-%Y\""),
+        )
+    };
+
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(invalid());
+    }
+
+    let sample = NaiveDate::from_ymd_opt(2021, 8, 13).ok_or_else(invalid)?;
+    let formatted = sample.format(format).to_string();
+    match NaiveDate::parse_from_str(&formatted, format) {
+        Ok(date) if date == sample => Ok(()),
+        _ => Err(invalid()),
+    }
+}
+
 /// Checks if string is a date in format YYYY-MM-DD
 pub fn is_date(string: &str) -> bool {
     let re = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
@@ -144,6 +428,168 @@ pub fn is_datetime(string: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1h"), Ok(Duration::hours(1)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::days(2)));
+        assert_eq!(parse_duration(" 1w "), Ok(Duration::weeks(1)));
+        assert_eq!(
+            parse_duration("soon"),
+            Err(error::new(
+                "parse_duration",
+                "'soon' is not a valid duration, use something like 1h, 2d, or 1w"
+            ))
+        );
+        assert_eq!(
+            parse_duration("99999999999999w"),
+            Err(error::new(
+                "parse_duration",
+                "'99999999999999w' is not a valid duration, use something like 1h, 2d, or 1w"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_localized_date_to_iso() {
+        let config = test::fixtures::config().await;
+        assert_eq!(localized_date_to_iso("13-08-2021", &config), "13-08-2021");
+
+        let config = Config {
+            date_input_format: Some(String::from("%d-%m-%Y")),
+            ..config
+        };
+        assert_eq!(localized_date_to_iso("13-08-2021", &config), "2021-08-13");
+        // Falls back to the existing parsing
+        assert_eq!(localized_date_to_iso("2021-08-13", &config), "2021-08-13");
+        assert_eq!(localized_date_to_iso("tomorrow", &config), "tomorrow");
+    }
+
+    #[tokio::test]
+    async fn format_datetime_drops_the_zone_on_other_days() {
+        let config = test::fixtures::config().await;
+        let tz = timezone_from_str(&config.timezone).unwrap();
+
+        let other_day = datetime_from_str("2021-09-06T16:00:00", tz).unwrap();
+        assert_eq!(
+            format_datetime(&other_day, &config),
+            Ok(String::from("2021-09-06 16:00"))
+        );
+
+        let today = now(&config).unwrap();
+        assert_eq!(
+            format_datetime(&today, &config),
+            Ok(today.format("%H:%M").to_string())
+        );
+    }
+
+    #[test]
+    fn recurrence_starting_replaces_the_start() {
+        assert_eq!(
+            recurrence_starting("every 2 weeks", "2024-06-01"),
+            "every 2 weeks starting 2024-06-01"
+        );
+        assert_eq!(
+            recurrence_starting("every 2 weeks starting 2024-05-01", "2024-06-01"),
+            "every 2 weeks starting 2024-06-01"
+        );
+        assert_eq!(
+            recurrence_starting("Every day From May 1", "tomorrow"),
+            "Every day starting tomorrow"
+        );
+        assert_eq!(
+            recurrence_starting(
+                "every day starting 2024-05-01 until 2024-12-31",
+                "2024-06-01"
+            ),
+            "every day starting 2024-06-01 until 2024-12-31"
+        );
+        assert_eq!(
+            recurrence_starting("every monday ending May 31", "2024-06-01"),
+            "every monday starting 2024-06-01 ending May 31"
+        );
+        assert!(is_recurring_string("Every monday"));
+        assert!(is_recurring_string("daily at 9am"));
+        assert!(!is_recurring_string("jeden Montag"));
+        assert!(!is_recurring_string("tomorrow"));
+    }
+
+    #[test]
+    fn format_relative_handles_future_and_past() {
+        assert_eq!(format_relative(Duration::minutes(25)), "in 25m");
+        assert_eq!(format_relative(Duration::minutes(90)), "in 1h 30m");
+        assert_eq!(format_relative(Duration::hours(-2)), "2h ago");
+        assert_eq!(format_relative(Duration::minutes(-5)), "5m ago");
+        assert_eq!(format_relative(Duration::seconds(30)), "now");
+    }
+
+    #[test]
+    fn test_weekday_time_after() {
+        let tz: Tz = "America/Vancouver".parse().unwrap();
+        // A Wednesday afternoon
+        let now = NaiveDate::from_ymd_opt(2024, 3, 13)
+            .unwrap()
+            .and_hms_opt(14, 0, 0)
+            .unwrap()
+            .and_local_timezone(tz)
+            .unwrap();
+        let at = |date: &str| datetime_from_str(date, tz).unwrap();
+
+        assert_eq!(
+            weekday_time_after("monday 3pm", now, true),
+            Some(at("2024-03-18T15:00:00"))
+        );
+        assert_eq!(
+            weekday_time_after("Fri 09:00", now, true),
+            Some(at("2024-03-15T09:00:00"))
+        );
+        assert_eq!(
+            weekday_time_after("thu 12am", now, true),
+            Some(at("2024-03-14T00:00:00"))
+        );
+        assert_eq!(
+            weekday_time_after("wednesday 4:30pm", now, true),
+            Some(at("2024-03-13T16:30:00"))
+        );
+        assert_eq!(weekday_time_after("monday 3", now, true), None);
+        assert_eq!(weekday_time_after("monday 13pm", now, true), None);
+        assert_eq!(weekday_time_after("next monday", now, true), None);
+    }
+
+    #[test]
+    fn test_weekday_time_after_rollover() {
+        let tz: Tz = "America/Vancouver".parse().unwrap();
+        let now = NaiveDate::from_ymd_opt(2024, 3, 13)
+            .unwrap()
+            .and_hms_opt(14, 0, 0)
+            .unwrap()
+            .and_local_timezone(tz)
+            .unwrap();
+        let at = |date: &str| datetime_from_str(date, tz).unwrap();
+
+        // Wednesday 9am has already passed today
+        assert_eq!(
+            weekday_time_after("wed 9am", now, true),
+            Some(at("2024-03-20T09:00:00"))
+        );
+        assert_eq!(
+            weekday_time_after("wed 9am", now, false),
+            Some(at("2024-03-13T09:00:00"))
+        );
+        assert_eq!(
+            format_for_api(&at("2024-03-20T09:00:00")),
+            "2024-03-20T16:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_validate_date_format() {
+        assert_eq!(validate_date_format("%d-%m-%Y"), Ok(()));
+        assert_eq!(validate_date_format("%d/%m/%Y"), Ok(()));
+        assert!(validate_date_format("%d-%m").is_err());
+        assert!(validate_date_format("%Q-%m-%Y").is_err());
+    }
 
     #[test]
     fn test_is_date() {
@@ -161,6 +607,93 @@ mod tests {
         assert!(!is_datetime("today"));
     }
 
+    #[tokio::test]
+    async fn date_for_datetime_respects_day_start_hour() {
+        let config = test::fixtures::config().await;
+        let before = datetime_from_str("2024-05-02T01:59:00", Tz::US__Pacific).unwrap();
+        let after = datetime_from_str("2024-05-02T02:00:00", Tz::US__Pacific).unwrap();
+        let may_first = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let may_second = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        assert_eq!(date_for_datetime(before, &config), may_second);
+        assert_eq!(date_for_datetime(after, &config), may_second);
+
+        let config = Config {
+            day_start_hour: Some(2),
+            ..config
+        };
+
+        assert_eq!(date_for_datetime(before, &config), may_first);
+        assert_eq!(date_for_datetime(after, &config), may_second);
+    }
+
+    #[tokio::test]
+    async fn test_next_occurrence() {
+        let config = test::fixtures::config().await;
+        // 2024-05-01 is a Wednesday
+        let next = |date: &str, string: &str| {
+            let date_info = DateInfo {
+                date: String::from(date),
+                is_recurring: true,
+                string: String::from(string),
+                timezone: None,
+            };
+            next_occurrence(&date_info, &config).map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+        };
+
+        assert_eq!(
+            next("2024-05-01", "every day"),
+            Some(String::from("2024-05-02 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "daily"),
+            Some(String::from("2024-05-02 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "every other day"),
+            Some(String::from("2024-05-03 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "every 3 days"),
+            Some(String::from("2024-05-04 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "Every week"),
+            Some(String::from("2024-05-08 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "weekly"),
+            Some(String::from("2024-05-08 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "every 2 weeks"),
+            Some(String::from("2024-05-15 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "every mon"),
+            Some(String::from("2024-05-06 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-01", "every wednesday"),
+            Some(String::from("2024-05-08 00:00"))
+        );
+        assert_eq!(
+            next("2024-05-06T16:30:00", "every other mon at 16:30"),
+            Some(String::from("2024-05-20 16:30"))
+        );
+        assert_eq!(next("2024-05-01", "every month"), None);
+        assert_eq!(next("2024-05-01", "every weekday"), None);
+        assert_eq!(next("2024-05-01", "every last day of the month"), None);
+
+        let not_recurring = DateInfo {
+            date: String::from("2024-05-01"),
+            is_recurring: false,
+            string: String::from("every day"),
+            timezone: None,
+        };
+        assert_eq!(next_occurrence(&not_recurring, &config), None);
+    }
+
     #[test]
     fn test_timezone_from_string() {
         assert_eq!(
@@ -172,5 +705,45 @@ mod tests {
             timezone_from_str(&Some("GMT -7:00".to_string())),
             Ok(Tz::Etc__GMTPlus7),
         );
+
+        assert_eq!(
+            timezone_from_str(&Some("US/Pacfic".to_string())),
+            Err(error::new(
+                "timezone_from_str",
+                "'US/Pacfic' is not a valid timezone"
+            )),
+        );
+
+        assert_eq!(
+            timezone_from_str(&Some("GMT seven".to_string())),
+            Err(error::new(
+                "timezone_from_str",
+                "'GMT seven' is not a valid timezone"
+            )),
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_timezones() {
+        assert_eq!(
+            fuzzy_timezones("us/pacific"),
+            Ok(vec![String::from("US/Pacific")])
+        );
+        assert_eq!(
+            fuzzy_timezones("los angeles"),
+            Ok(vec![String::from("America/Los_Angeles")])
+        );
+
+        let pacific = fuzzy_timezones("pacific").unwrap();
+        assert!(pacific.contains(&String::from("US/Pacific")));
+        assert!(pacific.contains(&String::from("Canada/Pacific")));
+
+        let typo = fuzzy_timezones("US/Pacfic").unwrap();
+        assert!(typo.contains(&String::from("US/Pacific")));
+
+        assert_eq!(
+            fuzzy_timezones("xyzzy"),
+            Err(error::new("fuzzy_timezones", "No timezone matches 'xyzzy'"))
+        );
     }
 }
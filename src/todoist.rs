@@ -1,34 +1,74 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
 use serde_json::{json, Number, Value};
 
 mod request;
 
+use crate::collaborators::{self, Collaborator};
 use crate::config::Config;
-use crate::error::Error;
+use crate::error::{self, Error, Kind};
+use crate::labels::{self, Label};
+use crate::projects::palette::ProjectColor;
 use crate::projects::Project;
+use crate::queue::{self, QueuedOp};
 use crate::sections::Section;
 use crate::tasks::priority::Priority;
-use crate::tasks::Task;
-use crate::{projects, sections, tasks, time};
+use crate::tasks::{CompletedTask, Task};
+use crate::{color, projects, sections, tasks, time};
 
 // TODOIST URLS
 const QUICK_ADD_URL: &str = "/sync/v9/quick/add";
 const PROJECT_DATA_URL: &str = "/sync/v9/projects/get_data";
 const SYNC_URL: &str = "/sync/v9/sync";
+const COMPLETED_URL: &str = "/sync/v9/completed/get_all";
 pub const REST_V2_TASKS_URL: &str = "/rest/v2/tasks/";
 const SECTIONS_URL: &str = "/rest/v2/sections";
 const PROJECTS_URL: &str = "/rest/v2/projects";
+const LABELS_URL: &str = "/rest/v2/labels";
+
+/// The parts of a sync API response that we care about
+#[derive(Deserialize)]
+struct SyncResponse {
+    temp_id_mapping: HashMap<String, String>,
+}
+
+/// The user resource from the sync API
+#[derive(Deserialize)]
+struct UserResponse {
+    user: User,
+}
+
+#[derive(Deserialize)]
+struct User {
+    tz_info: TimezoneInfo,
+}
+
+#[derive(Deserialize)]
+struct TimezoneInfo {
+    timezone: String,
+}
 
 /// Add a new task to the inbox with natural language support
 pub async fn quick_add_task(config: &Config, content: &str) -> Result<Task, Error> {
     let url = String::from(QUICK_ADD_URL);
-    let body = json!({"text": content, "auto_reminder": true});
+    let body = json!({"text": content, "auto_reminder": true, "due_lang": config.due_lang()});
 
     let json = request::post_todoist_sync(config, url, body, true).await?;
     tasks::json_to_task(json)
 }
 
+/// Add a new task to the inbox without natural language processing, so that characters
+/// such as @ and # stay in the content rather than becoming labels and projects
+pub async fn quick_add_literal_task(config: &Config, content: &str) -> Result<Task, Error> {
+    let body = json!({"content": content, "auto_reminder": true});
+
+    post_new_task(config, body).await
+}
+
 pub async fn get_task(config: &Config, id: &str) -> Result<Task, Error> {
     let url = format!("{REST_V2_TASKS_URL}{id}");
     let json = request::get_todoist_rest(config, url).await?;
@@ -45,9 +85,9 @@ pub async fn add_task(
     priority: Priority,
     description: &String,
     due: &Option<String>,
+    deadline: &Option<NaiveDate>,
     labels: &[String],
 ) -> Result<Task, Error> {
-    let url = String::from(REST_V2_TASKS_URL);
     let mut body: HashMap<String, Value> = HashMap::new();
     body.insert("content".to_owned(), Value::String(content.to_owned()));
     body.insert(
@@ -65,20 +105,76 @@ pub async fn add_task(
     body.insert("labels".to_owned(), Value::Array(labels));
 
     if let Some(date) = due {
-        if time::is_date(date) || time::is_datetime(date) {
+        let date = &time::localized_date_to_iso(date, config);
+        if let Some(datetime) = time::parse_weekday_time(date, config)? {
+            body.insert(
+                "due_datetime".to_owned(),
+                Value::String(time::format_for_api(&datetime)),
+            );
+        } else if time::is_date(date) || time::is_datetime(date) {
             body.insert("due_date".to_owned(), Value::String(date.to_owned()));
         } else {
             body.insert("due_string".to_owned(), Value::String(date.to_owned()));
+            body.insert("due_lang".to_owned(), Value::String(config.due_lang()));
         }
     }
 
+    // Todoist keeps the deadline apart from the due date, it is never parsed as natural language
+    if let Some(deadline) = deadline {
+        body.insert(
+            "deadline_date".to_owned(),
+            Value::String(deadline.format("%Y-%m-%d").to_string()),
+        );
+    }
+
     if let Some(section) = section {
         body.insert("section_id".to_owned(), Value::String(section.id.clone()));
     }
 
-    let body = json!(body);
+    post_new_task(config, json!(body)).await
+}
+
+/// Add a task to the same project and section as another task
+pub async fn add_followup_task(
+    config: &Config,
+    content: &str,
+    previous: &Task,
+) -> Result<Task, Error> {
+    let mut body = json!({
+        "content": content,
+        "project_id": previous.project_id,
+        "auto_reminder": true
+    });
+    if let Some(section_id) = &previous.section_id {
+        body["section_id"] = json!(section_id);
+    }
+
+    post_new_task(config, body).await
+}
 
-    let json = request::post_todoist_rest(config, url, body, true).await?;
+/// Create a task via the REST API, queueing it for `tod sync` if Todoist can't be reached
+/// The request ID is queued too, so a create that reached Todoist before timing out is not duplicated
+async fn post_new_task(config: &Config, body: Value) -> Result<Task, Error> {
+    let url = String::from(REST_V2_TASKS_URL);
+    let request_id = request::new_uuid();
+    let json = match request::post_todoist_rest_with_id(
+        config,
+        url,
+        body.clone(),
+        true,
+        &request_id,
+    )
+    .await
+    {
+        Err(e) if e.is_network() => {
+            let op = QueuedOp::Create {
+                body,
+                request_id: Some(request_id),
+            };
+            Err(queue::enqueue(config, op).await)
+        }
+        result => result,
+    }?;
     tasks::json_to_task(json)
 }
 
@@ -87,7 +183,7 @@ pub async fn tasks_for_project(config: &Config, project: &Project) -> Result<Vec
     let url = String::from(PROJECT_DATA_URL);
     let body = json!({ "project_id": project.id });
     let json = request::post_todoist_sync(config, url, body, true).await?;
-    tasks::sync_json_to_tasks(json)
+    tasks::sync_json_to_tasks(json).map(tasks::filter_active)
 }
 
 pub async fn tasks_for_filter(config: &Config, filter: &str) -> Result<Vec<Task>, Error> {
@@ -96,7 +192,37 @@ pub async fn tasks_for_filter(config: &Config, filter: &str) -> Result<Vec<Task>
     let encoded = encode(filter);
     let url = format!("{REST_V2_TASKS_URL}?filter={encoded}");
     let json = request::get_todoist_rest(config, url).await?;
-    tasks::rest_json_to_tasks(json)
+    tasks::rest_json_to_tasks(json).map(tasks::filter_active)
+}
+
+/// Open tasks nested under the task at any depth
+pub async fn open_subtasks(config: &Config, task: &Task) -> Result<Vec<Task>, Error> {
+    let url = format!("{REST_V2_TASKS_URL}?project_id={}", task.project_id);
+    let json = request::get_todoist_rest(config, url).await?;
+    let project_tasks = tasks::rest_json_to_tasks(json)?;
+    Ok(tasks::descendants(&project_tasks, &task.id))
+}
+
+/// People a shared project has been shared with, empty for projects that aren't shared
+pub async fn collaborators_for_project(
+    config: &Config,
+    project_id: &str,
+) -> Result<Vec<Collaborator>, Error> {
+    let url = format!("{PROJECTS_URL}/{project_id}/collaborators");
+    let json = request::get_todoist_rest(config, url).await?;
+    collaborators::json_to_collaborators(json)
+}
+
+/// Assign a task to a collaborator in its project
+pub async fn assign_task(
+    config: &Config,
+    task: &Task,
+    collaborator: &Collaborator,
+) -> Result<String, Error> {
+    let body = json!({ "assignee_id": collaborator.id });
+    post_task_update(config, &task.id, body, true).await?;
+    // Does not pass back an task
+    Ok(String::from("✓"))
 }
 
 pub async fn sections_for_project(
@@ -109,11 +235,108 @@ pub async fn sections_for_project(
     sections::json_to_sections(json)
 }
 
+/// The timezone set in Todoist, fetching it also checks that the token works
+pub async fn user_timezone(config: &Config) -> Result<String, Error> {
+    let body = json!({"sync_token": "*", "resource_types": ["user"]});
+    let json = request::post_todoist_sync(config, SYNC_URL.to_string(), body, true).await?;
+    let response: UserResponse = serde_json::from_str(&json)?;
+    Ok(response.user.tz_info.timezone)
+}
+
+pub async fn labels(config: &Config) -> Result<Vec<Label>, Error> {
+    let json = request::get_todoist_rest(config, LABELS_URL.to_string()).await?;
+    labels::json_to_labels(json)
+}
+
+/// Renames a personal label, Todoist updates the tasks that have it
+pub async fn rename_label(config: &Config, label: &Label, name: &str) -> Result<String, Error> {
+    let url = format!("{LABELS_URL}/{}", label.id);
+    let body = json!({ "name": name });
+    request::post_todoist_rest(config, url, body, true).await?;
+    Ok(String::from("✓"))
+}
+
 pub async fn projects(config: &Config) -> Result<Vec<Project>, Error> {
     let json = request::get_todoist_rest(config, PROJECTS_URL.to_string()).await?;
     projects::json_to_projects(json)
 }
 
+/// Checks the token and connection with a lightweight request, reporting how long it took
+pub async fn ping(config: &Config) -> Result<String, Error> {
+    let start = Instant::now();
+    match request::get_todoist_rest(config, PROJECTS_URL.to_string()).await {
+        Ok(_) => Ok(format!(
+            "{} Todoist responded in {}ms",
+            color::success_string("✓", &config.colors),
+            start.elapsed().as_millis()
+        )),
+        Err(Error {
+            kind: Kind::Api {
+                status: status @ (401 | 403),
+            },
+            ..
+        }) => Err(error::with_kind(
+            Kind::Api { status },
+            "ping",
+            "Todoist rejected the token, update it in config or run tod setup",
+        )),
+        Err(e) if e.is_network() => Err(error::with_kind(
+            Kind::Network,
+            "ping",
+            "Could not reach Todoist, check your connection",
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn get_project(config: &Config, id: &str) -> Result<Project, Error> {
+    let url = format!("{PROJECTS_URL}/{id}");
+    let json = request::get_todoist_rest(config, url).await?;
+    projects::json_to_project(json)
+}
+
+/// Create a new project in Todoist
+pub async fn add_project(
+    config: &Config,
+    name: &str,
+    color: &Option<ProjectColor>,
+    parent: &Option<Project>,
+) -> Result<Project, Error> {
+    let temp_id = request::new_uuid();
+    let mut args = json!({ "name": name });
+    if let Some(color) = color {
+        args["color"] = json!(color.to_string());
+    }
+    if let Some(parent) = parent {
+        args["parent_id"] = json!(parent.id);
+    }
+    let body = json!({"commands": [{"type": "project_add", "uuid": request::new_uuid(), "temp_id": temp_id, "args": args}]});
+    let url = String::from(SYNC_URL);
+
+    let json = request::post_todoist_sync(config, url, body, true).await?;
+    let response: SyncResponse = serde_json::from_str(&json)?;
+    let id = response.temp_id_mapping.get(&temp_id).ok_or_else(|| {
+        error::new(
+            "add_project",
+            "Todoist did not return an ID for the new project",
+        )
+    })?;
+
+    get_project(config, id).await
+}
+
+/// Change the color of a project in Todoist
+pub async fn update_project_color(
+    config: &Config,
+    project: &Project,
+    color: &ProjectColor,
+) -> Result<String, Error> {
+    let url = format!("{PROJECTS_URL}/{}", project.id);
+    let body = json!({ "color": color.to_string() });
+    request::post_todoist_rest(config, url, body, true).await?;
+    Ok(String::from("✓"))
+}
+
 /// Move an task to a different project
 pub async fn move_task_to_project(
     config: &Config,
@@ -128,6 +351,27 @@ pub async fn move_task_to_project(
     Ok(String::from("✓"))
 }
 
+/// Fetches the task again and returns an error when it isn't in the project. Todoist can accept a
+/// move and leave the task where it was, i.e. when the section doesn't exist.
+pub async fn verify_task_project(
+    config: &Config,
+    task_id: &str,
+    project_id: &str,
+) -> Result<(), Error> {
+    let task = get_task(config, task_id).await?;
+    if task.project_id == project_id {
+        Ok(())
+    } else {
+        Err(error::new(
+            "verify",
+            &format!(
+                "Task '{}' is in project {} instead of {project_id}",
+                task.content, task.project_id
+            ),
+        ))
+    }
+}
+
 pub async fn move_task_to_section(
     config: &Config,
     task: Task,
@@ -148,9 +392,7 @@ pub async fn update_task_priority(
     priority: Priority,
 ) -> Result<String, Error> {
     let body = json!({ "priority": priority });
-    let url = format!("{}{}", REST_V2_TASKS_URL, task.id);
-
-    request::post_todoist_rest(config, url, body, true).await?;
+    post_task_update(config, &task.id, body, true).await?;
     // Does not pass back an task
     Ok(String::from("✓"))
 }
@@ -160,9 +402,7 @@ pub async fn add_task_label(config: &Config, task: Task, label: String) -> Resul
     let mut labels = task.labels;
     labels.push(label);
     let body = json!({ "labels": labels});
-    let url = format!("{}{}", REST_V2_TASKS_URL, task.id);
-
-    request::post_todoist_rest(config, url, body, true).await?;
+    post_task_update(config, &task.id, body, true).await?;
     // Does not pass back an task
     Ok(String::from("✓"))
 }
@@ -174,15 +414,38 @@ pub async fn update_task_due(
     due_string: String,
     spinner: bool,
 ) -> Result<String, Error> {
-    let due_string = if task.is_recurring() {
-        format!("{} starting {due_string}", task.due.unwrap().string)
-    } else {
-        due_string
+    let due_string = time::localized_date_to_iso(&due_string, config);
+    // A bare date would replace the recurrence, so it only moves the start instead.
+    // A new recurrence or "no date" is sent as is.
+    let keeps_recurrence =
+        !time::is_recurring_string(&due_string) && !due_string.eq_ignore_ascii_case("no date");
+    let body = match (time::parse_weekday_time(&due_string, config)?, &task.due) {
+        (Some(datetime), _) if !task.is_recurring() => {
+            json!({ "due_datetime": time::format_for_api(&datetime) })
+        }
+        (_, Some(due)) if due.is_recurring && keeps_recurrence => {
+            // The start can only be moved in the English that recurrence_starting reads
+            let english = config.due_lang() == "en"
+                || time::is_date(&due_string)
+                || time::is_datetime(&due_string);
+            if !time::is_recurring_string(&due.string) || !english {
+                return Err(error::with_kind(
+                    Kind::Input,
+                    "update_task_due",
+                    &format!(
+                        "'{}' repeats '{}', give the whole recurrence to reschedule it so that it is not lost",
+                        task.content, due.string
+                    ),
+                ));
+            }
+            json!({
+                "due_string": time::recurrence_starting(&due.string, &due_string),
+                "due_lang": "en"
+            })
+        }
+        _ => json!({ "due_string": due_string }),
     };
-    let body = json!({ "due_string": due_string });
-    let url = format!("{}{}", REST_V2_TASKS_URL, task.id);
-
-    request::post_todoist_rest(config, url, body, spinner).await?;
+    post_task_update(config, &task.id, body, spinner).await?;
     // Does not pass back an task
     Ok(String::from("✓"))
 }
@@ -194,28 +457,133 @@ pub async fn update_task_name(
     new_name: String,
 ) -> Result<String, Error> {
     let body = json!({ "content": new_name });
-    let url = format!("{}{}", REST_V2_TASKS_URL, task.id);
+    post_task_update(config, &task.id, body, true).await?;
+    // Does not pass back a task
+    Ok(String::from("✓"))
+}
 
-    request::post_todoist_rest(config, url, body, true).await?;
+pub async fn update_task_description(
+    config: &Config,
+    task: &Task,
+    description: &str,
+) -> Result<String, Error> {
+    let body = json!({ "description": description });
+    post_task_update(config, &task.id, body, true).await?;
     // Does not pass back a task
     Ok(String::from("✓"))
 }
 
+#[derive(Deserialize)]
+struct CompletedResponse {
+    items: Vec<CompletedTask>,
+}
+
+/// Tasks completed between two times, optionally only for one project
+pub async fn completed_tasks(
+    config: &Config,
+    project: &Option<Project>,
+    since: DateTime<Tz>,
+    until: DateTime<Tz>,
+) -> Result<Vec<CompletedTask>, Error> {
+    let format = "%Y-%m-%dT%H:%M:%S";
+    let mut body = json!({
+        "since": since.with_timezone(&Utc).format(format).to_string(),
+        "until": until.with_timezone(&Utc).format(format).to_string(),
+        "limit": 200,
+        "annotate_items": true
+    });
+    if let Some(project) = project {
+        body["project_id"] = json!(project.id);
+    }
+    let url = String::from(COMPLETED_URL);
+
+    let json = request::post_todoist_sync(config, url, body, true).await?;
+    let response: CompletedResponse = serde_json::from_str(&json)?;
+    Ok(response.items)
+}
+
 /// Complete the last task returned by "next task"
 pub async fn complete_task(config: &Config, task_id: &str, spinner: bool) -> Result<String, Error> {
-    let body = json!({"commands": [{"type": "item_close", "uuid": request::new_uuid(), "temp_id": request::new_uuid(), "args": {"id": task_id}}]});
-    let url = String::from(SYNC_URL);
-
-    request::post_todoist_sync(config, url, body, spinner).await?;
+    match send_complete_task(config, task_id, spinner).await {
+        Err(e) if e.is_network() => {
+            let id = task_id.to_string();
+            Err(queue::enqueue(config, QueuedOp::Complete { id }).await)
+        }
+        result => result,
+    }?;
 
     if !cfg!(test) {
-        config.clone().clear_next_id().save().await?;
+        config
+            .clone()
+            .clear_next_id()
+            .set_last_completed_id(task_id)
+            .save()
+            .await?;
     }
 
     // Does not pass back a task
     Ok(String::from("✓"))
 }
 
+/// Reopen a completed task
+pub async fn uncomplete_task(
+    config: &Config,
+    task_id: &str,
+    spinner: bool,
+) -> Result<String, Error> {
+    let body = json!({"commands": [{"type": "item_uncomplete", "uuid": request::new_uuid(), "args": {"id": task_id}}]});
+    let url = String::from(SYNC_URL);
+
+    request::post_todoist_sync(config, url, body, spinner).await?;
+
+    // Does not pass back a task
+    Ok(String::from("✓"))
+}
+
+async fn send_complete_task(
+    config: &Config,
+    task_id: &str,
+    spinner: bool,
+) -> Result<String, Error> {
+    let body = json!({"commands": [{"type": "item_close", "uuid": request::new_uuid(), "temp_id": request::new_uuid(), "args": {"id": task_id}}]});
+    let url = String::from(SYNC_URL);
+
+    request::post_todoist_sync(config, url, body, spinner).await
+}
+
+/// Send an update for a task, queueing it for `tod sync` if Todoist can't be reached
+async fn post_task_update(
+    config: &Config,
+    id: &str,
+    body: Value,
+    spinner: bool,
+) -> Result<String, Error> {
+    let url = format!("{REST_V2_TASKS_URL}{id}");
+    match request::post_todoist_rest(config, url, body.clone(), spinner).await {
+        Err(e) if e.is_network() => {
+            let id = id.to_string();
+            Err(queue::enqueue(config, QueuedOp::Update { id, body }).await)
+        }
+        result => result,
+    }
+}
+
+/// Send a change that was queued while Todoist could not be reached
+pub async fn send_queued_op(config: &Config, op: &QueuedOp) -> Result<String, Error> {
+    match op {
+        QueuedOp::Create { body, request_id } => {
+            let url = String::from(REST_V2_TASKS_URL);
+            let request_id = request_id.clone().unwrap_or_else(request::new_uuid);
+            request::post_todoist_rest_with_id(config, url, body.clone(), true, &request_id).await
+        }
+        QueuedOp::Complete { id } => send_complete_task(config, id, true).await,
+        QueuedOp::Update { id, body } => {
+            let url = format!("{REST_V2_TASKS_URL}{id}");
+            request::post_todoist_rest(config, url, body.clone(), true).await
+        }
+    }
+}
+
 pub async fn delete_task(config: &Config, task: &Task, spinner: bool) -> Result<String, Error> {
     let body = json!({});
     let url = format!("{}{}", REST_V2_TASKS_URL, task.id);
@@ -228,6 +596,7 @@ pub async fn delete_task(config: &Config, task: &Task, spinner: bool) -> Result<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Args;
     use crate::tasks::priority::{self, Priority};
     use crate::tasks::{DateInfo, Task};
     use crate::{test, time};
@@ -261,11 +630,181 @@ mod tests {
                 due: None,
                 is_deleted: Some(false),
                 is_completed: None,
+                added_at: None,
+                section_id: None,
+                assignee_id: None,
             })
         );
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn ping_reports_latency_or_the_kind_of_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let ok_mock = server
+            .mock("GET", "/rest/v2/projects")
+            .match_header("authorization", "Bearer alreadycreated")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::projects())
+            .create_async()
+            .await;
+        let rejected_mock = server
+            .mock("GET", "/rest/v2/projects")
+            .match_header("authorization", "Bearer wrong")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let response = ping(&config).await.unwrap();
+        assert!(response.starts_with("✓ Todoist responded in "));
+        assert!(response.ends_with("ms"));
+        ok_mock.assert();
+
+        let rejected = Config {
+            token: String::from("wrong"),
+            ..config.clone()
+        };
+        assert_eq!(
+            ping(&rejected).await,
+            Err(error::with_kind(
+                Kind::Api { status: 401 },
+                "ping",
+                "Todoist rejected the token, update it in config or run tod setup"
+            ))
+        );
+        rejected_mock.assert();
+
+        let offline = config.mock_url(String::from("http://127.0.0.1:1"));
+        assert_eq!(
+            ping(&offline).await,
+            Err(error::with_kind(
+                Kind::Network,
+                "ping",
+                "Could not reach Todoist, check your connection"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn quick_add_sends_language() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/quick/add")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"text": "Brot kaufen morgen", "due_lang": "de"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+        let english_mock = server
+            .mock("POST", "/sync/v9/quick/add")
+            .match_body(mockito::Matcher::PartialJson(json!({"due_lang": "en"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let german = Config {
+            language: Some(String::from("de")),
+            ..config.clone()
+        };
+
+        assert!(quick_add_task(&german, "Brot kaufen morgen").await.is_ok());
+        assert!(quick_add_task(&config, "Buy bread tomorrow").await.is_ok());
+        mock.assert();
+        english_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn verbose_should_not_change_result() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/quick/add")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let verbose_config = Config {
+            args: Args {
+                verbose: true,
+                ..config.args.clone()
+            },
+            ..config.clone()
+        };
+
+        assert_eq!(
+            quick_add_task(&config, "testy test").await,
+            quick_add_task(&verbose_config, "testy test").await
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_quick_add_literal_task() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"content": "Email @bob about #budget !!1"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let result = quick_add_literal_task(&config, "Email @bob about #budget !!1").await;
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_add_task_with_deadline() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/tasks/")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "deadline_date": "2024-06-01",
+                "due_string": "tomorrow"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let project = test::fixtures::project();
+        let deadline = NaiveDate::from_ymd_opt(2024, 6, 1);
+
+        let task = add_task(
+            &config,
+            &String::from("New task"),
+            &project,
+            None,
+            Priority::None,
+            &String::new(),
+            &Some(String::from("tomorrow")),
+            &deadline,
+            &[],
+        )
+        .await;
+        assert!(task.is_ok());
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_add_task() {
         let mut server = mockito::Server::new_async().await;
@@ -292,6 +831,7 @@ mod tests {
                 priority,
                 &String::new(),
                 &None,
+                &None,
                 &[]
             )
             .await,
@@ -308,6 +848,9 @@ mod tests {
                 due: None,
                 is_deleted: Some(false),
                 is_completed: None,
+                added_at: None,
+                section_id: None,
+                assignee_id: None,
             })
         );
         mock.assert();
@@ -356,12 +899,70 @@ mod tests {
                 priority: Priority::Medium,
                 is_deleted: Some(false),
                 is_completed: None,
+                added_at: None,
+                section_id: Some(String::from("1234")),
+                assignee_id: None,
             }])
         );
 
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn should_add_a_project() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/sync/v9/sync")
+            .match_body(mockito::Matcher::Regex(String::from(
+                r#""type":"project_add""#,
+            )))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::project_add())
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("GET", "/rest/v2/projects/999")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::project())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let project = add_project(&config, "Doomsday", &None, &None)
+            .await
+            .unwrap();
+        assert_eq!(project.id, String::from("999"));
+        assert_eq!(project.name, String::from("Doomsday"));
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn should_update_a_project_color() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/projects/456")
+            .match_body(mockito::Matcher::Json(json!({"color": "berry_red"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::project())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let color: ProjectColor = "berry_red".parse().unwrap();
+
+        assert_eq!(
+            update_project_color(&config, &test::fixtures::project(), &color).await,
+            Ok(String::from("✓"))
+        );
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn should_complete_a_task() {
         let mut server = mockito::Server::new_async().await;
@@ -408,6 +1009,51 @@ mod tests {
         assert_eq!(response, Ok(String::from("✓")));
     }
 
+    #[tokio::test]
+    async fn verify_catches_move_that_did_not_happen() {
+        let mut server = mockito::Server::new_async().await;
+        let move_mock = server
+            .mock("POST", "/sync/v9/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+        // Todoist still has the task in its old project
+        let get_mock = server
+            .mock("GET", "/rest/v2/tasks/5149481867")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::task())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+        let task = Task {
+            id: String::from("5149481867"),
+            ..test::fixtures::task()
+        };
+        let project = test::fixtures::project();
+
+        let response = move_task_to_project(&config, task.clone(), &project, false).await;
+        assert_eq!(response, Ok(String::from("✓")));
+
+        assert_eq!(
+            verify_task_project(&config, &task.id, &project.id).await,
+            Err(error::new(
+                "verify",
+                "Task 'testy test' is in project 5555555 instead of 456"
+            ))
+        );
+        assert_eq!(
+            verify_task_project(&config, &task.id, "5555555").await,
+            Ok(())
+        );
+        move_mock.assert();
+        get_mock.assert();
+    }
+
     #[tokio::test]
     async fn should_prioritize_a_task() {
         let task = test::fixtures::task();
@@ -449,4 +1095,66 @@ mod tests {
         mock.assert();
         assert_eq!(response, Ok(String::from("✓")));
     }
+
+    #[tokio::test]
+    async fn should_keep_recurrence_when_moving_a_recurring_task() {
+        let task = Task {
+            due: Some(DateInfo {
+                date: String::from("2024-05-01"),
+                is_recurring: true,
+                timezone: None,
+                string: String::from("every 2 weeks starting 2024-05-01"),
+            }),
+            ..test::fixtures::task()
+        };
+        let url: &str = &format!("{}{}", "/rest/v2/tasks/", task.id);
+        let mut server = mockito::Server::new_async().await;
+
+        let mock = server
+            .mock("POST", url)
+            .match_body(mockito::Matcher::Json(json!({
+                "due_string": "every 2 weeks starting 2024-06-01",
+                "due_lang": "en"
+            })))
+            .with_status(204)
+            .with_header("content-type", "application/json")
+            .with_body(test::responses::sync())
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let response = update_task_due(&config, task, "2024-06-01".to_string(), true).await;
+        mock.assert();
+        assert_eq!(response, Ok(String::from("✓")));
+    }
+
+    #[tokio::test]
+    async fn update_task_due_does_not_drop_recurrence_it_cannot_read() {
+        let task = Task {
+            content: String::from("Müll rausbringen"),
+            due: Some(DateInfo {
+                date: String::from("2024-05-01"),
+                is_recurring: true,
+                timezone: None,
+                string: String::from("jeden Montag"),
+            }),
+            ..test::fixtures::task()
+        };
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rest/v2/tasks/222")
+            .expect(0)
+            .create_async()
+            .await;
+        let config = Config {
+            language: Some(String::from("de")),
+            ..test::fixtures::config().await
+        }
+        .mock_url(server.url());
+
+        let response = update_task_due(&config, task, "2024-06-01".to_string(), true).await;
+        mock.assert();
+        assert_eq!(response.map_err(|e| e.kind), Err(Kind::Input));
+    }
 }
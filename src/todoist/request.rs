@@ -1,13 +1,17 @@
 use std::env;
+use std::io::IsTerminal;
 use std::time::Duration;
+use std::time::Instant;
 
 use reqwest::header::AUTHORIZATION;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::Client;
 use reqwest::Response;
+use reqwest::StatusCode;
 use serde_json::json;
 use spinners::Spinner;
 use spinners::Spinners;
+use spinners::Stream;
 use uuid::Uuid;
 
 use crate::config::Args;
@@ -15,6 +19,7 @@ use crate::config::Config;
 use crate::debug;
 use crate::error;
 use crate::error::Error;
+use crate::error::Kind;
 
 const FAKE_UUID: &str = "42963283-2bab-4b1f-bad2-278ef2b6ba2c";
 const TODOIST_URL: &str = "https://api.todoist.com";
@@ -36,6 +41,7 @@ pub async fn post_todoist_sync(
 
     let spinner = maybe_start_spinner(config, spinner);
     debug::print(config, format!("POST {request_url}\nbody: {body}"));
+    let start = Instant::now();
     let response = Client::new()
         .post(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
@@ -46,7 +52,7 @@ pub async fn post_todoist_sync(
         .await?;
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "POST", url, body).await
+    handle_response(config, response, "POST", url, body, start).await
 }
 
 /// Post to Todoist via REST api
@@ -56,6 +62,18 @@ pub async fn post_todoist_rest(
     url: String,
     body: serde_json::Value,
     spinner: bool,
+) -> Result<String, Error> {
+    post_todoist_rest_with_id(config, url, body, spinner, &new_uuid()).await
+}
+
+/// Post to Todoist via REST api with a fixed X-Request-Id
+/// Todoist ignores a request it has already handled with the same ID, so resending one is safe
+pub async fn post_todoist_rest_with_id(
+    config: &Config,
+    url: String,
+    body: serde_json::Value,
+    spinner: bool,
+    request_id: &str,
 ) -> Result<String, Error> {
     let base_url = get_base_url(config);
     let token = &config.token;
@@ -65,19 +83,19 @@ pub async fn post_todoist_rest(
     let spinner = maybe_start_spinner(config, spinner);
 
     debug::print(config, format!("POST {request_url}\nbody: {body}"));
-
+    let start = Instant::now();
     let response = Client::new()
         .post(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
         .header(AUTHORIZATION, authorization)
-        .header("X-Request-Id", new_uuid())
+        .header("X-Request-Id", request_id)
         .json(&body)
         .timeout(get_timeout(config))
         .send()
         .await?;
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "POST", url, body).await
+    handle_response(config, response, "POST", url, body, start).await
 }
 
 pub async fn delete_todoist_rest(
@@ -94,7 +112,7 @@ pub async fn delete_todoist_rest(
     let spinner = maybe_start_spinner(config, spinner);
 
     debug::print(config, format!("DELETE {request_url}\nbody: {body}"));
-
+    let start = Instant::now();
     let response = Client::new()
         .delete(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
@@ -106,7 +124,7 @@ pub async fn delete_todoist_rest(
         .await?;
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "DELETE", url, body).await
+    handle_response(config, response, "DELETE", url, body, start).await
 }
 
 // Combine get and post into one function
@@ -118,10 +136,8 @@ pub async fn get_todoist_rest(config: &Config, url: String) -> Result<String, Er
     let request_url = format!("{base_url}{url}");
     let authorization: &str = &format!("Bearer {token}");
     let spinner = maybe_start_spinner(config, true);
-    if config.verbose.unwrap_or_default() {
-        println!("GET {request_url}")
-    }
     debug::print(config, format!("GET {request_url}"));
+    let start = Instant::now();
     let response = Client::new()
         .get(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
@@ -131,7 +147,7 @@ pub async fn get_todoist_rest(config: &Config, url: String) -> Result<String, Er
         .await?;
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "GET", url, json!({})).await
+    handle_response(config, response, "GET", url, json!({}), start).await
 }
 
 async fn handle_response(
@@ -140,13 +156,41 @@ async fn handle_response(
     method: &str,
     url: String,
     body: serde_json::Value,
+    start: Instant,
 ) -> Result<String, Error> {
-    if response.status().is_success() {
+    let status = response.status();
+    let elapsed = start.elapsed().as_millis();
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("html"));
+
+    if is_html {
+        debug::print(
+            config,
+            format!("{method} {url}\nstatus: {status} in {elapsed}ms\nresponse: html"),
+        );
+        Err(unexpected_response(status))
+    } else if status.is_success() {
         let text = response.text().await.unwrap();
-        debug::print(config, format!("{method} {url}\nresponse: {text}"));
+        debug::print(
+            config,
+            format!("{method} {url}\nstatus: {status} in {elapsed}ms\nresponse: {text}"),
+        );
+        if text.trim_start().starts_with('<') {
+            return Err(unexpected_response(status));
+        }
         Ok(text)
     } else {
-        Err(error::new(
+        debug::print(
+            config,
+            format!("{method} {url}\nstatus: {status} in {elapsed}ms"),
+        );
+        Err(error::with_kind(
+            Kind::Api {
+                status: status.as_u16(),
+            },
             "reqwest",
             &format!(
                 "
@@ -160,6 +204,17 @@ async fn handle_response(
     }
 }
 
+/// For HTML error pages and the like, which Todoist sends when it is having trouble
+fn unexpected_response(status: StatusCode) -> Error {
+    error::with_kind(
+        Kind::Api {
+            status: status.as_u16(),
+        },
+        "todoist",
+        &format!("Todoist returned an unexpected response ({status}), the service may be down"),
+    )
+}
+
 fn get_timeout(config: &Config) -> Duration {
     match config {
         Config {
@@ -205,20 +260,31 @@ fn maybe_start_spinner(config: &Config, spinner: bool) -> Option<Spinner> {
         return None;
     }
 
+    if show_spinner(config, spinner, std::io::stderr().is_terminal()) {
+        Some(Spinner::with_stream(
+            SPINNER,
+            MESSAGE.into(),
+            Stream::Stderr,
+        ))
+    } else {
+        None
+    }
+}
+
+/// The spinner goes to stderr and only when it is a terminal, so that piped output stays clean
+fn show_spinner(config: &Config, spinner: bool, is_terminal: bool) -> bool {
     match (env::var("DISABLE_SPINNER"), config.spinners, spinner) {
-        (Ok(_), _, _) => None,
-        (_, Some(false), _) => None,
-        (_, _, false) => None,
-        _ => {
-            let sp = Spinner::new(SPINNER, MESSAGE.into());
-            Some(sp)
-        }
+        (Ok(_), _, _) => false,
+        (_, Some(false), _) => false,
+        (_, _, false) => false,
+        _ => is_terminal && !config.args.quiet,
     }
 }
+
 fn maybe_stop_spinner(spinner: Option<Spinner>) {
     if let Some(mut sp) = spinner {
         sp.stop();
-        print!("\x1b[2K\r");
+        eprint!("\x1b[2K\r");
     };
 }
 
@@ -230,3 +296,67 @@ pub fn new_uuid() -> String {
         Uuid::new_v4().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    #[tokio::test]
+    async fn spinner_only_shows_on_a_terminal() {
+        let config = test::fixtures::config().await;
+        assert!(show_spinner(&config, true, true));
+        assert!(!show_spinner(&config, true, false));
+        assert!(!show_spinner(&config, false, true));
+
+        let quiet = Config {
+            args: Args {
+                quiet: true,
+                ..config.args.clone()
+            },
+            ..config
+        };
+        assert!(!show_spinner(&quiet, true, true));
+    }
+
+    #[tokio::test]
+    async fn html_responses_get_a_friendly_error() {
+        let mut server = mockito::Server::new_async().await;
+        let html = server
+            .mock("GET", "/rest/v2/projects")
+            .with_status(502)
+            .with_header("content-type", "text/html")
+            .with_body("<html><body>Bad Gateway</body></html>")
+            .create_async()
+            .await;
+        let unlabelled = server
+            .mock("GET", "/rest/v2/sections")
+            .with_status(200)
+            .with_body("<!DOCTYPE html><html></html>")
+            .create_async()
+            .await;
+        let config = test::fixtures::config().await.mock_url(server.url());
+
+        let result = get_todoist_rest(&config, String::from("/rest/v2/projects")).await;
+        html.assert();
+        assert_eq!(
+            result,
+            Err(error::with_kind(
+                Kind::Api { status: 502 },
+                "todoist",
+                "Todoist returned an unexpected response (502 Bad Gateway), the service may be down"
+            ))
+        );
+
+        let result = get_todoist_rest(&config, String::from("/rest/v2/sections")).await;
+        unlabelled.assert();
+        assert_eq!(
+            result,
+            Err(error::with_kind(
+                Kind::Api { status: 200 },
+                "todoist",
+                "Todoist returned an unexpected response (200 OK), the service may be down"
+            ))
+        );
+    }
+}